@@ -0,0 +1,76 @@
+use std::io::Read;
+use std::path::Path;
+
+use reqwest::header::HeaderMap;
+use reqwest::Method;
+use serde_json::Value;
+
+use ovhdata_common::ovhapi::OVHapiV6Client;
+
+use crate::config::Context;
+use crate::utils::ui::printer::{Output, Printer};
+use crate::utils::{Error, Result};
+
+// Placeholder of the API routes replaced by the current service name
+const SERVICE_NAME_PLACEHOLDER: &str = "{serviceName}";
+
+pub struct ApiCommand {
+    rcp_client: OVHapiV6Client,
+}
+
+impl ApiCommand {
+    pub fn new(rcp_client: OVHapiV6Client) -> Self {
+        Self { rcp_client }
+    }
+
+    /// Send a signed request to an API route and print the response
+    pub async fn execute(&self, method: Method, path: &str, body: Option<&Path>, query: &[(String, String)], output: Output) -> Result<()> {
+        let path = Self::resolve_path(path)?;
+        let segments = path.split('/').filter(|segment| !segment.is_empty()).collect::<Vec<&str>>();
+        let body = body.map(Self::read_body).transpose()?;
+
+        let response = self
+            .rcp_client
+            .execute(method, &segments, query, &HeaderMap::new(), body.as_ref())
+            .await?;
+
+        // Some routes answer with an empty or a non JSON body
+        let text = response.body_text().await;
+        let value = match text.is_empty() {
+            true => Value::Null,
+            false => serde_json::from_str::<Value>(&text).unwrap_or(Value::String(text)),
+        };
+
+        match output {
+            Output::Yaml => Printer::print_yaml(&value),
+            _ => Printer::print_json(&value),
+        }
+    }
+
+    /// Replace the service name placeholder of the route
+    fn resolve_path(path: &str) -> Result<String> {
+        if !path.contains(SERVICE_NAME_PLACEHOLDER) {
+            return Ok(path.to_string());
+        }
+
+        let service_name = Context::get().get_current_service_name().ok_or(Error::custom(format!(
+            "No service name to replace {} with, use the --service-name option or set one with 'config set-service-name'",
+            SERVICE_NAME_PLACEHOLDER
+        )))?;
+        Ok(path.replace(SERVICE_NAME_PLACEHOLDER, &service_name))
+    }
+
+    /// Read the JSON request body from a file, or from the standard input for '-'
+    fn read_body(file: &Path) -> Result<Value> {
+        let content = match file == Path::new("-") {
+            true => {
+                let mut content = String::new();
+                std::io::stdin().read_to_string(&mut content)?;
+                content
+            }
+            false => std::fs::read_to_string(file)?,
+        };
+
+        serde_json::from_str::<Value>(&content).map_err(|error| Error::custom(format!("Invalid JSON body in {:?}: {}", file, error)))
+    }
+}
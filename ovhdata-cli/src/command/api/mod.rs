@@ -0,0 +1,3 @@
+pub use command::*;
+
+mod command;
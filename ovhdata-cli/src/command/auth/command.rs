@@ -1,31 +1,106 @@
 use std::io::stdout;
+use std::net::Ipv4Addr;
+use std::time::Duration;
 
-use ovhdata_common::api;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::time::{sleep, timeout};
+use tracing::info;
+
+use ovhdata_common::api::{self, RetryPolicy};
+use ovhdata_common::model::auth::{AccessRule, CredentialRequest};
 use ovhdata_common::ovhapi::{AuthApi, OVHapiV6Client};
 
-use crate::config::{Config, Context, Ovhapiv6Credentials, Toggle};
+use crate::config::{Config, Context, Ovhapiv6Credentials, Toggle, CLI_NAME};
+use crate::credentials::ResolvedCredentials;
+use crate::utils::crypto::{passphrase, PASSPHRASE_ENV};
 use crate::utils::ui::printer::{Output, Printer, HELP_LOGIN_HOW_TO, HELP_LOGIN_SUCCESS};
 use crate::utils::{Error, Result};
 
+// Time given to the user to validate a new credential
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(600);
+const VALIDATION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const CREDENTIAL_VALIDATED: &str = "validated";
+
 pub struct Auth {
     config: Config,
+    retry_policy: RetryPolicy,
+    // Endpoint from the environment, the ovh.conf files or the config
+    endpoint_url: String,
 }
 
 impl Auth {
     pub fn new() -> Self {
         let config = Config::get();
-        Self { config }
+        let endpoint_url = ResolvedCredentials::resolve(&mut Context::get(), false)
+            .map(|resolved| resolved.endpoint_url.value)
+            .unwrap_or(config.ovhapiv6.endpoint_url.clone());
+        let retry_policy = config.retry.clone();
+        Self {
+            config,
+            retry_policy,
+            endpoint_url,
+        }
+    }
+
+    /// Retry the API requests according to the given policy instead of the configured one
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
     }
 
     pub async fn logout(&self) -> Result<()> {
         let mut context = Context::get();
-        context.logout();
+        context.logout()?;
         context.save()?;
 
         Printer::println_success(&mut stdout(), "You have successfully logged out!");
         Ok(())
     }
 
+    /// Print where the credentials and the service name come from
+    pub async fn sources(&self) -> Result<()> {
+        let resolved = ResolvedCredentials::resolve(&mut Context::get(), true)?;
+        Self::print_sources(&resolved);
+        Ok(())
+    }
+
+    fn print_sources(resolved: &ResolvedCredentials) {
+        for (name, source) in resolved.sources() {
+            let source = source.map(|source| source.to_string()).unwrap_or("not set".to_string());
+            println!("{:<20}{}", format!("{}:", name), source);
+        }
+    }
+
+    /// Encrypt the stored credentials with a passphrase
+    pub async fn lock(&self) -> Result<()> {
+        let mut context = Context::get();
+        if context.is_locked() {
+            return Err(Error::custom("The credentials are already encrypted"));
+        }
+        context.lock(&passphrase(true)?)?;
+        context.save()?;
+
+        Printer::println_success(
+            &mut stdout(),
+            &format!(
+                "The credentials are now encrypted, the passphrase will be asked when they are needed (or read from {})",
+                PASSPHRASE_ENV
+            ),
+        );
+        Ok(())
+    }
+
+    /// Decrypt the stored credentials
+    pub async fn unlock(&self) -> Result<()> {
+        let mut context = Context::get();
+        context.unlock()?;
+        context.save()?;
+
+        Printer::println_success(&mut stdout(), "The credentials are now stored in clear");
+        Ok(())
+    }
+
     pub async fn login(
         &self,
         application_key: Option<String>,
@@ -36,33 +111,29 @@ impl Auth {
         let interactive = application_secret.is_none() || application_key.is_none() || consumer_key.is_none();
 
         let creds = if interactive {
-            // If the config exists, test it
-            let ovhapicreds = {
-                let context = Context::get();
-                context.get_ovhapi_credentials()
-            };
+            // If the credentials exist, test them
+            let resolved = ResolvedCredentials::resolve(&mut Context::get(), true)?;
 
-            if let Some(creds) = ovhapicreds {
+            if resolved.is_complete() {
+                let creds = resolved.credentials();
                 // test connection
                 Printer::println_success(&mut stdout(), "Current connection infos...");
+                Self::print_sources(&resolved);
+
+                let creds_result = self.build_ovhapiv6_client(&creds)?.current_credential().await;
 
-                let creds_result = self.build_ovhapiv6_client(&creds).current_credential().await;
-
-                if creds_result.is_ok() {
-                    Printer::print_object(&creds_result.unwrap(), &output)?;
-                } else {
-                    let error = creds_result.err().unwrap();
-                    if let api::Error::Response(status_code, message) = error {
-                        match status_code {
-                            reqwest::StatusCode::FORBIDDEN | reqwest::StatusCode::UNAUTHORIZED => {
-                                Printer::eprintln_fail(&format!("You are not authenticated, status_code={}", status_code));
-                                Printer::eprintln_fail(&message);
-                            }
-
-                            // Propagate other errors
-                            _ => return Err(Error::DataApi(api::Error::Response(status_code, message))),
+                match creds_result {
+                    Ok(creds_details) => Printer::print_object(&creds_details, &output)?,
+                    Err(api::Error::Response(status_code, error)) => match status_code {
+                        reqwest::StatusCode::FORBIDDEN | reqwest::StatusCode::UNAUTHORIZED => {
+                            Printer::eprintln_fail(&format!("You are not authenticated, status_code={}", status_code));
+                            Printer::eprintln_fail(&error.message);
                         }
-                    }
+
+                        // Propagate other errors
+                        _ => return Err(Error::DataApi(api::Error::Response(status_code, error))),
+                    },
+                    Err(_) => {}
                 }
 
                 let confirm = Printer::confirm("Do you want to reset the current credentials?");
@@ -117,14 +188,129 @@ impl Auth {
             }
         };
 
+        self.save_credentials(creds, output, interactive).await
+    }
+
+    /// Login with a consumer key created with least privilege access rules and validated by the user in a browser
+    pub async fn login_with_new_credential(&self, application_key: Option<String>, application_secret: Option<String>, output: Output) -> Result<()> {
+        let application_key = match application_key {
+            Some(application_key) => application_key,
+            None => Printer::ask_input("Application Key", None)?,
+        };
+        let application_secret = match application_secret {
+            Some(application_secret) => application_secret,
+            None => Printer::ask_password("Application Secret", false).unwrap_or_default(),
+        };
+        if application_key.is_empty() || application_secret.is_empty() {
+            return Err(Error::UserInput);
+        }
+
+        // The user is redirected to this local listener once the consumer key is validated
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let redirection = format!("http://{}/", listener.local_addr()?);
+
+        let mut creds = Ovhapiv6Credentials {
+            application_key: Some(application_key),
+            application_secret: Some(application_secret),
+            consumer_key: Some(String::new()),
+        };
+        let request = CredentialRequest {
+            access_rules: AccessRule::data_integration(),
+            redirection: Some(redirection),
+        };
+        let validation = self.build_ovhapiv6_client(&creds)?.request_credential(&request).await?;
+        creds.consumer_key = Some(validation.consumer_key);
+
+        println!("Validate the new credential in your browser: {}", validation.validation_url);
+        if open::that(validation.validation_url.as_str()).is_err() {
+            println!("Unable to open a browser, please open the url above");
+        }
+
+        let spinner = Printer::start_spinner("Waiting for the credential validation");
+        let client = self.build_ovhapiv6_client(&creds)?;
+        let validated = timeout(VALIDATION_TIMEOUT, async {
+            // Whichever comes first: the browser redirection or a validated credential (e.g. validated on another computer)
+            tokio::select! {
+                () = Self::wait_redirection(&listener) => {},
+                () = Self::wait_validated(&client) => {},
+            }
+        })
+        .await;
+        Printer::stop_spinner(spinner);
+
+        if validated.is_err() {
+            return Err(Error::custom("The credential has not been validated in time, please login again"));
+        }
+
+        self.save_credentials(creds, output, true).await
+    }
+
+    /// Wait for the browser to be redirected to the listener, other requests and failed connections are ignored
+    async fn wait_redirection(listener: &TcpListener) {
+        loop {
+            match Self::answer_request(listener).await {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(error) => info!("Invalid request on the redirection listener: {}", error),
+            }
+        }
+    }
+
+    /// Answer the next request of the listener, true if it is the redirection
+    async fn answer_request(listener: &TcpListener) -> Result<bool> {
+        let (mut stream, _) = listener.accept().await?;
+        let mut buffer = [0u8; 4096];
+        let size = stream.read(&mut buffer).await?;
+        let request = String::from_utf8_lossy(&buffer[..size]);
+        let path = request
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("GET "))
+            .and_then(|line| line.split(' ').next());
+        if path.and_then(|path| path.split('?').next()) != Some("/") {
+            stream
+                .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .await?;
+            stream.shutdown().await?;
+            return Ok(false);
+        }
+
+        let body = format!(
+            "<html><body><h3>{} is now logged in, you can close this page.</h3></body></html>",
+            CLI_NAME
+        );
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await?;
+        Ok(true)
+    }
+
+    /// Poll the credential until the user validates it
+    async fn wait_validated(client: &OVHapiV6Client) {
+        loop {
+            sleep(VALIDATION_POLL_INTERVAL).await;
+            if let Ok(credential) = client.current_credential().await {
+                if credential.status == CREDENTIAL_VALIDATED {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Check the credentials then store them in the context
+    async fn save_credentials(&self, creds: Ovhapiv6Credentials, output: Output, interactive: bool) -> Result<()> {
         // test connection
-        let cred_details = self.build_ovhapiv6_client(&creds).current_credential().await?;
+        let cred_details = self.build_ovhapiv6_client(&creds)?.current_credential().await?;
         Printer::print_object(&cred_details, &output)?;
 
         // Store api credentials
         {
             let mut context = Context::get();
-            context.set_ovhapi_credentials(creds);
+            context.set_ovhapi_credentials(creds)?;
             context.save()?;
         }
 
@@ -138,12 +324,51 @@ impl Auth {
         Ok(())
     }
 
-    fn build_ovhapiv6_client(&self, creds: &Ovhapiv6Credentials) -> OVHapiV6Client {
-        OVHapiV6Client::new(
-            Config::get().ovhapiv6.endpoint_url.clone(),
+    /// Client with the given credentials, the network settings and the retry policy of the other commands
+    fn build_ovhapiv6_client(&self, creds: &Ovhapiv6Credentials) -> Result<OVHapiV6Client> {
+        let client = OVHapiV6Client::new(
+            self.endpoint_url.clone(),
             creds.application_key.clone().unwrap(),
             creds.application_secret.clone().unwrap(),
             creds.consumer_key.clone().unwrap(),
         )
+        .with_http_client(self.config.http.client()?)
+        .with_retry_policy(self.retry_policy.clone());
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use crate::command::auth::command::Auth;
+
+    async fn request(address: std::net::SocketAddr, request: &[u8]) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream.write_all(request).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn wait_redirection_ignores_other_requests() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let waiting = tokio::spawn(async move { Auth::wait_redirection(&listener).await });
+
+        // A connection closed without request, then a request of another path
+        drop(TcpStream::connect(address).await.unwrap());
+        assert!(request(address, b"GET /favicon.ico HTTP/1.1\r\n\r\n").await.starts_with("HTTP/1.1 404"));
+        assert!(!waiting.is_finished());
+
+        assert!(request(address, b"GET /?credentialToken=abc HTTP/1.1\r\n\r\n")
+            .await
+            .starts_with("HTTP/1.1 200"));
+        waiting.await.unwrap();
     }
 }
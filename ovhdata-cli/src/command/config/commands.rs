@@ -1,11 +1,22 @@
-use ovhdata_common::config::ConfigName;
+use std::io::stdout;
+use std::str::FromStr;
+
+use chrono::Utc;
+use crossterm::style::Stylize;
+use ovhdata_common::api::RetryPolicy;
+use ovhdata_common::config::{CacheConfig, ConfigName, ConfigOVHapiV6, HttpConfig, SchemaTypesConfig};
 use ovhdata_common::ovhapi::{OVHapiV6Client, ProjectApi};
+use ovhdata_common::utils::http::Url;
 
-use crate::config::{custom_config_path, Config, Context, SelectableItem};
-use crate::options::ConfigSubCommand;
+use crate::command::config::ProfileCommand;
+use crate::config::{custom_config_path, Config, Context, SelectableItem, CLI_NAME};
+use crate::options::{ConfigAdd, ConfigSubCommand, ProfileShim};
 use crate::utils::ui::printer::{Output, Printer};
 use crate::utils::{Error, Result};
 
+// Only authentication method of the configs
+const AUTH_METHOD_OVHAPIV6: &str = "ovhapiv6";
+
 pub struct ConfigCommand {
     rcp_client: OVHapiV6Client,
 }
@@ -21,17 +32,136 @@ impl ConfigCommand {
             ConfigSubCommand::Get(config_get) => self.get(config_get.config_name, config_get.output.unwrap_or_default().into()),
             ConfigSubCommand::Set(config_set) => self.set(config_set.config_name, config_set.output.unwrap_or_default().into()),
             ConfigSubCommand::SetServiceName(config_set_service_name) => self.set_service_name(&config_set_service_name.service_name).await,
+            other => Self::execute_local_command(other).await,
+        }
+    }
+
+    /// Execute a command that doesn't need the credentials
+    pub async fn execute_local_command(config_command: ConfigSubCommand) -> Result<()> {
+        match config_command {
+            ConfigSubCommand::Profile(ProfileShim { subcmd }) => ProfileCommand::new().execute_command(subcmd),
+            ConfigSubCommand::Add(config_add) => Self::add(config_add),
+            ConfigSubCommand::Remove(config_remove) => Self::remove(config_remove.name),
+            ConfigSubCommand::Validate(config_validate) => Self::validate(config_validate.name).await,
+            _ => Err(Error::custom("This command requires the credentials")),
+        }
+    }
+
+    /// Add a config with a custom API endpoint
+    fn add(input: ConfigAdd) -> Result<()> {
+        let config_name = ConfigName::from(input.name.clone());
+        if Config::get_all().get_config(config_name.clone()).is_some() {
+            return Err(Error::custom(format!("Configuration {} already exists", input.name)));
         }
+
+        let release_url = input.release_url.unwrap_or(Config::get().cli_release_url.clone());
+        for url in [&input.endpoint_url, &input.create_token_url, &release_url] {
+            Url::from_str(url).map_err(Error::custom)?;
+        }
+
+        let config = ovhdata_common::config::Config {
+            cli_release_url: release_url,
+            auth_method: AUTH_METHOD_OVHAPIV6.to_string(),
+            ovhapiv6: ConfigOVHapiV6 {
+                endpoint_url: input.endpoint_url.trim_end_matches('/').to_string(),
+                create_token_url: input.create_token_url,
+            },
+            retry: RetryPolicy::default(),
+            http: HttpConfig::default(),
+            cache: CacheConfig::default(),
+            schema_types: SchemaTypesConfig::default(),
+        };
+
+        let mut all_config = Config::get_all();
+        all_config.add_config(config_name, config);
+        all_config.save(custom_config_path())?;
+
+        Printer::println_success(
+            &mut stdout(),
+            &format!(
+                "Configuration {} added, check it with '{} config validate {}'",
+                input.name.clone().green(),
+                CLI_NAME,
+                input.name
+            ),
+        );
+        Ok(())
+    }
+
+    /// Remove a custom config, unless it is used
+    fn remove(name: String) -> Result<()> {
+        let config_name = ConfigName::from(name.clone());
+        if Config::is_region_default(&config_name) {
+            return Err(Error::custom(format!("Configuration {} is a default one, it can't be removed", name)));
+        }
+        if Context::get()
+            .get_profiles()
+            .iter()
+            .any(|(_, profile)| profile.config_name == config_name)
+        {
+            return Err(Error::custom(format!("Configuration {} is used by a profile", name)));
+        }
+
+        let mut all_config = Config::get_all();
+        if all_config.current_config_name == config_name {
+            return Err(Error::custom(format!("Configuration {} is the current one, set another one first", name)));
+        }
+        all_config
+            .remove_config(config_name)
+            .ok_or(Error::custom(format!("Unknown configuration {}", name)))?;
+        all_config.save(custom_config_path())?;
+
+        Printer::println_success(&mut stdout(), &format!("Configuration {} successfully removed", name.green()));
+        Ok(())
+    }
+
+    /// Check that the API endpoint answers its time
+    async fn validate(name: Option<String>) -> Result<()> {
+        let (config_name, config) = match name {
+            Some(name) => {
+                let config = Config::get_all()
+                    .get_config(name.as_str())
+                    .cloned()
+                    .ok_or(Error::custom(format!("Unknown configuration {}", name)))?;
+                (ConfigName::from(name), config)
+            }
+            None => {
+                let config = Config::get();
+                (config.name.clone(), config.config)
+            }
+        };
+        Url::from_str(&config.ovhapiv6.create_token_url).map_err(Error::custom)?;
+
+        let client = OVHapiV6Client::new(config.ovhapiv6.endpoint_url.clone(), String::new(), String::new(), String::new())
+            .with_http_client(config.http.client()?)
+            .with_retry_policy(config.retry.clone());
+        let spinner = Printer::start_spinner("Checking the API endpoint");
+        let server_time = client.server_time().await;
+        Printer::stop_spinner(spinner);
+        let time_delta = server_time? as i64 - Utc::now().timestamp();
+
+        Printer::println_success(
+            &mut stdout(),
+            &format!(
+                "Configuration {} is valid, {} answered with a clock delta of {}s",
+                config_name.as_str().green(),
+                config.ovhapiv6.endpoint_url,
+                time_delta
+            ),
+        );
+        Ok(())
     }
 
     /// List all available configurations with their names
     fn list(&self, output: Output) -> Result<()> {
+        // The config of the active profile if any
+        let current_config_name = Config::get_config_name();
         let all_config = Config::get_all();
         let all_items = all_config
             .configs
             .iter()
             .map(|(config_name, config)| {
-                let is_selected = &all_config.current_config_name == config_name;
+                let is_selected = &current_config_name == config_name;
                 SelectableItem::new(Config::new(config_name.clone(), config.clone(), None), is_selected)
             })
             .collect::<Vec<SelectableItem>>();
@@ -1,3 +1,5 @@
 pub use commands::*;
+pub use profile::*;
 
 mod commands;
+mod profile;
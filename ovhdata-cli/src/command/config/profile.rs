@@ -0,0 +1,98 @@
+use std::io::stdout;
+
+use crossterm::style::Stylize;
+use ovhdata_common::config::ConfigName;
+
+use crate::config::{Config, Context, Profile, ProfileItem, CLI_NAME};
+use crate::options::{ProfileAdd, ProfileDelete, ProfileSubCommand};
+use crate::utils::ui::printer::{Output, Printer};
+use crate::utils::{Error, Result};
+
+pub struct ProfileCommand {}
+
+impl ProfileCommand {
+    pub fn new() -> Self {
+        ProfileCommand {}
+    }
+
+    pub fn execute_command(&self, profile_command: ProfileSubCommand) -> Result<()> {
+        match profile_command {
+            ProfileSubCommand::Add(profile_add) => self.add(profile_add),
+            ProfileSubCommand::List(profile_list) => self.list(profile_list.output.unwrap_or_default().into()),
+            ProfileSubCommand::Use(profile_use) => self.use_profile(profile_use.name),
+            ProfileSubCommand::Delete(profile_delete) => self.delete(profile_delete),
+        }
+    }
+
+    /// Add a profile on a region config
+    fn add(&self, input: ProfileAdd) -> Result<()> {
+        let config_name = input.config.map(ConfigName::from).unwrap_or(Config::get_config_name());
+        if Config::get_all().get_config(config_name.clone()).is_none() {
+            return Err(Error::custom("Unknown configuration"));
+        }
+
+        let mut context = Context::get();
+        context.add_profile(input.name.clone(), Profile { config_name })?;
+        context.save()?;
+
+        Printer::println_success(
+            &mut stdout(),
+            &format!(
+                "Profile {} added, login with '{} --profile {} login'",
+                input.name.clone().green(),
+                CLI_NAME,
+                input.name
+            ),
+        );
+        Ok(())
+    }
+
+    /// List the profiles, the active one highlighted
+    fn list(&self, output: Output) -> Result<()> {
+        let context = Context::get();
+        let items = context
+            .get_profiles()
+            .into_iter()
+            .map(|(name, profile)| ProfileItem {
+                service_name: context.get_profile_service_name(&name),
+                is_current: context.get_active_profile() == Some(&name),
+                config_name: profile.config_name,
+                name,
+            })
+            .collect::<Vec<ProfileItem>>();
+        Printer::print_list(&items, &output)?;
+        Ok(())
+    }
+
+    /// Set the profile used by the next commands
+    fn use_profile(&self, name: String) -> Result<()> {
+        let mut context = Context::get();
+        context.use_profile(name.clone())?;
+        context.save()?;
+
+        Printer::println_success(&mut stdout(), &format!("Profile {} is now used", name.green()));
+        Ok(())
+    }
+
+    /// Delete a profile and its credentials
+    fn delete(&self, input: ProfileDelete) -> Result<()> {
+        let mut context = Context::get();
+        context.get_profile(&input.name)?;
+
+        if !input.script {
+            let message = format!(
+                "Are you sure you want to delete the profile {} and its credentials ?",
+                input.name.clone().green()
+            );
+            if Printer::confirm(&message).is_err() {
+                return Err(Error::custom("Delete profile canceled"));
+            }
+        }
+
+        context.delete_profile(&input.name)?;
+        context.save()?;
+
+        Printer::println_success(&mut stdout(), &format!("Profile {} successfully deleted", input.name.green()));
+        Ok(())
+    }
+}
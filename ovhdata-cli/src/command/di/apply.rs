@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::io::{stdout, Read};
+use std::path::{Path, PathBuf};
+
+use crossterm::style::Stylize;
+use ovhdata_common::model::di::manifest::{Action, Change, Manifest, Plan, ResourceKind};
+use ovhdata_common::model::utils::Pagination;
+use ovhdata_common::ovhapi::DiApi;
+use ovhdata_common::utils::reference::ValueReference;
+use ovhdata_common::BUG;
+
+use crate::config::Context;
+use crate::options::DiApply;
+use crate::utils::ui::printer::Printer;
+use crate::utils::{Error, Result};
+
+pub struct ApplyCommand<C: DiApi> {
+    rcp_client: C,
+}
+
+impl<C: DiApi + Clone> ApplyCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
+        Self { rcp_client }
+    }
+
+    /// Print the changes needed to match the manifests, then apply them
+    pub async fn execute(&self, input: &DiApply) -> Result<()> {
+        let service_name = Context::get().get_current_service_name().unwrap();
+        self.apply(&service_name, input).await
+    }
+
+    /// Apply the manifests on the given service
+    async fn apply(&self, service_name: &str, input: &DiApply) -> Result<()> {
+        let mut manifest = Manifest::default();
+        for file in &input.files {
+            for file in Self::manifest_files(file)? {
+                manifest.extend(Self::read_manifest(&file)?);
+            }
+        }
+        manifest.resolve_references(ValueReference::resolve).map_err(Error::custom)?;
+
+        let spinner = Printer::start_spinner("Comparing with the existing resources");
+        let pagination = Pagination::default();
+        let existing = tokio::try_join!(
+            self.rcp_client.di_sources(service_name, None, &pagination),
+            self.rcp_client.di_destinations(service_name, None),
+            self.rcp_client.di_workflows(service_name, None, &pagination),
+        );
+        Printer::stop_spinner(spinner);
+        let (sources, destinations, workflows) = existing?;
+
+        let plan = manifest
+            .plan(&sources, &destinations, &workflows, input.force_secrets)
+            .map_err(Error::custom)?;
+        Self::print_plan(&plan);
+
+        if plan.change_count() == 0 || input.dry_run {
+            return Ok(());
+        }
+        if !input.script {
+            Printer::confirm("Apply these changes ?")?;
+        }
+
+        // IDs of the sources and destinations, by name, for the workflows to reference them
+        let mut source_ids = sources
+            .into_iter()
+            .map(|source| (source.name, source.id))
+            .collect::<HashMap<String, String>>();
+        let mut destination_ids = destinations
+            .into_iter()
+            .map(|destination| (destination.name, destination.id))
+            .collect::<HashMap<String, String>>();
+
+        for change in &plan.sources {
+            let spec = change.manifest.source_spec();
+            let source = match &change.action {
+                Action::Create => self.rcp_client.di_source_post(service_name, &spec).await?,
+                Action::Update(id, _) => self.rcp_client.di_source_update(service_name, id, &spec).await?,
+                Action::Unchanged(_) => continue,
+            };
+            Self::print_applied(ResourceKind::Source, &change.manifest.name, &change.action);
+            source_ids.insert(source.name, source.id);
+        }
+
+        for change in &plan.destinations {
+            let spec = change.manifest.destination_spec();
+            let destination = match &change.action {
+                Action::Create => self.rcp_client.di_destination_post(service_name, &spec).await?,
+                Action::Update(id, _) => self.rcp_client.di_destination_update(service_name, id, &spec).await?,
+                Action::Unchanged(_) => continue,
+            };
+            Self::print_applied(ResourceKind::Destination, &change.manifest.name, &change.action);
+            destination_ids.insert(destination.name, destination.id);
+        }
+
+        for change in &plan.workflows {
+            let manifest = &change.manifest;
+            match &change.action {
+                Action::Create => {
+                    // The plan checked that the references exist or were created above
+                    let source_id = source_ids.get(&manifest.source).expect(BUG).clone();
+                    let destination_id = destination_ids.get(&manifest.destination).expect(BUG).clone();
+                    let spec = manifest.spec(source_id, destination_id);
+                    self.rcp_client.di_workflow_post(service_name, &spec).await?
+                }
+                Action::Update(id, _) => self.rcp_client.di_workflow_put(service_name, id, &manifest.patch()).await?,
+                Action::Unchanged(_) => continue,
+            };
+            Self::print_applied(ResourceKind::Workflow, &manifest.name, &change.action);
+        }
+        Ok(())
+    }
+
+    /// The given file, or the YAML files of a directory and its sub directories, sorted
+    fn manifest_files(path: &Path) -> Result<Vec<PathBuf>> {
+        if !path.is_dir() {
+            return Ok(vec![path.to_path_buf()]);
+        }
+
+        let mut files = vec![];
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            match path.extension().and_then(|extension| extension.to_str()) {
+                _ if path.is_dir() => files.extend(Self::manifest_files(&path)?),
+                Some("yaml" | "yml") => files.push(path),
+                _ => {}
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Read a YAML manifest from a file, or from the standard input for '-'
+    fn read_manifest(file: &Path) -> Result<Manifest> {
+        let content = match file == Path::new("-") {
+            true => {
+                let mut content = String::new();
+                std::io::stdin().read_to_string(&mut content)?;
+                content
+            }
+            false => std::fs::read_to_string(file).map_err(|error| Error::custom(format!("Unable to read {:?}: {}", file, error)))?,
+        };
+
+        serde_yaml::from_str::<Manifest>(&content).map_err(|error| Error::custom(format!("Invalid manifest {:?}: {}", file, error)))
+    }
+
+    fn print_plan(plan: &Plan) {
+        println!("Plan:");
+        Self::print_changes(ResourceKind::Source, &plan.sources, |manifest| &manifest.name);
+        Self::print_changes(ResourceKind::Destination, &plan.destinations, |manifest| &manifest.name);
+        Self::print_changes(ResourceKind::Workflow, &plan.workflows, |manifest| &manifest.name);
+
+        match plan.change_count() {
+            0 => println!("Nothing to apply, the resources match the manifests"),
+            count => println!("{} resource(s) to create or update", count),
+        }
+    }
+
+    fn print_changes<T>(kind: ResourceKind, changes: &[Change<T>], name: impl Fn(&T) -> &str) {
+        for change in changes {
+            let name = name(&change.manifest);
+            match &change.action {
+                Action::Create => println!("  {} create {} {}", "+".green(), kind, name.green()),
+                Action::Update(_, fields) => println!("  {} update {} {} ({})", "~".yellow(), kind, name.yellow(), fields.join(", ")),
+                Action::Unchanged(_) => println!("  {} {} {} unchanged", "=".dark_grey(), kind, name),
+            }
+        }
+    }
+
+    fn print_applied(kind: ResourceKind, name: &str, action: &Action) {
+        let done = match action {
+            Action::Create => "created",
+            _ => "updated",
+        };
+        Printer::println_success(&mut stdout(), &format!("The {} {} was {}", kind, name.green(), done));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ovhdata_common::model::utils::Pagination;
+    use ovhdata_common::ovhapi::{DiApi, FakeOVHapiClient};
+
+    use crate::command::di::apply::ApplyCommand;
+    use crate::options::DiApply;
+
+    const SERVICE_NAME: &str = "service-name";
+
+    const MANIFEST: &str = r#"
+sources:
+  - name: my-bucket
+    connectorId: source-connector
+    parameters:
+      bucket: data
+destinations:
+  - name: my-lake
+    connectorId: destination-connector
+workflows:
+  - name: daily-import
+    region: GRA
+    source: my-bucket
+    destination: my-lake
+    schedule: "0 1 * * *"
+"#;
+
+    #[tokio::test]
+    async fn apply_manifest() {
+        let client = FakeOVHapiClient::new().with_sample_connectors();
+        let command = ApplyCommand::new(client.clone());
+        let file = std::env::temp_dir().join(format!("ovhdata-manifest-{}.yaml", uuid::Uuid::new_v4()));
+        let input = |dry_run: bool| DiApply {
+            files: vec![file.clone()],
+            dry_run,
+            force_secrets: false,
+            script: true,
+        };
+
+        std::fs::write(&file, MANIFEST).unwrap();
+        command.apply(SERVICE_NAME, &input(true)).await.unwrap();
+        assert!(client.di_sources(SERVICE_NAME, None, &Pagination::default()).await.unwrap().is_empty());
+
+        command.apply(SERVICE_NAME, &input(false)).await.unwrap();
+        let workflows = client.di_workflows(SERVICE_NAME, None, &Pagination::default()).await.unwrap();
+        assert_eq!(workflows.len(), 1);
+        assert_eq!(workflows[0].source_name.as_deref(), Some("my-bucket"));
+        assert_eq!(workflows[0].destination_name.as_deref(), Some("my-lake"));
+
+        // Applied again, only the changed workflow is updated
+        std::fs::write(&file, MANIFEST.replace("0 1 * * *", "0 2 * * *")).unwrap();
+        let applied = command.apply(SERVICE_NAME, &input(false)).await;
+        std::fs::remove_file(&file).unwrap();
+        applied.unwrap();
+
+        let workflow = client.di_workflow(SERVICE_NAME, &workflows[0].id).await.unwrap();
+        assert_eq!(workflow.schedule.as_deref(), Some("0 2 * * *"));
+        assert_eq!(client.di_sources(SERVICE_NAME, None, &Pagination::default()).await.unwrap().len(), 1);
+    }
+}
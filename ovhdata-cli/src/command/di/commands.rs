@@ -1,21 +1,34 @@
+use crate::command::di::apply::ApplyCommand;
+use crate::command::di::connector_diff::ComparedCatalog;
 use crate::command::di::destination::DestinationCommand;
 use crate::command::di::destination_connector::DestinationConnectorCommand;
+use crate::command::di::export::ExportCommand;
 use crate::command::di::job::JobCommand;
 use crate::command::di::source::SourceCommand;
 use crate::command::di::source_connector::SourceConnectorCommand;
 use crate::command::di::workflow::WorkflowCommand;
 use crate::options::DiSubCommands;
 use crate::utils::Result;
-use ovhdata_common::ovhapi::OVHapiV6Client;
+use ovhdata_common::ovhapi::DiApi;
 use tracing::info;
 
-pub struct DiCommand {
-    rcp_client: OVHapiV6Client,
+pub struct DiCommand<C: DiApi> {
+    rcp_client: C,
+    compared: Option<ComparedCatalog<C>>,
 }
 
-impl DiCommand {
-    pub fn new(rcp: OVHapiV6Client) -> Self {
-        DiCommand { rcp_client: rcp }
+impl<C: DiApi + Clone> DiCommand<C> {
+    pub fn new(rcp: C) -> Self {
+        DiCommand {
+            rcp_client: rcp,
+            compared: None,
+        }
+    }
+
+    /// Catalog of another config, compared by the connector diff commands
+    pub fn with_compared_catalog(mut self, compared: Option<ComparedCatalog<C>>) -> Self {
+        self.compared = compared;
+        self
     }
 
     pub async fn execute_command(&self, di_commands: DiSubCommands) -> Result<()> {
@@ -23,10 +36,22 @@ impl DiCommand {
         match di_commands {
             DiSubCommands::Source(subcmd) => SourceCommand::new(self.rcp_client.clone()).execute_command(subcmd).await,
             DiSubCommands::Destination(subcmd) => DestinationCommand::new(self.rcp_client.clone()).execute_command(subcmd).await,
-            DiSubCommands::SourceConnector(subcmd) => SourceConnectorCommand::new(self.rcp_client.clone()).execute_command(subcmd).await,
-            DiSubCommands::DestinationConnector(subcmd) => DestinationConnectorCommand::new(self.rcp_client.clone()).execute_command(subcmd).await,
+            DiSubCommands::SourceConnector(subcmd) => {
+                SourceConnectorCommand::new(self.rcp_client.clone())
+                    .with_compared_catalog(self.compared.clone())
+                    .execute_command(subcmd)
+                    .await
+            }
+            DiSubCommands::DestinationConnector(subcmd) => {
+                DestinationConnectorCommand::new(self.rcp_client.clone())
+                    .with_compared_catalog(self.compared.clone())
+                    .execute_command(subcmd)
+                    .await
+            }
             DiSubCommands::Workflow(subcmd) => WorkflowCommand::new(self.rcp_client.clone()).execute_command(subcmd).await,
             DiSubCommands::Job(subcmd) => JobCommand::new(self.rcp_client.clone()).execute_command(subcmd).await,
+            DiSubCommands::Apply(apply) => ApplyCommand::new(self.rcp_client.clone()).execute(&apply).await,
+            DiSubCommands::Export(export) => ExportCommand::new(self.rcp_client.clone()).execute(&export).await,
         }
     }
 }
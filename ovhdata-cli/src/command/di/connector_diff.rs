@@ -0,0 +1,73 @@
+use std::io::stderr;
+
+use ovhdata_common::config::ConfigName;
+use ovhdata_common::model::di::connector_diff::{diff_connectors, CatalogConnector, ConnectorChange};
+use ovhdata_common::utils::snapshot::{SnapshotError, SnapshotStore};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::{snapshot_dir, Config};
+use crate::options::ConnectorDiff;
+use crate::utils::ui::printer::{Output, Printer};
+use crate::utils::{Error, Result};
+
+/// Client and service name of another config, whose connector catalog is compared with the current one
+#[derive(Clone)]
+pub struct ComparedCatalog<C> {
+    pub client: C,
+    pub config_name: ConfigName,
+    pub service_name: String,
+}
+
+/// Compare a connector catalog with a snapshot of it, or with the catalog of another config.
+/// The changes since the snapshot are listed, or the changes from the current config to the other one.
+/// The catalog is named after its snapshot directory, e.g. source-connectors.
+pub fn diff_catalog<T>(
+    input: &ConnectorDiff,
+    catalog: &str,
+    service_name: &str,
+    connectors: &[T],
+    compared: Option<(&ConfigName, Vec<T>)>,
+    output: Output,
+) -> Result<()>
+where
+    T: CatalogConnector + Serialize + DeserializeOwned,
+{
+    let snapshots = SnapshotStore::new(snapshot_dir(&Config::get().name).join(service_name).join(catalog));
+
+    let (against, changes) = match compared {
+        Some((config_name, compared_connectors)) => (
+            format!("config {}", config_name.as_str()),
+            diff_connectors(connectors, &compared_connectors),
+        ),
+        None => match snapshots.load::<Vec<T>>(&input.against) {
+            Ok((name, snapshot)) => (format!("snapshot {}", name), diff_connectors(&snapshot.content, connectors)),
+            // The first snapshot is the reference of the next comparisons
+            Err(SnapshotError::NoSnapshot(_)) if input.save => {
+                let name = snapshots.save(&connectors)?;
+                Printer::println_success(&mut stderr(), &format!("Nothing to compare with yet, snapshot {} saved", name));
+                return Ok(());
+            }
+            Err(error) => return Err(error.into()),
+        },
+    };
+
+    if changes.is_empty() {
+        Printer::println_success(&mut stderr(), &format!("No change compared with the {}", against));
+    }
+    Printer::print_list::<ConnectorChange>(changes.as_slice(), &output)?;
+
+    if input.save {
+        let name = snapshots.save(&connectors)?;
+        Printer::println_success(&mut stderr(), &format!("Snapshot {} saved", name));
+    }
+
+    let breaking = changes.iter().filter(|change| change.breaking).count();
+    match breaking {
+        0 => Ok(()),
+        _ => Err(Error::BreakingChanges(
+            format!("the {} compared with the {}", catalog.replace('-', " "), against),
+            breaking,
+        )),
+    }
+}
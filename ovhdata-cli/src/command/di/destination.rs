@@ -4,19 +4,20 @@ use std::io::stdout;
 use ovhdata_common::model::di::common::ParametersWrapper;
 use ovhdata_common::model::di::destination::DestinationSpec;
 use ovhdata_common::model::utils::sort_dest;
-use ovhdata_common::ovhapi::{DiApi, OVHapiV6Client};
+use ovhdata_common::ovhapi::DiApi;
 
 use crate::config::Context;
 use crate::options::{DestCreate, DestDelete, DestGet, DestList, DestUpdate, DiSubDestCommands};
+use crate::utils::parameters::input_parameters;
 use crate::utils::ui::printer::{Output, Printer};
 use crate::utils::{Error, Result};
 
-pub struct DestinationCommand {
-    rcp_client: OVHapiV6Client,
+pub struct DestinationCommand<C: DiApi> {
+    rcp_client: C,
 }
 
-impl DestinationCommand {
-    pub fn new(rcp_client: OVHapiV6Client) -> Self {
+impl<C: DiApi + Clone> DestinationCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
         Self { rcp_client }
     }
 
@@ -103,7 +104,9 @@ impl DestinationCommand {
 
         let connector = self.rcp_client.clone().di_destination_connector(&service_name, &connector_id).await?;
 
-        let parameters = Printer::ask_connector_parameters(&input.parameters, None, &connector.parameters).unwrap();
+        let input_parameters = input_parameters(&input.parameters, input.parameters_file.as_deref())?;
+
+        let parameters = Printer::ask_connector_parameters(&input_parameters, None, &connector.parameters)?;
         let parameters_len = parameters.len();
 
         // Default values will be overridden
@@ -113,8 +116,8 @@ impl DestinationCommand {
             connector_id: Some(connector_id.clone()),
         };
 
-        // new parameters we are in interactive mode
-        if input.connector_id.is_none() || parameters_len > input.parameters.len() {
+        // parameters were asked, we are in interactive mode
+        if input.connector_id.is_none() || (input_parameters.is_empty() && parameters_len > 0) {
             Printer::print_object(&spec, &output)?;
             let message = format!("Do you want to create the destination {} ?", input.name.clone());
             let confirm = Printer::confirm(&message);
@@ -161,7 +164,9 @@ impl DestinationCommand {
 
         // Update connector parameters with the
 
-        let parameters = Printer::ask_connector_parameters(&input.parameters, Some(&destination.parameters), &connector.parameters).unwrap();
+        let input_parameters = input_parameters(&input.parameters, input.parameters_file.as_deref())?;
+
+        let parameters = Printer::ask_connector_parameters(&input_parameters, Some(&destination.parameters), &connector.parameters)?;
         let parameters_len = parameters.len();
 
         // Default values will be overridden
@@ -171,8 +176,8 @@ impl DestinationCommand {
             connector_id: None,
         };
 
-        // new parameters we are in interactive mode
-        if interactive || parameters_len > input.parameters.len() {
+        // parameters were asked, we are in interactive mode
+        if interactive || (input_parameters.is_empty() && parameters_len > 0) {
             Printer::print_object(&spec, &output)?;
             let confirm = Printer::confirm(&format!("Do you want to update the destination {} ?", id));
 
@@ -1,23 +1,32 @@
-use ovhdata_common::ovhapi::{DiApi, OVHapiV6Client};
+use ovhdata_common::ovhapi::DiApi;
 
+use crate::command::di::connector_diff::{diff_catalog, ComparedCatalog};
 use crate::config::Context;
-use crate::options::{DestConnectorGet, DiSubDestConnectorCommands};
+use crate::options::{ConnectorDiff, DestConnectorGet, DiSubDestConnectorCommands};
 use crate::utils::ui::printer::{Output, Printer};
 use crate::utils::Result;
 
-pub struct DestinationConnectorCommand {
-    rcp_client: OVHapiV6Client,
+pub struct DestinationConnectorCommand<C: DiApi> {
+    rcp_client: C,
+    compared: Option<ComparedCatalog<C>>,
 }
 
-impl DestinationConnectorCommand {
-    pub fn new(rcp_client: OVHapiV6Client) -> Self {
-        Self { rcp_client }
+impl<C: DiApi + Clone> DestinationConnectorCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
+        Self { rcp_client, compared: None }
+    }
+
+    /// Catalog of another config to compare with
+    pub fn with_compared_catalog(mut self, compared: Option<ComparedCatalog<C>>) -> Self {
+        self.compared = compared;
+        self
     }
 
     pub async fn execute_command(&self, connector_commands: DiSubDestConnectorCommands) -> Result<()> {
         match connector_commands {
             DiSubDestConnectorCommands::List(list) => self.list_destination_connectors(list.output.unwrap_or_default().into()).await,
             DiSubDestConnectorCommands::Get(get) => self.get_destination_connector(&get, get.output.unwrap_or_default().into()).await,
+            DiSubDestConnectorCommands::Diff(diff) => self.diff(&diff, diff.output.unwrap_or_default().into()).await,
         }
     }
 
@@ -48,4 +57,21 @@ impl DestinationConnectorCommand {
         Printer::print_object(&connector, &output)?;
         Ok(())
     }
+
+    async fn diff(&self, input: &ConnectorDiff, output: Output) -> Result<()> {
+        let service_name = Context::get().get_current_service_name().unwrap();
+
+        let spinner = Printer::start_spinner("Retrieving connectors");
+        let connectors = self.rcp_client.clone().di_destination_connectors(&service_name).await?;
+        let compared = match &self.compared {
+            Some(compared) => Some((
+                &compared.config_name,
+                compared.client.clone().di_destination_connectors(&compared.service_name).await?,
+            )),
+            None => None,
+        };
+        Printer::stop_spinner(spinner);
+
+        diff_catalog(input, "destination-connectors", &service_name, &connectors, compared, output)
+    }
 }
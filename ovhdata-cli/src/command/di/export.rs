@@ -0,0 +1,173 @@
+use std::collections::{HashMap, HashSet};
+use std::io::stdout;
+use std::path::Path;
+
+use crossterm::style::Stylize;
+use ovhdata_common::model::di::manifest::Manifest;
+use ovhdata_common::model::utils::Pagination;
+use ovhdata_common::ovhapi::DiApi;
+use serde::Serialize;
+
+use crate::config::Context;
+use crate::options::DiExport;
+use crate::utils::ui::printer::Printer;
+use crate::utils::{Error, Result};
+
+pub struct ExportCommand<C: DiApi> {
+    rcp_client: C,
+}
+
+impl<C: DiApi + Clone> ExportCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
+        Self { rcp_client }
+    }
+
+    /// Write the DI resources of the service as manifests that `di apply` can read back
+    pub async fn execute(&self, input: &DiExport) -> Result<()> {
+        let service_name = Context::get().get_current_service_name().unwrap();
+
+        let spinner = Printer::start_spinner("Reading the DI resources");
+        let pagination = Pagination::default();
+        let resources = tokio::try_join!(
+            self.rcp_client.di_sources(&service_name, None, &pagination),
+            self.rcp_client.di_destinations(&service_name, None),
+            self.rcp_client.di_workflows(&service_name, None, &pagination),
+            self.rcp_client.di_source_connectors(&service_name),
+            self.rcp_client.di_destination_connectors(&service_name),
+        );
+        Printer::stop_spinner(spinner);
+        let (sources, destinations, workflows, source_connectors, destination_connectors) = resources?;
+
+        // Secret parameters names, by connector ID
+        let secrets = source_connectors
+            .iter()
+            .map(|connector| (&connector.id, &connector.parameters))
+            .chain(destination_connectors.iter().map(|connector| (&connector.id, &connector.parameters)))
+            .map(|(id, parameters)| {
+                let names = parameters
+                    .iter()
+                    .filter(|parameter| parameter.is_secret())
+                    .map(|parameter| parameter.name.as_str())
+                    .collect::<Vec<&str>>();
+                (id.as_str(), names)
+            })
+            .collect::<HashMap<&str, Vec<&str>>>();
+
+        let manifest = Manifest::export(&sources, &destinations, &workflows, |connector_id, parameter| {
+            secrets
+                .get(connector_id)
+                .map(|names| names.contains(&parameter.name.as_str()))
+                .unwrap_or(false)
+        });
+
+        match (&input.dir, &input.file) {
+            (Some(dir), _) => Self::write_resources(dir, manifest),
+            (_, Some(file)) => {
+                std::fs::write(file, to_yaml(&manifest)?)?;
+                Printer::println_success(&mut stdout(), &format!("Manifest written in {}", file.display().to_string().green()));
+                Ok(())
+            }
+            _ => {
+                print!("{}", to_yaml(&manifest)?);
+                Ok(())
+            }
+        }
+    }
+
+    /// Write one manifest per resource, in a sub directory per kind of resource
+    fn write_resources(dir: &Path, manifest: Manifest) -> Result<()> {
+        let mut files = vec![];
+        let mut used = HashSet::new();
+        for source in manifest.sources {
+            let name = source.name.clone();
+            let manifest = Manifest {
+                sources: vec![source],
+                ..Manifest::default()
+            };
+            files.push((dir.join("sources").join(file_name("sources", &name, &mut used)), manifest));
+        }
+        for destination in manifest.destinations {
+            let name = destination.name.clone();
+            let manifest = Manifest {
+                destinations: vec![destination],
+                ..Manifest::default()
+            };
+            files.push((dir.join("destinations").join(file_name("destinations", &name, &mut used)), manifest));
+        }
+        for workflow in manifest.workflows {
+            let name = workflow.name.clone();
+            let manifest = Manifest {
+                workflows: vec![workflow],
+                ..Manifest::default()
+            };
+            files.push((dir.join("workflows").join(file_name("workflows", &name, &mut used)), manifest));
+        }
+
+        for (file, manifest) in &files {
+            if let Some(parent) = file.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(file, to_yaml(manifest)?)?;
+        }
+        Printer::println_success(
+            &mut stdout(),
+            &format!("{} manifests written in {}", files.len(), dir.display().to_string().green()),
+        );
+        Ok(())
+    }
+}
+
+/// YAML with the keys sorted, so that exports can be compared
+fn to_yaml(value: &impl Serialize) -> Result<String> {
+    let value = serde_json::to_value(value).map_err(Error::custom)?;
+    serde_yaml::to_string(&value).map_err(Error::custom)
+}
+
+/// Manifest file of a resource, named after it.
+/// A suffix is added when another resource of the kind has the same file name, ignoring the case for the case insensitive file systems.
+fn file_name(kind: &str, name: &str, used: &mut HashSet<String>) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+
+    let mut file_name = format!("{}.yaml", name);
+    let mut count = 1;
+    while !used.insert(format!("{}/{}", kind, file_name.to_lowercase())) {
+        count += 1;
+        file_name = format!("{}-{}.yaml", name, count);
+    }
+    file_name
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::command::di::export::file_name;
+
+    #[test]
+    fn unique_file_names() {
+        let mut used = HashSet::new();
+        let names = ["my source", "my_source", "My_Source", "my_source-2", "daily"]
+            .iter()
+            .map(|name| file_name("sources", name, &mut used))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "my_source.yaml",
+                "my_source-2.yaml",
+                "My_Source-3.yaml",
+                "my_source-2-2.yaml",
+                "daily.yaml"
+            ]
+        );
+
+        // Each kind of resource has its own directory
+        assert_eq!(file_name("workflows", "daily", &mut used), "daily.yaml");
+    }
+}
@@ -1,19 +1,21 @@
 use crossterm::style::Stylize;
-use ovhdata_common::model::utils::sort_job;
-use ovhdata_common::ovhapi::{DiApi, OVHapiV6Client};
+use ovhdata_common::model::di::job::{Job, JobOutcome};
+use ovhdata_common::model::utils::{sort_job, Pagination};
+use ovhdata_common::ovhapi::DiApi;
 use std::io::stdout;
+use std::time::{Duration, Instant};
 
 use crate::config::Context;
-use crate::options::{DiSubJobCommands, JobGet, JobList, JobStop};
+use crate::options::{DiSubJobCommands, JobGet, JobList, JobStop, JobWait, WaitOptions};
 use crate::utils::ui::printer::{Output, Printer};
-use crate::utils::Result;
+use crate::utils::{Error, Result};
 
-pub struct JobCommand {
-    rcp_client: OVHapiV6Client,
+pub struct JobCommand<C: DiApi> {
+    rcp_client: C,
 }
 
-impl JobCommand {
-    pub fn new(rcp_client: OVHapiV6Client) -> Self {
+impl<C: DiApi + Clone> JobCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
         Self { rcp_client }
     }
 
@@ -22,6 +24,7 @@ impl JobCommand {
             DiSubJobCommands::List(job_list) => self.list(&job_list, job_list.output.unwrap_or_default().into()).await,
             DiSubJobCommands::Get(job_get) => self.get(&job_get, job_get.output.unwrap_or_default().into()).await,
             DiSubJobCommands::Stop(job_stop) => self.stop(&job_stop).await,
+            DiSubJobCommands::Wait(job_wait) => self.wait(&job_wait, job_wait.output.unwrap_or_default().into()).await,
         }
     }
 
@@ -30,7 +33,7 @@ impl JobCommand {
         let interactive = input.workflow_id.is_none();
 
         let workflow_id = if interactive {
-            let workflows = self.rcp_client.clone().di_workflows(&service_name, None).await?;
+            let workflows = self.rcp_client.clone().di_workflows(&service_name, None, &Pagination::default()).await?;
             Printer::ask_select_table(&workflows, None)?.id.clone()
         } else {
             input.workflow_id.clone().unwrap()
@@ -40,7 +43,16 @@ impl JobCommand {
             Printer::print_command(&format!("di job list --service-name {} --workflow-id {}", &service_name, &workflow_id));
         }
 
-        let mut jobs = self.rcp_client.clone().di_jobs(&service_name, &workflow_id, input.filter.clone()).await?;
+        let mut jobs = self
+            .rcp_client
+            .clone()
+            .di_jobs(
+                &service_name,
+                &workflow_id,
+                input.filter.clone(),
+                &Pagination::new(input.page_size, input.limit),
+            )
+            .await?;
 
         if output == Output::default_table() && !jobs.is_empty() {
             jobs = sort_job(jobs, input.sort.clone().unwrap_or_default().as_str(), input.desc);
@@ -93,12 +105,80 @@ impl JobCommand {
         Ok(())
     }
 
+    async fn wait(&self, input: &JobWait, output: Output) -> Result<()> {
+        let service_name = Context::get().get_current_service_name().unwrap();
+
+        let (workflow_id, id) = self.get_ids(&service_name, &input.workflow_id, &input.id).await?;
+
+        if input.id.is_none() {
+            Printer::print_command(&format!(
+                "di job wait {} --service-name {} --workflow-id {}",
+                &id, &service_name, &workflow_id
+            ));
+        }
+
+        self.wait_job(&service_name, &workflow_id, &id, &input.wait_options, output).await
+    }
+
+    /// Poll a job until it ends, then print it
+    /// A job not completed is an error, with its own exit code
+    pub async fn wait_job(&self, service_name: &str, workflow_id: &str, id: &str, options: &WaitOptions, output: Output) -> Result<()> {
+        let timeout = options.timeout.map(Duration::from_secs);
+        let poll_interval = Duration::from_secs(options.poll_interval);
+        let start = Instant::now();
+
+        let mut spinner = None;
+        let mut status = String::new();
+        let job = loop {
+            let job = match self.rcp_client.clone().di_job(service_name, workflow_id, id).await {
+                Ok(job) => job,
+                Err(error) => {
+                    Printer::stop_spinner(spinner);
+                    return Err(error.into());
+                }
+            };
+
+            // The spinner timer shows the time spent in the current status
+            if job.status != status {
+                Printer::stop_spinner(spinner.take());
+                spinner = Printer::start_timer_spinner(&format!("Job {} {}", id, job.status));
+                status = job.status.clone();
+            }
+
+            if job.outcome().is_some() {
+                break job;
+            }
+
+            let remaining = match timeout {
+                Some(timeout) if start.elapsed() >= timeout => {
+                    Printer::stop_spinner(spinner);
+                    return Err(Error::JobTimeout(id.to_string(), job.status, timeout.as_secs()));
+                }
+                Some(timeout) => timeout - start.elapsed(),
+                None => poll_interval,
+            };
+            tokio::time::sleep(poll_interval.min(remaining)).await;
+        };
+        Printer::stop_spinner(spinner);
+
+        Printer::print_object(&job, &output)?;
+        Self::job_result(job)
+    }
+
+    fn job_result(job: Job) -> Result<()> {
+        match job.outcome() {
+            Some(JobOutcome::Completed) => Ok(()),
+            Some(JobOutcome::Interrupted) => Err(Error::JobInterrupted(job.id)),
+            _ => Err(Error::JobFailed(job.id, job.status)),
+        }
+    }
+
     async fn get_ids(&self, service_name: &str, input_workflow_id: &Option<String>, input_id: &Option<String>) -> Result<(String, String)> {
         let missing_workflow = input_workflow_id.is_none();
         let mut missing_job = input_id.is_none();
 
         let workflow_id = if missing_workflow {
-            let workflows = self.rcp_client.clone().di_workflows(service_name, None).await?;
+            let workflows = self.rcp_client.clone().di_workflows(service_name, None, &Pagination::default()).await?;
             missing_job = true;
 
             Printer::ask_select_table(&workflows, None)?.id.clone()
@@ -107,7 +187,11 @@ impl JobCommand {
         };
 
         let id = if missing_job {
-            let jobs = self.rcp_client.clone().di_jobs(service_name, &workflow_id, None).await?;
+            let jobs = self
+                .rcp_client
+                .clone()
+                .di_jobs(service_name, &workflow_id, None, &Pagination::default())
+                .await?;
 
             Printer::ask_select_table(&jobs, input_id.clone())?.id.clone()
         } else {
@@ -117,3 +201,67 @@ impl JobCommand {
         Ok((workflow_id, id))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ovhdata_common::model::di::destination::DestinationSpec;
+    use ovhdata_common::model::di::source::SourceSpec;
+    use ovhdata_common::model::di::workflow::{JobPost, WorkflowSpec};
+    use ovhdata_common::ovhapi::{DiApi, FakeOVHapiClient, FAKE_DESTINATION_CONNECTOR_ID, FAKE_SOURCE_CONNECTOR_ID};
+
+    use crate::command::di::job::JobCommand;
+    use crate::options::WaitOptions;
+    use crate::utils::ui::printer::Output;
+    use crate::utils::Error;
+
+    const SERVICE_NAME: &str = "service-name";
+
+    /// ID of a new workflow
+    async fn create_workflow(client: &FakeOVHapiClient) -> String {
+        let source_spec = SourceSpec {
+            name: "my-bucket".to_string(),
+            connector_id: Some(FAKE_SOURCE_CONNECTOR_ID.to_string()),
+            parameters: vec![],
+        };
+        let source = client.di_source_post(SERVICE_NAME, &source_spec).await.unwrap();
+        let destination_spec = DestinationSpec {
+            name: "my-lake".to_string(),
+            connector_id: Some(FAKE_DESTINATION_CONNECTOR_ID.to_string()),
+            parameters: vec![],
+        };
+        let destination = client.di_destination_post(SERVICE_NAME, &destination_spec).await.unwrap();
+        let workflow_spec = WorkflowSpec {
+            name: "daily-import".to_string(),
+            region: "GRA".to_string(),
+            description: None,
+            source_id: source.id,
+            destination_id: destination.id,
+            schedule: None,
+            enabled: true,
+        };
+        let workflow = client.di_workflow_post(SERVICE_NAME, &workflow_spec).await.unwrap();
+        workflow.id
+    }
+
+    /// Run the workflow then wait for its job, as `di workflow run --wait` does
+    async fn run_and_wait(client: FakeOVHapiClient) -> crate::utils::Result<()> {
+        let workflow_id = create_workflow(&client).await;
+        let job = client.di_job_post(SERVICE_NAME, &workflow_id, &JobPost::default()).await.unwrap();
+        let options = WaitOptions {
+            timeout: Some(60),
+            poll_interval: 0,
+        };
+        JobCommand::new(client)
+            .wait_job(SERVICE_NAME, &workflow_id, &job.id, &options, Output::Json)
+            .await
+    }
+
+    #[tokio::test]
+    async fn wait_job_outcome() {
+        assert!(run_and_wait(FakeOVHapiClient::new().with_sample_connectors()).await.is_ok());
+        assert!(matches!(
+            run_and_wait(FakeOVHapiClient::new().with_sample_connectors().with_job_final_status("FAILED")).await,
+            Err(Error::JobFailed(_, status)) if status == "FAILED"
+        ));
+    }
+}
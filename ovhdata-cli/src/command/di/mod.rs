@@ -1,8 +1,12 @@
 pub use commands::*;
+pub use connector_diff::ComparedCatalog;
 
+mod apply;
 mod commands;
+mod connector_diff;
 mod destination;
 mod destination_connector;
+mod export;
 mod job;
 mod source;
 mod source_connector;
@@ -3,21 +3,22 @@ use std::io::stdout;
 
 use ovhdata_common::model::di::common::ParametersWrapper;
 use ovhdata_common::model::di::source::SourceSpec;
-use ovhdata_common::model::utils::sort_source;
-use ovhdata_common::ovhapi::{DiApi, OVHapiV6Client};
+use ovhdata_common::model::utils::{sort_source, Pagination};
+use ovhdata_common::ovhapi::DiApi;
 
 use crate::command::di::source_metadata::SourceMetadataCommand;
 use crate::config::Context;
 use crate::options::{DiSubSourceCommands, SourceCreate, SourceDelete, SourceGet, SourceList, SourceUpdate};
+use crate::utils::parameters::input_parameters;
 use crate::utils::ui::printer::{Output, Printer};
 use crate::utils::{Error, Result};
 
-pub struct SourceCommand {
-    rcp_client: OVHapiV6Client,
+pub struct SourceCommand<C: DiApi> {
+    rcp_client: C,
 }
 
-impl SourceCommand {
-    pub fn new(rcp_client: OVHapiV6Client) -> Self {
+impl<C: DiApi + Clone> SourceCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
         Self { rcp_client }
     }
 
@@ -37,7 +38,11 @@ impl SourceCommand {
     async fn list(&self, input: &SourceList, output: Output) -> Result<()> {
         let service_name = Context::get().get_current_service_name().unwrap();
 
-        let mut sources = self.rcp_client.clone().di_sources(&service_name, input.filter.clone()).await?;
+        let mut sources = self
+            .rcp_client
+            .clone()
+            .di_sources(&service_name, input.filter.clone(), &Pagination::new(input.page_size, input.limit))
+            .await?;
 
         if output == Output::default_table() && !sources.is_empty() {
             sources = sort_source(sources, input.sort.clone().unwrap_or_default().as_str(), input.desc);
@@ -115,7 +120,9 @@ impl SourceCommand {
 
         // Update connector parameters with the
 
-        let parameters = Printer::ask_connector_parameters(&input.parameters, Some(&source.parameters), &connector.parameters).unwrap();
+        let input_parameters = input_parameters(&input.parameters, input.parameters_file.as_deref())?;
+
+        let parameters = Printer::ask_connector_parameters(&input_parameters, Some(&source.parameters), &connector.parameters)?;
         let parameters_len = parameters.len();
 
         // Default values will be overridden
@@ -125,8 +132,8 @@ impl SourceCommand {
             connector_id: None,
         };
 
-        // new parameters we are in interactive mode
-        if interactive || parameters_len > input.parameters.len() {
+        // parameters were asked, we are in interactive mode
+        if interactive || (input_parameters.is_empty() && parameters_len > 0) {
             Printer::print_object(&spec, &output)?;
             let confirm = Printer::confirm(&format!("Do you want to update the source {} ?", id));
 
@@ -172,7 +179,9 @@ impl SourceCommand {
 
         let connector = self.rcp_client.clone().di_source_connector(&service_name, &connector_id).await?;
 
-        let parameters = Printer::ask_connector_parameters(&input.parameters, None, &connector.parameters).unwrap();
+        let input_parameters = input_parameters(&input.parameters, input.parameters_file.as_deref())?;
+
+        let parameters = Printer::ask_connector_parameters(&input_parameters, None, &connector.parameters)?;
         let parameters_len = parameters.len();
 
         // Default values will be overridden
@@ -182,8 +191,8 @@ impl SourceCommand {
             connector_id: Some(connector_id.clone()),
         };
 
-        // new parameters we are in interactive mode
-        if interactive || parameters_len > input.parameters.len() {
+        // parameters were asked, we are in interactive mode
+        if interactive || (input_parameters.is_empty() && parameters_len > 0) {
             Printer::print_object(&spec, &output)?;
             let confirm = Printer::confirm(&format!("Do you want to create the source {} ?", &input.name));
 
@@ -238,7 +247,7 @@ impl SourceCommand {
         let interactive = input_id.is_none();
 
         let id = if interactive {
-            let sources = self.rcp_client.clone().di_sources(service_name, None).await?;
+            let sources = self.rcp_client.clone().di_sources(service_name, None, &Pagination::default()).await?;
 
             Printer::ask_select_table(&sources, None)?.id.clone()
         } else {
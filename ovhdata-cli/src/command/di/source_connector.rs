@@ -1,23 +1,32 @@
-use ovhdata_common::ovhapi::{DiApi, OVHapiV6Client};
+use ovhdata_common::ovhapi::DiApi;
 
+use crate::command::di::connector_diff::{diff_catalog, ComparedCatalog};
 use crate::config::Context;
-use crate::options::{DiSubSourceConnectorCommands, SourceConnectorGet};
+use crate::options::{ConnectorDiff, DiSubSourceConnectorCommands, SourceConnectorGet};
 use crate::utils::ui::printer::{Output, Printer};
 use crate::utils::Result;
 
-pub struct SourceConnectorCommand {
-    rcp_client: OVHapiV6Client,
+pub struct SourceConnectorCommand<C: DiApi> {
+    rcp_client: C,
+    compared: Option<ComparedCatalog<C>>,
 }
 
-impl SourceConnectorCommand {
-    pub fn new(rcp_client: OVHapiV6Client) -> Self {
-        Self { rcp_client }
+impl<C: DiApi + Clone> SourceConnectorCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
+        Self { rcp_client, compared: None }
+    }
+
+    /// Catalog of another config to compare with
+    pub fn with_compared_catalog(mut self, compared: Option<ComparedCatalog<C>>) -> Self {
+        self.compared = compared;
+        self
     }
 
     pub async fn execute_command(&self, connector_commands: DiSubSourceConnectorCommands) -> Result<()> {
         match connector_commands {
             DiSubSourceConnectorCommands::List(connector_list) => self.list_src_connectors(connector_list.output.unwrap_or_default().into()).await,
             DiSubSourceConnectorCommands::Get(get) => self.get_src_connector(&get, get.output.unwrap_or_default().into()).await,
+            DiSubSourceConnectorCommands::Diff(diff) => self.diff(&diff, diff.output.unwrap_or_default().into()).await,
         }
     }
 
@@ -48,4 +57,21 @@ impl SourceConnectorCommand {
         Printer::print_object(&connector, &output)?;
         Ok(())
     }
+
+    async fn diff(&self, input: &ConnectorDiff, output: Output) -> Result<()> {
+        let service_name = Context::get().get_current_service_name().unwrap();
+
+        let spinner = Printer::start_spinner("Retrieving connectors");
+        let connectors = self.rcp_client.clone().di_source_connectors(&service_name).await?;
+        let compared = match &self.compared {
+            Some(compared) => Some((
+                &compared.config_name,
+                compared.client.clone().di_source_connectors(&compared.service_name).await?,
+            )),
+            None => None,
+        };
+        Printer::stop_spinner(spinner);
+
+        diff_catalog(input, "source-connectors", &service_name, &connectors, compared, output)
+    }
 }
@@ -1,28 +1,47 @@
-use ovhdata_common::model::di::source_metadata::TableMeta;
-use ovhdata_common::ovhapi::{DiApi, OVHapiV6Client};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{stderr, stdout, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::config::Context;
-use crate::options::{SourceGet, SourceSubMetaCommands};
+use chrono::{DateTime, Utc};
+
+use ovhdata_common::model::di::metadata_diff::{diff_metadata, MetadataChange};
+use ovhdata_common::model::di::metadata_schema::{SchemaFormat, TypeMap};
+use ovhdata_common::model::di::source_metadata::{is_extraction_complete, TableMeta, TablesMeta};
+use ovhdata_common::model::utils::Pagination;
+use ovhdata_common::ovhapi::DiApi;
+use ovhdata_common::utils::snapshot::SnapshotStore;
+
+use crate::config::{snapshot_dir, Config, Context};
+use crate::options::{MetadataDiff, MetadataExtract, MetadataGet, NameValue, SourceSubMetaCommands, WaitOptions};
 use crate::utils::ui::printer::{Output, Printer};
-use crate::utils::Result;
+use crate::utils::{Error, Result};
+
+// Polls listing no table before the source is considered without tables
+const MAX_EMPTY_POLLS: u32 = 3;
+// Margin on the start of the extraction, for the dates precision and the time delta with the OVH API server clock
+const CLOCK_MARGIN_SECS: i64 = 2;
 
-pub struct SourceMetadataCommand {
-    rcp_client: OVHapiV6Client,
+pub struct SourceMetadataCommand<C: DiApi> {
+    rcp_client: C,
 }
 
-impl SourceMetadataCommand {
-    pub fn new(rcp_client: OVHapiV6Client) -> Self {
+impl<C: DiApi + Clone> SourceMetadataCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
         Self { rcp_client }
     }
 
     pub async fn execute_command(&self, commands: SourceSubMetaCommands) -> Result<()> {
         match commands {
-            SourceSubMetaCommands::Get(source_get) => self.get(&source_get, source_get.output.unwrap_or_default().into()).await,
-            SourceSubMetaCommands::Extract(source_get) => self.extract(&source_get, source_get.output.unwrap_or_default().into()).await,
+            SourceSubMetaCommands::Get(metadata_get) => self.get(&metadata_get, metadata_get.output.unwrap_or_default().into()).await,
+            SourceSubMetaCommands::Extract(metadata_extract) => {
+                self.extract(&metadata_extract, metadata_extract.output.unwrap_or_default().into()).await
+            }
+            SourceSubMetaCommands::Diff(metadata_diff) => self.diff(&metadata_diff, metadata_diff.output.unwrap_or_default().into()).await,
         }
     }
 
-    async fn get(&self, input: &SourceGet, output: Output) -> Result<()> {
+    async fn get(&self, input: &MetadataGet, output: Output) -> Result<()> {
         let service_name = Context::get().get_current_service_name().unwrap();
 
         let id = self.get_source_id(&service_name, &input.id).await?;
@@ -35,11 +54,107 @@ impl SourceMetadataCommand {
         let tables = self.rcp_client.clone().di_source_metadata(&service_name, &id).await?;
         Printer::stop_spinner(spinner);
 
-        Printer::print_list::<TableMeta>(tables.as_slice(), &output)?;
+        Self::save_snapshot(&service_name, &id, &tables);
+
+        match input.format {
+            Some(format) => Self::export_schemas(&tables, format, &input.type_maps, input.output_dir.as_deref()),
+            None => Ok(Printer::print_list::<TableMeta>(tables.as_slice(), &output)?),
+        }
+    }
+
+    /// Keep the metadata of a complete extraction, so that later ones can be compared to it
+    fn save_snapshot(service_name: &str, id: &str, tables: &TablesMeta) {
+        if !is_extraction_complete(tables) {
+            return;
+        }
+        match metadata_snapshots(service_name, id).save(tables) {
+            Ok(name) => Printer::println_success(&mut stderr(), &format!("Metadata snapshot {} saved", name)),
+            Err(error) => Printer::eprintln_fail(&error.to_string()),
+        }
+    }
+
+    async fn diff(&self, input: &MetadataDiff, output: Output) -> Result<()> {
+        let service_name = Context::get().get_current_service_name().unwrap();
+
+        let id = self.get_source_id(&service_name, &input.id).await?;
+
+        if input.id.is_none() {
+            Printer::print_command(&format!(
+                "di source metadata diff {} --against {} --service-name {}",
+                &id, &input.against, &service_name
+            ));
+        }
+
+        let (name, snapshot) = metadata_snapshots(&service_name, &id).load::<TablesMeta>(&input.against)?;
+
+        let spinner = Printer::start_spinner("Retrieving source metadata");
+        let tables = self.rcp_client.clone().di_source_metadata(&service_name, &id).await?;
+        Printer::stop_spinner(spinner);
+
+        // Tables being extracted or failed would be reported as removed
+        if !is_extraction_complete(&tables) {
+            return Err(Error::IncompleteMetadata(id));
+        }
+
+        let changes = diff_metadata(&snapshot.content, &tables, input.cardinality_threshold);
+        if changes.is_empty() {
+            Printer::println_success(&mut stderr(), &format!("No change since the snapshot {}", name));
+        }
+        Printer::print_list::<MetadataChange>(changes.as_slice(), &output)?;
+
+        let breaking = changes.iter().filter(|change| change.breaking).count();
+        match breaking {
+            0 => Ok(()),
+            _ => Err(Error::BreakingChanges(format!("the metadata of the source {}", id), breaking)),
+        }
+    }
+
+    /// Write the schema of each table with metadata, to a file per table or all at once to stdout
+    fn export_schemas(tables: &[TableMeta], format: SchemaFormat, type_maps: &[NameValue], output_dir: Option<&Path>) -> Result<()> {
+        let types = Self::schema_types(format, type_maps)?;
+
+        let tables = tables
+            .iter()
+            .filter(|table| {
+                if table.metadata.is_empty() {
+                    Printer::eprintln_fail(&format!("Table {} has no metadata, skipped", table_name(table)));
+                }
+                !table.metadata.is_empty()
+            })
+            .cloned()
+            .collect::<Vec<TableMeta>>();
+
+        let output_dir = match output_dir {
+            Some(output_dir) => output_dir,
+            None => return Ok(write!(stdout(), "{}", format.render_all(&tables, &types))?),
+        };
+
+        std::fs::create_dir_all(output_dir)?;
+        for table in &tables {
+            let path = output_dir.join(format!("{}.{}", file_name(&table_name(table)), format.extension()));
+            std::fs::write(&path, format.render(table, &types))?;
+            Printer::println_success(
+                &mut stderr(),
+                &format!("Schema of the table {} written to {}", table_name(table), path.display()),
+            );
+        }
         Ok(())
     }
 
-    async fn extract(&self, input: &SourceGet, output: Output) -> Result<()> {
+    /// Types of the format, the `--type-map` options overriding the configured ones
+    fn schema_types(format: SchemaFormat, type_maps: &[NameValue]) -> Result<TypeMap> {
+        let config = Config::get();
+        let mut overrides: BTreeMap<String, String> = match format {
+            SchemaFormat::JsonSchema => config.schema_types.json_schema.clone(),
+            SchemaFormat::SqlDdl => config.schema_types.sql_ddl.clone(),
+            SchemaFormat::Avro => config.schema_types.avro.clone(),
+        };
+        overrides.extend(type_maps.iter().map(|type_map| (type_map.name.clone(), type_map.value.clone())));
+
+        format.types(&overrides).map_err(Error::custom)
+    }
+
+    async fn extract(&self, input: &MetadataExtract, output: Output) -> Result<()> {
         let service_name = Context::get().get_current_service_name().unwrap();
 
         let id = self.get_source_id(&service_name, &input.id).await?;
@@ -49,18 +164,108 @@ impl SourceMetadataCommand {
         }
 
         let spinner = Printer::start_spinner("Extracting metadata from source");
+        let requested_at = Utc::now();
         let tables = self.rcp_client.clone().di_source_metadata_post(&service_name, &id).await?;
         Printer::stop_spinner(spinner);
 
+        if input.wait {
+            let triggered_at = Self::triggered_at(&tables, requested_at);
+            return self.wait_extraction(&service_name, &id, triggered_at, &input.wait_options, output).await;
+        }
+
         Printer::print_list::<TableMeta>(tables.as_slice(), &output)?;
         Ok(())
     }
 
+    /// Start of the extraction on the server clock, the tables of this extraction being started after it.
+    /// The tables returned when it is triggered give it, otherwise the local request time is corrected by the known time delta.
+    fn triggered_at(tables: &TablesMeta, requested_at: DateTime<Utc>) -> DateTime<Utc> {
+        let started_at = tables.iter().filter_map(|table| table.started_at).min().unwrap_or_else(|| {
+            let time_delta = Context::get().get_ovhapi_time_delta().unwrap_or_default();
+            requested_at + chrono::Duration::seconds(time_delta)
+        });
+        started_at - chrono::Duration::seconds(CLOCK_MARGIN_SECS)
+    }
+
+    /// Poll the metadata until the extraction of every table is over, each table being reported on stderr once it ends.
+    /// The tables started before the extraction was triggered are still those of the previous one.
+    async fn wait_extraction(&self, service_name: &str, id: &str, triggered_at: DateTime<Utc>, options: &WaitOptions, output: Output) -> Result<()> {
+        let timeout = options.timeout.map(Duration::from_secs);
+        let poll_interval = Duration::from_secs(options.poll_interval);
+        let start = Instant::now();
+        let is_ended = |table: &TableMeta| table.is_ended() && table.started_at.is_none_or(|started_at| started_at >= triggered_at);
+
+        let mut spinner = None;
+        let mut ended_tables = HashSet::new();
+        let mut empty_polls = 0;
+        let tables = loop {
+            let tables = match self.rcp_client.clone().di_source_metadata(service_name, id).await {
+                Ok(tables) => tables,
+                Err(error) => {
+                    Printer::stop_spinner(spinner);
+                    return Err(error.into());
+                }
+            };
+
+            let newly_ended = tables
+                .iter()
+                .filter(|table| is_ended(table) && ended_tables.insert(table_name(table)))
+                .collect::<Vec<&TableMeta>>();
+            if spinner.is_none() || !newly_ended.is_empty() {
+                Printer::stop_spinner(spinner.take());
+                for table in newly_ended {
+                    Self::print_table_result(table);
+                }
+                spinner = Printer::start_timer_spinner(&format!("Extracting metadata, {}/{} tables done", ended_tables.len(), tables.len()));
+            }
+
+            // The tables may not be listed yet right after the extraction is triggered
+            if !tables.is_empty() && tables.iter().all(is_ended) {
+                break tables;
+            }
+            empty_polls = if tables.is_empty() { empty_polls + 1 } else { 0 };
+            if empty_polls > MAX_EMPTY_POLLS {
+                Printer::stop_spinner(spinner);
+                return Err(Error::NoTable(id.to_string()));
+            }
+
+            let remaining = match timeout {
+                Some(timeout) if start.elapsed() >= timeout => {
+                    Printer::stop_spinner(spinner);
+                    return Err(Error::ExtractionTimeout(id.to_string(), timeout.as_secs()));
+                }
+                Some(timeout) => timeout - start.elapsed(),
+                None => poll_interval,
+            };
+            tokio::time::sleep(poll_interval.min(remaining)).await;
+        };
+        Printer::stop_spinner(spinner);
+
+        Printer::print_list::<TableMeta>(tables.as_slice(), &output)?;
+
+        let failed_tables = tables.iter().filter(|table| table.has_failed()).map(table_name).collect::<Vec<String>>();
+        match failed_tables.is_empty() {
+            true => Ok(()),
+            false => Err(Error::ExtractionFailed(id.to_string(), failed_tables.join(", "))),
+        }
+    }
+
+    fn print_table_result(table: &TableMeta) {
+        let name = table_name(table);
+        match table.has_failed() {
+            true => {
+                let error = table.error.as_deref().or(table.error_code.as_deref()).unwrap_or(&table.status);
+                Printer::eprintln_fail(&format!("Table {} failed: {}", name, error));
+            }
+            false => Printer::println_success(&mut stderr(), &format!("Table {} extracted", name)),
+        }
+    }
+
     async fn get_source_id(&self, service_name: &str, input_id: &Option<String>) -> Result<String> {
         let interactive = input_id.is_none();
 
         let id = if interactive {
-            let sources = self.rcp_client.clone().di_sources(service_name, None).await?;
+            let sources = self.rcp_client.clone().di_sources(service_name, None, &Pagination::default()).await?;
 
             Printer::ask_select_table(&sources, None)?.id.clone()
         } else {
@@ -70,3 +275,83 @@ impl SourceMetadataCommand {
         Ok(id)
     }
 }
+
+/// Metadata snapshots of a source, for the current config and service
+fn metadata_snapshots(service_name: &str, id: &str) -> SnapshotStore {
+    SnapshotStore::new(snapshot_dir(&Config::get().name).join(service_name).join("sources").join(id))
+}
+
+fn table_name(table: &TableMeta) -> String {
+    table.table_name.clone().unwrap_or_default()
+}
+
+/// Table names may hold a schema prefix or characters not allowed in file names
+fn file_name(table_name: &str) -> String {
+    table_name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use ovhdata_common::model::di::source::SourceSpec;
+    use ovhdata_common::model::di::source_metadata::TableMeta;
+    use ovhdata_common::ovhapi::{DiApi, FakeOVHapiClient, FAKE_SOURCE_CONNECTOR_ID};
+
+    use crate::command::di::source_metadata::SourceMetadataCommand;
+    use crate::options::WaitOptions;
+    use crate::utils::ui::printer::Output;
+    use crate::utils::Error;
+
+    const SERVICE_NAME: &str = "service-name";
+
+    #[tokio::test]
+    async fn wait_extraction_with_skewed_clock() {
+        // The local clock is a minute ahead of the server one
+        let table = TableMeta {
+            table_name: Some("orders".to_string()),
+            status: "SUCCESS".to_string(),
+            error: None,
+            error_code: None,
+            started_at: None,
+            ended_at: None,
+            metadata: vec![],
+        };
+        let client = FakeOVHapiClient::new()
+            .with_sample_connectors()
+            .with_extracted_tables(vec![table])
+            .with_clock_offset(-60);
+        let source_spec = SourceSpec {
+            name: "my-database".to_string(),
+            connector_id: Some(FAKE_SOURCE_CONNECTOR_ID.to_string()),
+            parameters: vec![],
+        };
+        let source = client.di_source_post(SERVICE_NAME, &source_spec).await.unwrap();
+        let command = SourceMetadataCommand::new(client.clone());
+        let options = |timeout: u64| WaitOptions {
+            timeout: Some(timeout),
+            poll_interval: 0,
+        };
+
+        let requested_at = Utc::now();
+        let tables = client.di_source_metadata_post(SERVICE_NAME, &source.id).await.unwrap();
+        let triggered_at = SourceMetadataCommand::<FakeOVHapiClient>::triggered_at(&tables, requested_at);
+        command
+            .wait_extraction(SERVICE_NAME, &source.id, triggered_at, &options(5), Output::Json)
+            .await
+            .unwrap();
+
+        // On the local clock, the extracted table would look like the one of a previous extraction
+        assert!(matches!(
+            command
+                .wait_extraction(SERVICE_NAME, &source.id, requested_at, &options(0), Output::Json)
+                .await,
+            Err(Error::ExtractionTimeout(..))
+        ));
+    }
+}
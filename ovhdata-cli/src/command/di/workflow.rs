@@ -1,21 +1,29 @@
+use chrono::Utc;
 use crossterm::style::Stylize;
 use std::io::stdout;
 
-use ovhdata_common::model::di::workflow::{WorkflowPatch, WorkflowSpec};
-use ovhdata_common::model::utils::sort_workflow;
-use ovhdata_common::ovhapi::{DiApi, OVHapiV6Client};
+use ovhdata_common::model::di::common::ParametersWrapper;
+use ovhdata_common::model::di::workflow::{JobPost, WorkflowPatch, WorkflowSpec};
+use ovhdata_common::model::utils::{sort_workflow, Pagination};
+use ovhdata_common::ovhapi::DiApi;
+use ovhdata_common::utils::cron::CronSchedule;
 
+use crate::command::di::job::JobCommand;
 use crate::config::Context;
 use crate::options::{DiSubWorkflowCommands, WorkflowCreate, WorkflowDelete, WorkflowGet, WorkflowList, WorkflowRun, WorkflowUpdate};
+use crate::utils::parameters::input_parameters;
 use crate::utils::ui::printer::{Output, Printer};
 use crate::utils::{Error, Result};
 
-pub struct WorkflowCommand {
-    rcp_client: OVHapiV6Client,
+// Number of next runs shown before confirming a schedule
+const NEXT_RUNS_PREVIEW: usize = 5;
+
+pub struct WorkflowCommand<C: DiApi> {
+    rcp_client: C,
 }
 
-impl WorkflowCommand {
-    pub fn new(rcp_client: OVHapiV6Client) -> Self {
+impl<C: DiApi + Clone> WorkflowCommand<C> {
+    pub fn new(rcp_client: C) -> Self {
         Self { rcp_client }
     }
 
@@ -35,7 +43,11 @@ impl WorkflowCommand {
     async fn list(&self, input: &WorkflowList, output: Output) -> Result<()> {
         let service_name = Context::get().get_current_service_name().unwrap();
 
-        let mut workflows = self.rcp_client.clone().di_workflows(&service_name, input.filter.clone()).await?;
+        let mut workflows = self
+            .rcp_client
+            .clone()
+            .di_workflows(&service_name, input.filter.clone(), &Pagination::new(input.page_size, input.limit))
+            .await?;
 
         if output == Output::default_table() && !workflows.is_empty() {
             workflows = sort_workflow(workflows, input.sort.clone().unwrap_or_default().as_str(), input.desc);
@@ -69,7 +81,7 @@ impl WorkflowCommand {
 
         let missing_source = input.source_id.is_none();
         let source_id = if missing_source {
-            let sources = self.rcp_client.clone().di_sources(&service_name, None).await?;
+            let sources = self.rcp_client.clone().di_sources(&service_name, None, &Pagination::default()).await?;
             Printer::ask_select_table(&sources, None)?.id.clone()
         } else {
             input.source_id.clone().unwrap()
@@ -92,10 +104,11 @@ impl WorkflowCommand {
         };
 
         let schedule = if interactive {
-            Printer::ask_input_string("Enter a schedule (press enter to skip)", input.schedule.clone(), true, None)
+            Printer::ask_input_schedule("Enter a schedule (press enter to skip)", input.schedule.clone())
         } else {
             input.schedule.clone()
         };
+        let cron_schedule = parse_schedule(&schedule)?;
 
         let spec = WorkflowSpec {
             name: input.name.clone(),
@@ -110,6 +123,9 @@ impl WorkflowCommand {
         // if there was an interaction, ask for confirmation
         if missing_destination || missing_source {
             Printer::print_object(&spec, &output)?;
+            if let Some(cron_schedule) = &cron_schedule {
+                print_next_runs(cron_schedule);
+            }
             let message = format!("Do you want to create the workflow {} ?", input.name.clone());
             let confirm = Printer::confirm(&message);
 
@@ -143,21 +159,47 @@ impl WorkflowCommand {
         let interactive = input.id.is_none();
 
         let id = if interactive {
-            let workflows = self.rcp_client.clone().di_workflows(&service_name, None).await?;
+            let workflows = self.rcp_client.clone().di_workflows(&service_name, None, &Pagination::default()).await?;
             Printer::ask_select_table(&workflows, None)?.id.clone()
         } else {
             input.id.clone().unwrap()
         };
 
+        // The overridden parameters must be known by the workflow
+        let parameters = input_parameters(&input.parameters, None)?;
+        let job_post = match parameters.is_empty() {
+            true => JobPost {
+                parameters,
+                region: input.region.clone(),
+            },
+            false => {
+                let workflow = self.rcp_client.clone().di_workflow(&service_name, &id).await?;
+                workflow.job_post(parameters, input.region.clone()).map_err(Error::custom)?
+            }
+        };
+
         if interactive {
-            Printer::print_command(&format!("di destination run {} --service-name {} ", &id, &service_name));
+            let mut cmd = format!("di workflow run {} --service-name {}", &id, &service_name);
+            if !job_post.parameters.is_empty() {
+                cmd.push_str(&format!(" {}", ParametersWrapper(job_post.parameters.clone())));
+            }
+            if let Some(region) = &job_post.region {
+                cmd.push_str(&format!(" --region {}", region));
+            }
+            Printer::print_command(&cmd);
         }
 
         let spinner = Printer::start_spinner("Running workflow");
-        let workflow = self.rcp_client.clone().di_job_post(&service_name, &id).await?;
+        let job = self.rcp_client.clone().di_job_post(&service_name, &id, &job_post).await?;
         Printer::stop_spinner(spinner);
 
-        Printer::print_object(&workflow, &output)?;
+        if input.wait {
+            return JobCommand::new(self.rcp_client.clone())
+                .wait_job(&service_name, &id, &job.id, &input.wait_options, output)
+                .await;
+        }
+
+        Printer::print_object(&job, &output)?;
         Ok(())
     }
 
@@ -199,7 +241,7 @@ impl WorkflowCommand {
 
             let name = Printer::ask_input_string("Enter the new name", Some(workflow.name), true, None);
             let description = Printer::ask_input_string("Enter the new definition", workflow.description, true, None);
-            let schedule = Printer::ask_input_string("Enter the new schedule", workflow.schedule, true, None);
+            let schedule = Printer::ask_input_schedule("Enter the new schedule", workflow.schedule);
             let enabled = Printer::ask_input_boolean("Is the workflow enabled", workflow.enabled).unwrap();
 
             WorkflowPatch {
@@ -216,17 +258,21 @@ impl WorkflowCommand {
                 enabled: input.enabled,
             }
         };
+        let cron_schedule = parse_schedule(&spec.schedule)?;
 
         if interactive_update {
             Printer::print_object(&spec, &output)?;
+            if let Some(cron_schedule) = &cron_schedule {
+                print_next_runs(cron_schedule);
+            }
             let confirm = Printer::confirm(&format!("Do you want to update the workflow {} ?", &workflow_id));
 
             let mut cmd = format!("di workflow update {} --service-name {} ", &workflow_id, &service_name);
             if spec.name.clone().is_some() {
                 cmd.push_str(&format!(" --name {}", spec.name.clone().unwrap()));
             }
-            if spec.enabled.is_some() {
-                cmd.push_str(&format!(" --enabled {}", spec.enabled.unwrap()));
+            if let Some(enabled) = spec.enabled {
+                cmd.push_str(&format!(" --enabled {}", enabled));
             }
             if spec.description.clone().is_some() {
                 cmd.push_str(&format!(" --description {}", spec.description.clone().unwrap()));
@@ -288,7 +334,7 @@ impl WorkflowCommand {
         let interactive = input_id.is_none();
 
         let id = if interactive {
-            let workflows = self.rcp_client.clone().di_workflows(service_name, None).await?;
+            let workflows = self.rcp_client.clone().di_workflows(service_name, None, &Pagination::default()).await?;
             Printer::ask_select_table(&workflows, None)?.id.clone()
         } else {
             input_id.clone().unwrap()
@@ -297,3 +343,34 @@ impl WorkflowCommand {
         Ok(id)
     }
 }
+
+/// Check the schedule before sending it, rather than finding a typo when the API rejects it.
+/// None if not set, or cleared with an empty value
+fn parse_schedule(schedule: &Option<String>) -> Result<Option<CronSchedule>> {
+    schedule
+        .as_deref()
+        .filter(|schedule| !schedule.is_empty())
+        .map(|schedule| CronSchedule::parse(schedule).map_err(|error| Error::custom(format!("Invalid schedule '{}': {}", schedule, error))))
+        .transpose()
+}
+
+fn print_next_runs(schedule: &CronSchedule) {
+    println!("Runs {}, next runs:", schedule.describe().green());
+    for run in schedule.next_runs(Utc::now(), NEXT_RUNS_PREVIEW) {
+        println!("  {}", run.format("%Y-%m-%d %H:%M UTC"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::di::workflow::parse_schedule;
+
+    #[test]
+    fn parse_empty_schedule() {
+        assert!(parse_schedule(&None).unwrap().is_none());
+        // An empty schedule clears the one of the workflow
+        assert!(parse_schedule(&Some(String::new())).unwrap().is_none());
+        assert!(parse_schedule(&Some("0 1 * * *".to_string())).unwrap().is_some());
+        assert!(parse_schedule(&Some("0 1".to_string())).is_err());
+    }
+}
@@ -11,7 +11,6 @@ use filetime::FileTime;
 use futures::StreamExt;
 use regex::Regex;
 use reqwest::header::USER_AGENT;
-use reqwest::Client;
 use semver::Version;
 use serde::Deserialize;
 use tokio::fs::File;
@@ -122,7 +121,9 @@ impl Upgrade {
         tmp_dir.push(format!("{}.{}.tmp", CLI_NAME, last_version));
         let mut file = File::create(tmp_dir.clone()).await?;
 
-        let response = reqwest::get(&Upgrade::get_binary_url().await?).await?;
+        // The download may last longer than an API request
+        let client = Config::get().http.download_client()?;
+        let response = client.get(&Upgrade::get_binary_url().await?).send().await?;
 
         let mut stream = response.bytes_stream();
         while let Some(bytes) = stream.next().await {
@@ -197,7 +198,7 @@ impl Upgrade {
             .iter()
             .filter(|asset| re.is_match(&asset.name))
             .map(|asset| asset.browser_download_url.clone())
-            .last()
+            .next_back()
             .ok_or(Error::Custom(format!("Asset not found OS={}", OS)))
     }
 
@@ -215,7 +216,7 @@ impl Upgrade {
         }
 
         // get last version from remote
-        let client = Client::builder().build()?.get(url).header(USER_AGENT, "request");
+        let client = Config::get().http.client()?.get(url).header(USER_AGENT, "request");
         let body_response = client.send().await?.text().await?;
 
         // parse version
@@ -5,29 +5,40 @@ use std::fs::{create_dir_all, metadata, set_permissions, File};
 use std::io::{BufReader, BufWriter};
 use std::ops::Deref;
 use std::path::PathBuf;
-use std::sync::{Mutex, MutexGuard};
+use std::sync::{Mutex, MutexGuard, RwLock};
 
 use ovhdata_common::config::{AllConfig, ConfigName};
 use ovhdata_common::model::di::common::EnsureSecret;
 use ovhdata_macros::PrintObjectCompletely;
 
+use chrono::{DateTime, Duration, Utc};
 use crossterm::style::Stylize;
 use descriptor::Descriptor;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error as ThisError;
-use tracing::error;
 use uuid::Uuid;
 
+use crate::credentials::{env, Source, Sourced, SERVICE_NAME_ENV};
+use crate::utils::crypto::{passphrase, EncryptedData, SecretKey};
+
 lazy_static! {
     static ref CONTEXT: Mutex<Context> = Mutex::new(Context::load(None).expect("Unexpected error"));
     static ref CONFIG: Mutex<AllConfig> = Mutex::new(Config::load().expect("Unexpected error"));
+    // Config of the active profile, used for this command only (never saved)
+    static ref CONFIG_NAME_OVERRIDE: RwLock<Option<ConfigName>> = RwLock::new(None);
 }
 
 pub const CLI_NAME: &str = "ovhdata-cli";
 const DEFAULT_REGION: Region = Region::EU;
 const CONFIG_EU: &str = include_str!("../config/eu.json");
 const CONFIG_CA: &str = include_str!("../config/ca.json");
+// OVH API time delta is computed again once expired
+const TIME_DELTA_TTL_HOURS: i64 = 24;
+// Credentials and service names of a profile are stored along the region ones under this prefix, so they are encrypted alike
+const PROFILE_KEY_PREFIX: &str = "profile:";
+// Name selecting the region credentials instead of a profile
+pub const DEFAULT_PROFILE: &str = "default";
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -60,21 +71,63 @@ impl EnsureSecret<Ovhapiv6Credentials> for Ovhapiv6Credentials {
     }
 }
 
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TimeDelta {
+    // Delta in seconds between the OVH API server clock and the local clock
+    pub delta: i64,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Named set of credentials and service name on a region config
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Profile {
+    pub config_name: ConfigName,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Context {
     // UUID use to identify uniquely a context log file (so that different user can work in parallel on the same machine)
     #[serde(default)]
     pub uuid: Uuid,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub ovhapi_credentials: Option<HashMap<ConfigName, Ovhapiv6Credentials>>,
+    ovhapi_credentials: Option<HashMap<ConfigName, Ovhapiv6Credentials>>,
+    // Credentials encrypted with a passphrase, replacing the plaintext ones once the store is locked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encrypted_credentials: Option<EncryptedData>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub service_names: Option<HashMap<ConfigName, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ovhapi_time_deltas: Option<HashMap<ConfigName, TimeDelta>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profiles: Option<HashMap<String, Profile>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_profile: Option<String>,
     #[serde(default)]
     toggles: HashSet<Toggle>,
     #[serde(default)]
     pub features: Features,
     #[serde(skip)]
     pub config_path: PathBuf,
+    // Service name given on the command line, taking precedence over the environment (never saved as such)
+    #[serde(skip)]
+    service_name_override: Option<String>,
+    // Key of the encrypted credentials once decrypted (never saved)
+    #[serde(skip)]
+    credentials_key: Option<SecretKey>,
+    // Profile used by this command, the current one or the one given on the command line (never saved)
+    #[serde(skip)]
+    active_profile: Option<String>,
+    // Whether the profile was given on the command line, taking precedence over the environment (never saved)
+    #[serde(skip)]
+    profile_given: bool,
+}
+
+/// Config, profile and service name of the command, put aside while another config is used
+pub struct SwitchedConfig {
+    config_name: Option<ConfigName>,
+    active_profile: Option<String>,
+    profile_given: bool,
+    service_name_override: Option<String>,
 }
 
 #[derive(PartialEq, Clone, Serialize, Deserialize, Descriptor)]
@@ -114,28 +167,124 @@ impl Context {
         }
     }
 
-    pub fn get_ovhapi_credentials(&self) -> Option<Ovhapiv6Credentials> {
-        let config_name = Config::get_config_name();
-        self.ovhapi_credentials.as_ref().and_then(|x| x.get(&config_name).cloned())
+    pub fn get_ovhapi_credentials(&mut self) -> Result<Option<Ovhapiv6Credentials>> {
+        self.decrypt_credentials()?;
+        let key = self.key();
+        Ok(self.ovhapi_credentials.as_ref().and_then(|x| x.get(&key).cloned()))
     }
 
-    pub fn set_ovhapi_credentials(&mut self, creds: Ovhapiv6Credentials) {
-        let config_name = Config::get_config_name();
+    pub fn set_ovhapi_credentials(&mut self, creds: Ovhapiv6Credentials) -> Result<()> {
+        self.decrypt_credentials()?;
+        let key = self.key();
         match &mut self.ovhapi_credentials {
             Some(map) => {
-                map.insert(config_name, creds);
+                map.insert(key, creds);
             }
             None => {
                 let mut map = HashMap::new();
-                map.insert(config_name, creds);
+                map.insert(key, creds);
                 self.ovhapi_credentials = Some(map);
             }
         }
+        Ok(())
     }
 
-    pub fn get_current_service_name(&self) -> Option<String> {
+    /// Return true if the credentials are stored encrypted
+    pub fn is_locked(&self) -> bool {
+        self.encrypted_credentials.is_some()
+    }
+
+    /// Encrypt the stored credentials with a key derived from the passphrase
+    pub fn lock(&mut self, passphrase: &str) -> Result<()> {
+        if self.is_locked() {
+            return Err(Error::Credentials("the credentials are already encrypted".to_string()));
+        }
+        let key = SecretKey::generate(passphrase)?;
+        self.encrypted_credentials = Some(key.encrypt(&self.credentials_json()?)?);
+        self.credentials_key = Some(key);
+        Ok(())
+    }
+
+    /// Decrypt the stored credentials, they are saved in clear again
+    pub fn unlock(&mut self) -> Result<()> {
+        if !self.is_locked() {
+            return Err(Error::Credentials("the credentials are not encrypted".to_string()));
+        }
+        self.decrypt_credentials()?;
+        self.encrypted_credentials = None;
+        self.credentials_key = None;
+        Ok(())
+    }
+
+    /// Decrypt the credentials in memory, asking the passphrase if needed
+    fn decrypt_credentials(&mut self) -> Result<()> {
+        if self.credentials_key.is_some() {
+            return Ok(());
+        }
+        if let Some(encrypted_credentials) = &self.encrypted_credentials {
+            let (credentials, key) = encrypted_credentials.decrypt(&passphrase(false)?)?;
+            self.ovhapi_credentials = serde_json::from_slice(&credentials)?;
+            self.credentials_key = Some(key);
+        }
+        Ok(())
+    }
+
+    fn credentials_json(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&self.ovhapi_credentials)?)
+    }
+
+    /// Get the OVH API time delta of the current config if it is not expired
+    pub fn get_ovhapi_time_delta(&self) -> Option<i64> {
+        let config_name = Config::get_config_name();
+        self.ovhapi_time_deltas
+            .as_ref()
+            .and_then(|x| x.get(&config_name))
+            .filter(|time_delta| time_delta.computed_at + Duration::hours(TIME_DELTA_TTL_HOURS) > Utc::now())
+            .map(|time_delta| time_delta.delta)
+    }
+
+    pub fn set_ovhapi_time_delta(&mut self, delta: i64) {
         let config_name = Config::get_config_name();
-        self.get_service_name(&config_name)
+        let time_delta = TimeDelta {
+            delta,
+            computed_at: Utc::now(),
+        };
+        match &mut self.ovhapi_time_deltas {
+            Some(map) => {
+                map.insert(config_name, time_delta);
+            }
+            None => {
+                let mut map = HashMap::new();
+                map.insert(config_name, time_delta);
+                self.ovhapi_time_deltas = Some(map);
+            }
+        }
+    }
+
+    pub fn get_current_service_name(&self) -> Option<String> {
+        self.get_current_service_name_with_source().map(|service_name| service_name.value)
+    }
+
+    /// Service name with the precedence: flag > env > context, the env being skipped for a profile given on the command line
+    pub fn get_current_service_name_with_source(&self) -> Option<Sourced> {
+        if let Some(service_name) = &self.service_name_override {
+            return Some(Sourced::new(service_name.clone(), Source::Flag));
+        }
+        let key = self.key();
+        env(SERVICE_NAME_ENV).filter(|_| !self.profile_given).or_else(|| {
+            self.get_service_name(&key)
+                .map(|service_name| Sourced::new(service_name, Source::Context))
+        })
+    }
+
+    /// Use the given service name for the current command, whatever the environment says
+    pub fn set_service_name_override(&mut self, service_name: String) {
+        self.service_name_override = Some(service_name);
+    }
+
+    /// Service name saved in the context, whatever the command line and the environment say
+    pub fn get_saved_service_name(&self) -> Option<String> {
+        self.get_service_name(&self.key())
     }
 
     fn get_service_name(&self, config_name: &ConfigName) -> Option<String> {
@@ -143,35 +292,140 @@ impl Context {
     }
 
     pub fn set_service_name(&mut self, service_name: String) {
-        let config_name = Config::get_config_name();
+        let key = self.key();
         match &mut self.service_names {
             Some(map) => {
-                map.insert(config_name, service_name);
+                map.insert(key, service_name);
             }
             None => {
                 let mut map = HashMap::new();
-                map.insert(config_name, service_name);
+                map.insert(key, service_name);
                 self.service_names = Some(map);
             }
         }
     }
 
-    pub fn logout(&mut self) {
-        let config_name = Config::get_config_name();
-        match &mut self.ovhapi_credentials {
-            Some(map) => {
-                map.remove(&config_name);
-            }
-            None => {}
+    pub fn logout(&mut self) -> Result<()> {
+        self.decrypt_credentials()?;
+        let key = self.key();
+        if let Some(map) = &mut self.ovhapi_credentials {
+            map.remove(&key);
         }
-        match &mut self.service_names {
-            Some(map) => {
-                map.remove(&config_name);
-            }
-            None => {}
+        if let Some(map) = &mut self.service_names {
+            map.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Key of the credentials and service name: the active profile, or the current region config
+    fn key(&self) -> ConfigName {
+        match &self.active_profile {
+            Some(profile) => Self::profile_key(profile),
+            None => Config::get_config_name(),
         }
     }
 
+    fn profile_key(profile: &str) -> ConfigName {
+        ConfigName::from(format!("{}{}", PROFILE_KEY_PREFIX, profile))
+    }
+
+    /// Use the given profile, or the current one, for this command
+    pub fn activate_profile(&mut self, profile: Option<String>) -> Result<()> {
+        self.profile_given = profile.is_some();
+        let profile = match profile.or(self.current_profile.clone()) {
+            Some(profile) if profile != DEFAULT_PROFILE => profile,
+            _ => return Ok(()),
+        };
+        let config_name = self.get_profile(&profile)?.config_name.clone();
+        *CONFIG_NAME_OVERRIDE.write().expect("Unexpected error") = Some(config_name);
+        self.active_profile = Some(profile);
+        Ok(())
+    }
+
+    /// Use the region credentials and service name of another config, until the returned state is restored
+    pub fn switch_config(&mut self, config_name: ConfigName) -> Result<SwitchedConfig> {
+        if Config::get_all().get_config(config_name.clone()).is_none() {
+            return Err(Error::Config(ovhdata_common::config::Error::ConfigNameNotFound(config_name)));
+        }
+        Ok(SwitchedConfig {
+            config_name: CONFIG_NAME_OVERRIDE.write().expect("Unexpected error").replace(config_name),
+            active_profile: self.active_profile.take(),
+            profile_given: std::mem::take(&mut self.profile_given),
+            service_name_override: self.service_name_override.take(),
+        })
+    }
+
+    pub fn restore_config(&mut self, switched: SwitchedConfig) {
+        *CONFIG_NAME_OVERRIDE.write().expect("Unexpected error") = switched.config_name;
+        self.active_profile = switched.active_profile;
+        self.profile_given = switched.profile_given;
+        self.service_name_override = switched.service_name_override;
+    }
+
+    pub fn get_active_profile(&self) -> Option<&String> {
+        self.active_profile.as_ref()
+    }
+
+    /// Whether the profile was given on the command line
+    pub fn is_profile_given(&self) -> bool {
+        self.profile_given
+    }
+
+    pub fn get_profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles
+            .as_ref()
+            .and_then(|profiles| profiles.get(name))
+            .ok_or(Error::UnknownProfile(name.to_string()))
+    }
+
+    pub fn get_profiles(&self) -> Vec<(String, Profile)> {
+        let mut profiles = self.profiles.clone().unwrap_or_default().into_iter().collect::<Vec<(String, Profile)>>();
+        profiles.sort_by(|(name, _), (other, _)| name.cmp(other));
+        profiles
+    }
+
+    /// Service name of a profile
+    pub fn get_profile_service_name(&self, name: &str) -> Option<String> {
+        self.get_service_name(&Self::profile_key(name))
+    }
+
+    pub fn add_profile(&mut self, name: String, profile: Profile) -> Result<()> {
+        if name == DEFAULT_PROFILE || self.get_profile(&name).is_ok() {
+            return Err(Error::ProfileAlreadyExists(name));
+        }
+        self.profiles.get_or_insert_with(HashMap::new).insert(name, profile);
+        Ok(())
+    }
+
+    /// Set the profile used by the next commands, the region credentials are used again with the default profile
+    pub fn use_profile(&mut self, name: String) -> Result<()> {
+        self.current_profile = match name == DEFAULT_PROFILE {
+            true => None,
+            false => Some(self.get_profile(&name).map(|_| name)?),
+        };
+        Ok(())
+    }
+
+    /// Delete a profile with its credentials and service name
+    pub fn delete_profile(&mut self, name: &str) -> Result<()> {
+        self.get_profile(name)?;
+        self.decrypt_credentials()?;
+        let key = Self::profile_key(name);
+        if let Some(map) = &mut self.ovhapi_credentials {
+            map.remove(&key);
+        }
+        if let Some(map) = &mut self.service_names {
+            map.remove(&key);
+        }
+        if let Some(profiles) = &mut self.profiles {
+            profiles.remove(name);
+        }
+        if self.current_profile.as_deref() == Some(name) {
+            self.current_profile = None;
+        }
+        Ok(())
+    }
+
     /// Load context from a file, return default if it does not exist yet
     fn load(custom_path: Option<PathBuf>) -> Result<Self> {
         // Context path
@@ -240,7 +494,22 @@ impl Context {
         Self::create_context_file(self.config_path.clone())?;
 
         // Write context
-        serde_json::to_writer_pretty(BufWriter::new(File::create(&self.config_path)?), self)?;
+        let writer = BufWriter::new(File::create(&self.config_path)?);
+        match &self.credentials_key {
+            // Encrypt the credentials again, the plaintext ones stay in memory only
+            Some(key) => {
+                let mut context = serde_json::to_value(self)?;
+                if let Some(fields) = context.as_object_mut() {
+                    fields.remove("ovhapi_credentials");
+                    fields.insert(
+                        "encrypted_credentials".to_string(),
+                        serde_json::to_value(key.encrypt(&self.credentials_json()?)?)?,
+                    );
+                }
+                serde_json::to_writer_pretty(writer, &context)?
+            }
+            None => serde_json::to_writer_pretty(writer, self)?,
+        }
 
         Ok(())
     }
@@ -264,10 +533,18 @@ impl Default for Context {
         Self {
             uuid: Uuid::new_v4(),
             ovhapi_credentials: None,
+            encrypted_credentials: None,
             service_names: None,
+            ovhapi_time_deltas: None,
+            profiles: None,
+            current_profile: None,
             toggles: HashSet::new(),
             features: Features::default(),
             config_path: default_context_path(),
+            service_name_override: None,
+            credentials_key: None,
+            active_profile: None,
+            profile_given: false,
         }
     }
 }
@@ -302,6 +579,26 @@ fn map_row_selectable_item(selectable_item: &SelectableItem, cell: String) -> St
     .to_string()
 }
 
+/// Profile as listed, the current one being highlighted
+#[derive(Serialize, Descriptor)]
+#[descriptor(default_headers = ["name", "config_name", "service_name"], map = map_row_profile_item)]
+pub struct ProfileItem {
+    pub name: String,
+    pub config_name: ConfigName,
+    pub service_name: Option<String>,
+    #[serde(skip)]
+    #[descriptor(skip)]
+    pub is_current: bool,
+}
+
+fn map_row_profile_item(profile_item: &ProfileItem, cell: String) -> String {
+    match profile_item.is_current {
+        true => cell.green(),
+        false => cell.reset(),
+    }
+    .to_string()
+}
+
 impl SelectableItem {
     pub fn new(item: Config, is_selected: bool) -> Self {
         SelectableItem { item, is_selected }
@@ -337,10 +634,12 @@ impl Config {
     }
 
     pub fn get() -> Self {
+        let config_name_override = CONFIG_NAME_OVERRIDE.read().expect("Unexpected error").clone();
         let all_config = Self::get_all();
-        match all_config.get_current_config() {
-            Ok(config) => Config::new(all_config.current_config_name.as_str(), config.clone(), None),
-            Err(error) => panic!("{}", error.to_string()),
+        let config_name = config_name_override.unwrap_or(all_config.current_config_name.clone());
+        match all_config.get_config(config_name.clone()) {
+            Some(config) => Config::new(config_name, config.clone(), None),
+            None => panic!("{}", ovhdata_common::config::Error::ConfigNameNotFound(config_name)),
         }
     }
 
@@ -362,7 +661,16 @@ impl Config {
         match AllConfig::try_from(custom_config_path()) {
             Ok(all_config) => {
                 let mut configs = all_config.configs.clone();
-                configs.extend(default_configs);
+                // Default configs override the custom ones, except for the local settings (retries, network, cache, schema types)
+                for (config_name, mut default_config) in default_configs {
+                    if let Some(custom_config) = configs.get(&config_name) {
+                        default_config.retry = custom_config.retry.clone();
+                        default_config.http = custom_config.http.clone();
+                        default_config.cache = custom_config.cache.clone();
+                        default_config.schema_types = custom_config.schema_types.clone();
+                    }
+                    configs.insert(config_name, default_config);
+                }
                 Ok(AllConfig {
                     current_config_name: all_config.current_config_name,
                     configs,
@@ -375,6 +683,11 @@ impl Config {
         }
     }
 
+    /// Return true for the embedded region configs, loaded again on each run
+    pub fn is_region_default(config_name: &ConfigName) -> bool {
+        [Region::EU, Region::CA].iter().any(|region| config_name.as_str() == region.to_string())
+    }
+
     fn load_region_default(region: Region) -> Result<ovhdata_common::config::Config> {
         let _config_val = serde_json::from_str(match region {
             Region::CA => CONFIG_CA,
@@ -393,6 +706,22 @@ pub fn config_dir() -> PathBuf {
     path
 }
 
+/// Directory of the API responses cached for a configuration
+pub fn cache_dir(config_name: &ConfigName) -> PathBuf {
+    let mut path = config_dir();
+    path.push("cache");
+    path.push(config_name.as_str());
+    path
+}
+
+/// Directory of the local snapshots taken for a configuration
+pub fn snapshot_dir(config_name: &ConfigName) -> PathBuf {
+    let mut path = config_dir();
+    path.push("snapshots");
+    path.push(config_name.as_str());
+    path
+}
+
 fn default_context_path() -> PathBuf {
     let mut path = config_dir();
     path.push("context.json");
@@ -418,11 +747,17 @@ pub enum Error {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("{0}")]
-    ConfigError(ovhdata_common::config::Error),
+    Config(ovhdata_common::config::Error),
+    #[error("Credentials error: {0}")]
+    Credentials(String),
+    #[error("Unknown profile {0}")]
+    UnknownProfile(String),
+    #[error("Profile {0} already exists")]
+    ProfileAlreadyExists(String),
 }
 
 impl From<ovhdata_common::config::Error> for Error {
     fn from(err: ovhdata_common::config::Error) -> Self {
-        Error::ConfigError(err)
+        Error::Config(err)
     }
 }
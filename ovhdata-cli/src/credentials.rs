@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use tracing::warn;
+
+use crate::config::{Config, Context, Ovhapiv6Credentials, Result};
+
+// Environment variables shared with the other OVH SDKs
+pub const APPLICATION_KEY_ENV: &str = "OVH_APPLICATION_KEY";
+pub const APPLICATION_SECRET_ENV: &str = "OVH_APPLICATION_SECRET";
+pub const CONSUMER_KEY_ENV: &str = "OVH_CONSUMER_KEY";
+pub const ENDPOINT_ENV: &str = "OVH_ENDPOINT";
+pub const SERVICE_NAME_ENV: &str = "OVHDATA_SERVICE_NAME";
+
+// Credential file of the other OVH SDKs
+const OVH_CONF_FILE: &str = "ovh.conf";
+const OVH_CONF_DEFAULT_SECTION: &str = "default";
+
+// Endpoints known by the OVH SDKs
+const ENDPOINTS: [(&str, &str); 7] = [
+    ("ovh-eu", "https://eu.api.ovh.com/1.0"),
+    ("ovh-ca", "https://ca.api.ovh.com/1.0"),
+    ("ovh-us", "https://api.us.ovhcloud.com/1.0"),
+    ("kimsufi-eu", "https://eu.api.kimsufi.com/1.0"),
+    ("kimsufi-ca", "https://ca.api.kimsufi.com/1.0"),
+    ("soyoustart-eu", "https://eu.api.soyoustart.com/1.0"),
+    ("soyoustart-ca", "https://ca.api.soyoustart.com/1.0"),
+];
+
+/// Where a value comes from, by order of precedence
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    Flag,
+    Env(&'static str),
+    File(PathBuf),
+    Context,
+    Config(String),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Flag => f.write_str("command line option"),
+            Source::Env(name) => write!(f, "environment variable {}", name),
+            Source::File(path) => write!(f, "file {}", path.display()),
+            Source::Context => f.write_str("CLI context"),
+            Source::Config(name) => write!(f, "CLI configuration {}", name),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sourced {
+    pub value: String,
+    pub source: Source,
+}
+
+impl Sourced {
+    pub fn new(value: String, source: Source) -> Self {
+        Self { value, source }
+    }
+}
+
+/// OVH API credentials resolved from the environment, the ovh.conf files and the context
+pub struct ResolvedCredentials {
+    pub endpoint_url: Sourced,
+    pub application_key: Option<Sourced>,
+    pub application_secret: Option<Sourced>,
+    pub consumer_key: Option<Sourced>,
+    pub service_name: Option<Sourced>,
+}
+
+impl ResolvedCredentials {
+    /// Resolve the service name with the precedence: flag > env > context.
+    /// The endpoint and the keys are taken together from the first source giving all of them:
+    /// env > ovh.conf file > CLI configuration and context, so that the keys always match their endpoint.
+    /// A profile given on the command line only uses the CLI configuration and context.
+    /// The context is only read (and its passphrase asked) if no other source gives the credentials
+    pub fn resolve(context: &mut Context, use_context: bool) -> Result<Self> {
+        let config = Config::get();
+        let endpoint_url = Sourced::new(config.ovhapiv6.endpoint_url.clone(), Source::Config(config.name.as_str().to_string()));
+        let service_name = context.get_current_service_name_with_source();
+
+        let profile_given = context.is_profile_given();
+        let ovh_conf = if profile_given { OvhConf::default() } else { OvhConf::load() };
+        let env = |name| env(name).filter(|_| !profile_given);
+        let mut resolved = Self::from_sources(env, &ovh_conf, endpoint_url, || match use_context {
+            true => context.get_ovhapi_credentials(),
+            false => Ok(None),
+        })?;
+        resolved.service_name = service_name;
+        Ok(resolved)
+    }
+
+    fn from_sources<E, C>(env: E, ovh_conf: &OvhConf, endpoint_url: Sourced, context_credentials: C) -> Result<Self>
+    where
+        E: Fn(&'static str) -> Option<Sourced>,
+        C: FnOnce() -> Result<Option<Ovhapiv6Credentials>>,
+    {
+        if let Some(resolved) = Self::from_env(env).or_else(|| ovh_conf.credentials()) {
+            return Ok(resolved);
+        }
+
+        let (application_key, application_secret, consumer_key) = match context_credentials()? {
+            Some(creds) => (creds.application_key, creds.application_secret, creds.consumer_key),
+            None => (None, None, None),
+        };
+        let from_context = |value: Option<String>| value.map(|value| Sourced::new(value, Source::Context));
+        Ok(Self {
+            endpoint_url,
+            application_key: from_context(application_key),
+            application_secret: from_context(application_secret),
+            consumer_key: from_context(consumer_key),
+            service_name: None,
+        })
+    }
+
+    /// Endpoint and keys of the environment, if it gives all of them
+    fn from_env<E: Fn(&'static str) -> Option<Sourced>>(env: E) -> Option<Self> {
+        let missing = missing_env(&env);
+        if !missing.is_empty() {
+            warn!("Credentials of the environment ignored, missing {}", missing.join(", "));
+        }
+        let endpoint = env(ENDPOINT_ENV)?;
+        Some(Self {
+            endpoint_url: Sourced::new(endpoint_url(&endpoint.value), endpoint.source),
+            application_key: Some(env(APPLICATION_KEY_ENV)?),
+            application_secret: Some(env(APPLICATION_SECRET_ENV)?),
+            consumer_key: Some(env(CONSUMER_KEY_ENV)?),
+            service_name: None,
+        })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.application_key.is_some() && self.application_secret.is_some() && self.consumer_key.is_some()
+    }
+
+    pub fn credentials(&self) -> Ovhapiv6Credentials {
+        Ovhapiv6Credentials {
+            application_key: self.application_key.as_ref().map(|key| key.value.clone()),
+            application_secret: self.application_secret.as_ref().map(|secret| secret.value.clone()),
+            consumer_key: self.consumer_key.as_ref().map(|key| key.value.clone()),
+        }
+    }
+
+    /// Name and source of each value, the secrets are not given
+    pub fn sources(&self) -> Vec<(&'static str, Option<&Source>)> {
+        vec![
+            ("Endpoint", Some(&self.endpoint_url.source)),
+            ("Application key", self.application_key.as_ref().map(|value| &value.source)),
+            ("Application secret", self.application_secret.as_ref().map(|value| &value.source)),
+            ("Consumer key", self.consumer_key.as_ref().map(|value| &value.source)),
+            ("Service name", self.service_name.as_ref().map(|value| &value.source)),
+        ]
+    }
+}
+
+/// Credential variables missing from a partly set environment, none if it gives all or none of them
+fn missing_env<E: Fn(&'static str) -> Option<Sourced>>(env: &E) -> Vec<&'static str> {
+    let names = [ENDPOINT_ENV, APPLICATION_KEY_ENV, APPLICATION_SECRET_ENV, CONSUMER_KEY_ENV];
+    let missing = names.into_iter().filter(|name| env(name).is_none()).collect::<Vec<_>>();
+    match missing.len() == names.len() {
+        true => vec![],
+        false => missing,
+    }
+}
+
+/// Url of an endpoint given by its name (ovh-eu, ovh-ca...) or directly by its url
+pub fn endpoint_url(endpoint: &str) -> String {
+    ENDPOINTS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(endpoint))
+        .map(|(_, url)| url.to_string())
+        .unwrap_or(endpoint.to_string())
+}
+
+/// Non empty value of an environment variable
+pub fn env(name: &'static str) -> Option<Sourced> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(|value| Sourced::new(value, Source::Env(name)))
+}
+
+// Values of an INI file by section and key
+type Ini = HashMap<String, HashMap<String, String>>;
+
+/// The ovh.conf INI files, by order of precedence: current directory, home directory, /etc
+#[derive(Default)]
+struct OvhConf {
+    files: Vec<(PathBuf, Ini)>,
+}
+
+impl OvhConf {
+    fn load() -> Self {
+        let mut paths = vec![PathBuf::from(OVH_CONF_FILE)];
+        if let Some(home) = dirs::home_dir() {
+            paths.push(home.join(format!(".{}", OVH_CONF_FILE)));
+        }
+        paths.push(PathBuf::from("/etc").join(OVH_CONF_FILE));
+
+        let files = paths
+            .into_iter()
+            .filter_map(|path| std::fs::read_to_string(&path).ok().map(|content| (path, parse_ini(&content))))
+            .collect();
+        Self { files }
+    }
+
+    /// Endpoint and keys of the first file giving all of them, the keys being in the section named after the endpoint
+    fn credentials(&self) -> Option<ResolvedCredentials> {
+        self.files.iter().find_map(|(path, sections)| {
+            let get = |section: &str, key: &str| {
+                sections
+                    .get(section)
+                    .and_then(|values| values.get(key))
+                    .filter(|value| !value.is_empty())
+                    .map(|value| Sourced::new(value.clone(), Source::File(path.clone())))
+            };
+            let endpoint = get(OVH_CONF_DEFAULT_SECTION, "endpoint")?;
+            Some(ResolvedCredentials {
+                endpoint_url: Sourced::new(endpoint_url(&endpoint.value), endpoint.source),
+                application_key: Some(get(&endpoint.value, "application_key")?),
+                application_secret: Some(get(&endpoint.value, "application_secret")?),
+                consumer_key: Some(get(&endpoint.value, "consumer_key")?),
+                service_name: None,
+            })
+        })
+    }
+}
+
+/// Parse an INI content: `[section]` headers, `key=value` lines and `;` or `#` comments
+fn parse_ini(content: &str) -> Ini {
+    let mut sections = Ini::new();
+    let mut current = String::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            current = section.trim().to_string();
+        } else if let Some((key, value)) = line.split_once('=').or_else(|| line.split_once(':')) {
+            sections
+                .entry(current.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use crate::config::Ovhapiv6Credentials;
+    use crate::credentials::{missing_env, parse_ini, OvhConf, ResolvedCredentials, Source, Sourced};
+
+    const CA_URL: &str = "https://ca.api.ovh.com/1.0";
+
+    fn env(values: &[(&'static str, &str)]) -> impl Fn(&'static str) -> Option<Sourced> {
+        let values = values
+            .iter()
+            .map(|(name, value)| (*name, Sourced::new(value.to_string(), Source::Env(name))))
+            .collect::<HashMap<_, _>>();
+        move |name| values.get(name).cloned()
+    }
+
+    fn ovh_conf(content: &str) -> OvhConf {
+        OvhConf {
+            files: vec![(PathBuf::from("ovh.conf"), parse_ini(content))],
+        }
+    }
+
+    fn resolve(env_values: &[(&'static str, &str)], ovh_conf_content: &str) -> ResolvedCredentials {
+        let config_endpoint = Sourced::new(CA_URL.to_string(), Source::Config("OVH-CA".to_string()));
+        let context_credentials = Ovhapiv6Credentials {
+            application_key: Some("context-ak".to_string()),
+            application_secret: Some("context-as".to_string()),
+            consumer_key: Some("context-ck".to_string()),
+        };
+        ResolvedCredentials::from_sources(env(env_values), &ovh_conf(ovh_conf_content), config_endpoint, || {
+            Ok(Some(context_credentials))
+        })
+        .unwrap()
+    }
+
+    fn values(resolved: &ResolvedCredentials) -> (String, Option<String>, Source) {
+        let application_key = resolved.application_key.as_ref();
+        (
+            resolved.endpoint_url.value.clone(),
+            application_key.map(|key| key.value.clone()),
+            application_key.map(|key| key.source.clone()).unwrap(),
+        )
+    }
+
+    const FULL_OVH_CONF: &str = "[default]\nendpoint=ovh-eu\n[ovh-eu]\napplication_key=file-ak\napplication_secret=file-as\nconsumer_key=file-ck\n";
+
+    #[test]
+    fn env_then_ovh_conf_then_context() {
+        let full_env = [
+            ("OVH_ENDPOINT", "ovh-ca"),
+            ("OVH_APPLICATION_KEY", "env-ak"),
+            ("OVH_APPLICATION_SECRET", "env-as"),
+            ("OVH_CONSUMER_KEY", "env-ck"),
+        ];
+        assert_eq!(
+            values(&resolve(&full_env, FULL_OVH_CONF)),
+            (CA_URL.to_string(), Some("env-ak".to_string()), Source::Env("OVH_APPLICATION_KEY"))
+        );
+        assert_eq!(
+            values(&resolve(&[], FULL_OVH_CONF)),
+            (
+                "https://eu.api.ovh.com/1.0".to_string(),
+                Some("file-ak".to_string()),
+                Source::File(PathBuf::from("ovh.conf"))
+            )
+        );
+        assert_eq!(
+            values(&resolve(&[], "")),
+            (CA_URL.to_string(), Some("context-ak".to_string()), Source::Context)
+        );
+    }
+
+    #[test]
+    fn partial_sources_are_not_mixed() {
+        // Keys without endpoint, or endpoint without keys, leave the config endpoint with the context credentials
+        let keys_only = [
+            ("OVH_APPLICATION_KEY", "env-ak"),
+            ("OVH_APPLICATION_SECRET", "env-as"),
+            ("OVH_CONSUMER_KEY", "env-ck"),
+        ];
+        assert_eq!(
+            values(&resolve(&keys_only, "")),
+            (CA_URL.to_string(), Some("context-ak".to_string()), Source::Context)
+        );
+        assert_eq!(
+            values(&resolve(&[("OVH_ENDPOINT", "ovh-eu")], "")),
+            (CA_URL.to_string(), Some("context-ak".to_string()), Source::Context)
+        );
+        // The ovh-ca config is selected while ovh.conf only gives the ovh-eu endpoint
+        assert_eq!(
+            values(&resolve(&[], "[default]\nendpoint=ovh-eu\n")),
+            (CA_URL.to_string(), Some("context-ak".to_string()), Source::Context)
+        );
+
+        let resolved = resolve(&[], "[default]\nendpoint=ovh-eu\n[ovh-eu]\napplication_key=file-ak\n");
+        assert_eq!(resolved.endpoint_url.source, Source::Config("OVH-CA".to_string()));
+        assert_eq!(resolved.consumer_key.map(|key| key.value), Some("context-ck".to_string()));
+    }
+
+    #[test]
+    fn partial_env_is_reported() {
+        assert!(missing_env(&env(&[])).is_empty());
+        assert_eq!(
+            missing_env(&env(&[("OVH_ENDPOINT", "ovh-eu"), ("OVH_APPLICATION_KEY", "env-ak")])),
+            vec!["OVH_APPLICATION_SECRET", "OVH_CONSUMER_KEY"]
+        );
+    }
+}
@@ -13,25 +13,30 @@ use tracing::{error, info};
 use tracing_subscriber::fmt::writer::Tee;
 use tracing_subscriber::EnvFilter;
 
+use ovhdata_common::api::{Cassette, ResponseCache, RetryPolicy};
+use ovhdata_common::config::ConfigName;
 use ovhdata_common::ovhapi::OVHapiV6Client;
 use ovhdata_common::BUG;
 
+use crate::command::api::ApiCommand;
 use crate::command::auth;
 use crate::command::completion::CompletionCommand;
 use crate::command::config::ConfigCommand;
 use crate::command::debug::DebugCommand;
-use crate::command::di::DiCommand;
+use crate::command::di::{ComparedCatalog, DiCommand};
 use crate::command::me::MeCommand;
 use crate::command::upgrade;
 
 use crate::options::*;
 
-use crate::config::{Config, Context, CLI_NAME};
+use crate::config::{cache_dir, Config, Context, Ovhapiv6Credentials, CLI_NAME};
+use crate::credentials::ResolvedCredentials;
 use crate::logging::SESSION_ID;
-use crate::utils::ui::printer::{Printer, HELP_NO_AUTH_HOW_TO, HELP_NO_SERVICE_NAME_HOW_TO, NO_COLOR, NO_SPINNER};
+use crate::utils::ui::printer::{Output, Printer, HELP_NO_AUTH_HOW_TO, HELP_NO_SERVICE_NAME_HOW_TO, NO_COLOR, NO_SPINNER};
 
 mod command;
 mod config;
+mod credentials;
 mod logging;
 mod options;
 mod utils;
@@ -138,7 +143,7 @@ fn init_log(verbosity: u8, json: bool) {
         return;
     }
     // Configure tracing subscriber to append on the log file
-    match OpenOptions::new().create(true).write(true).append(true).open(log_file_path.as_path()) {
+    match OpenOptions::new().create(true).append(true).open(log_file_path.as_path()) {
         Ok(log_file) => {
             let result = match verbosity {
                 0 => ovhdata_common::log::init_subscriber(log_file, json, EnvFilter::new("info")),
@@ -167,18 +172,54 @@ fn init_log(verbosity: u8, json: bool) {
 
 /// Execute a command
 async fn execute_command(opts: Opts) -> Result<()> {
-    // Use service name given if set
+    // Use the profile given if set, or the current one
+    Context::get().activate_profile(opts.profile)?;
+
+    // Use service name given if set, it takes precedence over the environment for this command
     if let Some(service_name) = opts.service_name {
         let mut context = Context::get();
-        context.set_service_name(service_name);
+        context.set_service_name(service_name.clone());
+        context.set_service_name_override(service_name);
+    };
+
+    // Retry policy from the configuration, overridden by the options
+    let mut retry_policy = Config::get().retry.clone();
+    if let Some(max_retries) = opts.max_retries {
+        retry_policy.max_retries = max_retries;
+    }
+    if opts.retry_non_idempotent {
+        retry_policy.retry_non_idempotent = true;
+    }
+
+    // Cassette recording or replaying the API requests
+    let cassette = match (opts.record, opts.replay) {
+        (Some(dir), _) => Some(Cassette::record(dir)?),
+        (_, Some(dir)) => Some(Cassette::replay(dir)?),
+        _ => None,
+    };
+
+    // Local cache of the connector catalog, the recorded or replayed sessions always use the API
+    let response_cache = match cassette {
+        Some(_) => None,
+        None => {
+            let config = Config::get();
+            Some(ResponseCache::new(cache_dir(&config.name), config.cache.connectors_ttl()).with_refresh(opts.refresh))
+        }
     };
 
     match opts.subcmd {
         // Upgrade
         SubCommand::Upgrade(Upgrade { force }) => upgrade::Upgrade::new().upgrade(force, true, false).await?,
         // Login
+        SubCommand::Login(login) if login.show_sources => auth::Auth::new().sources().await?,
+        SubCommand::Login(login) if login.create_credential => {
+            let command = auth::Auth::new().with_retry_policy(retry_policy);
+            command
+                .login_with_new_credential(login.application_key, login.secret, login.output.unwrap_or_default().into())
+                .await?
+        }
         SubCommand::Login(login) => {
-            let command = auth::Auth::new();
+            let command = auth::Auth::new().with_retry_policy(retry_policy);
             command
                 .login(
                     login.application_key,
@@ -192,25 +233,68 @@ async fn execute_command(opts: Opts) -> Result<()> {
         // Logout
         SubCommand::Logout(_logout) => auth::Auth::new().logout().await?,
 
+        // Credentials storage
+        SubCommand::Auth(AuthShim { subcmd }) => match subcmd {
+            AuthSubCommand::Lock(_) => auth::Auth::new().lock().await?,
+            AuthSubCommand::Unlock(_) => auth::Auth::new().unlock().await?,
+        },
+
         // Debug
         SubCommand::Debug(Debug { session_id }) => DebugCommand::new().log(session_id).await?,
 
         // Me
         SubCommand::Me(me) => {
-            let command = MeCommand::new(build_ovhapi_client().await?);
-            command.me(me.output.unwrap_or_default().into()).await?
+            let client = build_ovhapi_client(retry_policy, cassette).await?;
+            let result = MeCommand::new(client.clone()).me(me.output.unwrap_or_default().into()).await;
+            save_time_delta(&client)?;
+            result?
+        }
+
+        // Raw API request
+        SubCommand::Api(api) => {
+            let client = build_ovhapi_client(retry_policy, cassette).await?;
+            let query = api
+                .query
+                .into_iter()
+                .map(|query| (query.name, query.value))
+                .collect::<Vec<(String, String)>>();
+            let output = match api.output.unwrap_or(OutputObject::Json) {
+                OutputObject::Yaml => Output::Yaml,
+                _ => Output::Json,
+            };
+            let result = ApiCommand::new(client.clone())
+                .execute(api.method, &api.path, api.body.as_deref(), &query, output)
+                .await;
+            save_time_delta(&client)?;
+            result?
         }
 
         // Data Integration
         SubCommand::Di(DiShim { subcmd }) => {
-            let command = DiCommand::new(build_ovhapi_cloud_client().await?);
-            command.execute_command(subcmd).await?
+            let compared = match subcmd.against_config() {
+                Some(config_name) => Some(build_compared_catalog(ConfigName::from(config_name), retry_policy.clone(), opts.refresh).await?),
+                None => None,
+            };
+            let client = build_ovhapi_cloud_client(retry_policy, cassette)
+                .await?
+                .with_response_cache(response_cache);
+            let result = DiCommand::new(client.clone())
+                .with_compared_catalog(compared)
+                .execute_command(subcmd)
+                .await;
+            save_time_delta(&client)?;
+            result?
         }
 
+        // Config commands not using the credentials
+        SubCommand::Config(ConfigShim { subcmd }) if !subcmd.requires_credentials() => ConfigCommand::execute_local_command(subcmd).await?,
+
         // Config
         SubCommand::Config(ConfigShim { subcmd }) => {
-            let command = ConfigCommand::new(build_ovhapi_cloud_client().await?);
-            command.execute_command(subcmd).await?
+            let client = build_ovhapi_cloud_client(retry_policy, cassette).await?;
+            let result = ConfigCommand::new(client.clone()).execute_command(subcmd).await;
+            save_time_delta(&client)?;
+            result?
         }
 
         // Completion
@@ -224,8 +308,8 @@ async fn execute_command(opts: Opts) -> Result<()> {
 }
 
 /// Build an OVHapi v6 cloud client (with service_name)
-async fn build_ovhapi_cloud_client() -> Result<OVHapiV6Client> {
-    let apiv6client = build_ovhapi_client().await?;
+async fn build_ovhapi_cloud_client(retry_policy: RetryPolicy, cassette: Option<Cassette>) -> Result<OVHapiV6Client> {
+    let apiv6client = build_ovhapi_client(retry_policy, cassette).await?;
 
     let context = Context::get();
     let service_name = context.get_current_service_name();
@@ -242,28 +326,98 @@ async fn build_ovhapi_cloud_client() -> Result<OVHapiV6Client> {
 }
 
 /// Build an OVHapi v6 client
-async fn build_ovhapi_client() -> Result<OVHapiV6Client> {
-    let context = Context::get();
-    let ovhapicreds_option = context.get_ovhapi_credentials();
-
-    // No ovh api creds exit
-    if ovhapicreds_option.is_none() {
+async fn build_ovhapi_client(retry_policy: RetryPolicy, cassette: Option<Cassette>) -> Result<OVHapiV6Client> {
+    let mut context = Context::get();
+    let replay = matches!(cassette, Some(Cassette::Replay { .. }));
+    // Replayed responses don't need the credentials, nor the passphrase of the context
+    let resolved = ResolvedCredentials::resolve(&mut context, !replay)?;
+
+    // No ovh api creds exit, unless the responses are replayed
+    if !resolved.is_complete() && !replay {
         eprintln!();
         let help = Printer::gen_help(HELP_NO_AUTH_HOW_TO);
         eprintln!("{}", help);
         exit(EXIT_CODE_ERROR);
     }
 
-    let ovhapicreds = ovhapicreds_option.unwrap();
+    let ovhapicreds = resolved.credentials();
+    let config = Config::get();
     let ovhapiv6_client = OVHapiV6Client::new(
-        Config::get().ovhapiv6.endpoint_url.clone(),
-        ovhapicreds.application_key.unwrap(),
-        ovhapicreds.application_secret.unwrap(),
-        ovhapicreds.consumer_key.unwrap(),
-    );
+        resolved.endpoint_url.value,
+        ovhapicreds.application_key.unwrap_or_default(),
+        ovhapicreds.application_secret.unwrap_or_default(),
+        ovhapicreds.consumer_key.unwrap_or_default(),
+    )
+    // A replayed session doesn't ask the server time
+    .with_time_delta(if replay { Some(0) } else { context.get_ovhapi_time_delta() })
+    .with_http_client(config.http.client()?)
+    .with_retry_policy(retry_policy)
+    .with_cassette(cassette);
     Ok(ovhapiv6_client)
 }
 
+/// Build an OVHapi v6 client on another config, with its region credentials and service name, to compare its connector catalog
+async fn build_compared_catalog(config_name: ConfigName, retry_policy: RetryPolicy, refresh: bool) -> Result<ComparedCatalog<OVHapiV6Client>> {
+    let switched = Context::get().switch_config(config_name.clone())?;
+    let compared = build_compared_client(&config_name, retry_policy, refresh).await;
+    Context::get().restore_config(switched);
+    compared
+}
+
+/// The environment and the ovh.conf files give the credentials of the current config only,
+/// the other config uses its own endpoint and the credentials saved with it
+async fn build_compared_client(config_name: &ConfigName, retry_policy: RetryPolicy, refresh: bool) -> Result<ComparedCatalog<OVHapiV6Client>> {
+    let mut context = Context::get();
+    let (application_key, application_secret, consumer_key) = match context.get_ovhapi_credentials()? {
+        Some(Ovhapiv6Credentials {
+            application_key: Some(application_key),
+            application_secret: Some(application_secret),
+            consumer_key: Some(consumer_key),
+        }) => (application_key, application_secret, consumer_key),
+        _ => {
+            return Err(utils::Error::custom(format!(
+                "No credentials for the config {}, login with it first",
+                config_name.as_str()
+            )))
+        }
+    };
+    let service_name = context.get_saved_service_name().ok_or_else(|| {
+        utils::Error::custom(format!(
+            "No service name for the config {}, select one with it first",
+            config_name.as_str()
+        ))
+    })?;
+
+    let config = Config::get();
+    let response_cache = ResponseCache::new(cache_dir(config_name), config.cache.connectors_ttl()).with_refresh(refresh);
+    let client = OVHapiV6Client::new(config.ovhapiv6.endpoint_url.clone(), application_key, application_secret, consumer_key)
+        .with_time_delta(context.get_ovhapi_time_delta())
+        .with_http_client(config.http.client()?)
+        .with_retry_policy(retry_policy)
+        .with_response_cache(Some(response_cache));
+    Ok(ComparedCatalog {
+        client,
+        config_name: config_name.clone(),
+        service_name,
+    })
+}
+
+/// Save the OVH API time delta computed by the client, so that next commands don't have to ask it again
+fn save_time_delta(client: &OVHapiV6Client) -> Result<()> {
+    if matches!(client.cassette(), Some(Cassette::Replay { .. })) {
+        return Ok(());
+    }
+
+    let mut context = Context::get();
+    if let Some(time_delta) = client.time_delta() {
+        if context.get_ovhapi_time_delta() != Some(time_delta) {
+            context.set_ovhapi_time_delta(time_delta);
+            context.save()?;
+        }
+    }
+    Ok(())
+}
+
 /// Unwrap the result or print and error and exit
 fn unwrap_or_exit<T>(result: Result<T>, verbosity: u8) -> T {
     match result {
@@ -272,6 +426,12 @@ fn unwrap_or_exit<T>(result: Result<T>, verbosity: u8) -> T {
             error!("{}", err);
             eprintln!();
             Printer::eprintln_fail(&format!("{}", err));
+            if let Some(hint) = err.hint() {
+                eprintln!("Hint: {}", hint);
+            }
+            if let Some(query_id) = err.query_id() {
+                eprintln!("OVH query ID (to give to the support): {}", query_id);
+            }
             eprintln!();
             if verbosity > 0 {
                 eprintln!("To print the full logs of this command:");
@@ -279,7 +439,7 @@ fn unwrap_or_exit<T>(result: Result<T>, verbosity: u8) -> T {
             } else {
                 eprintln!("You may use -v option. Useful for debugging and seeing what's is going on \"under the hood\".");
             }
-            exit(EXIT_CODE_ERROR);
+            exit(err.exit_code());
         }
     }
 }
@@ -290,3 +450,8 @@ pub type ExitCode = i32;
 pub const EXIT_CODE_SUCCESS: ExitCode = 0;
 pub const EXIT_CODE_ERROR: ExitCode = 1;
 pub const EXIT_CODE_SKIPPED: ExitCode = 10;
+pub const EXIT_CODE_JOB_FAILED: ExitCode = 20;
+pub const EXIT_CODE_JOB_INTERRUPTED: ExitCode = 21;
+pub const EXIT_CODE_TIMEOUT: ExitCode = 22;
+pub const EXIT_CODE_EXTRACTION_FAILED: ExitCode = 23;
+pub const EXIT_CODE_BREAKING_CHANGES: ExitCode = 24;
@@ -0,0 +1,21 @@
+use clap::Parser;
+
+#[derive(Parser)]
+pub struct AuthShim {
+    #[clap(subcommand)]
+    pub subcmd: AuthSubCommand,
+}
+
+#[derive(Parser)]
+pub enum AuthSubCommand {
+    /// Encrypt the stored credentials with a passphrase
+    Lock(AuthLock),
+    /// Decrypt the stored credentials, they are stored in clear again
+    Unlock(AuthUnlock),
+}
+
+#[derive(Parser)]
+pub struct AuthLock {}
+
+#[derive(Parser)]
+pub struct AuthUnlock {}
@@ -19,12 +19,31 @@ pub enum ConfigSubCommand {
     Set(ConfigSet),
     /// Set cloud service name as default
     SetServiceName(ConfigServiceName),
+    /// Manage the profiles, several accounts on the same region
+    Profile(ProfileShim),
+    /// Add a config with a custom API endpoint
+    Add(ConfigAdd),
+    /// Remove a custom config
+    #[clap(visible_alias = "rm")]
+    Remove(ConfigRemove),
+    /// Check that the API endpoint of a config is reachable
+    Validate(ConfigValidate),
+}
+
+impl ConfigSubCommand {
+    /// Return true if the command calls the API with the credentials and the service name
+    pub fn requires_credentials(&self) -> bool {
+        !matches!(
+            self,
+            ConfigSubCommand::Profile(_) | ConfigSubCommand::Add(_) | ConfigSubCommand::Remove(_) | ConfigSubCommand::Validate(_)
+        )
+    }
 }
 
 #[derive(Parser)]
 pub struct ConfigList {
     /// Command output format
-    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "table"]).map(|s| s.parse::<OutputList>().unwrap()))]
+    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "list"]).map(|s| s.parse::<OutputList>().unwrap()))]
     pub output: Option<OutputList>,
 }
 
@@ -49,3 +68,80 @@ pub struct ConfigServiceName {
     /// Service name to set (interactive input if not set)
     pub service_name: Option<String>,
 }
+
+#[derive(Parser)]
+pub struct ConfigAdd {
+    /// Config name
+    pub name: String,
+    /// OVHcloud API endpoint, e.g. https://api.us.ovhcloud.com/1.0
+    #[clap(long)]
+    pub endpoint_url: String,
+    /// Page creating the API credentials, e.g. https://api.us.ovhcloud.com/createToken
+    #[clap(long)]
+    pub create_token_url: String,
+    /// Url of the CLI releases (the current config one if not set)
+    #[clap(long)]
+    pub release_url: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct ConfigRemove {
+    /// Config name
+    pub name: String,
+}
+
+#[derive(Parser)]
+pub struct ConfigValidate {
+    /// Config name (current one if not set)
+    pub name: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct ProfileShim {
+    #[clap(subcommand)]
+    pub subcmd: ProfileSubCommand,
+}
+
+#[derive(Parser)]
+pub enum ProfileSubCommand {
+    /// Add a profile, then login with `--profile <NAME> login`
+    Add(ProfileAdd),
+    /// List the profiles
+    #[clap(visible_alias = "ls")]
+    List(ProfileList),
+    /// Set the profile used by the next commands ('default' to use the region credentials again)
+    Use(ProfileUse),
+    /// Delete a profile with its credentials
+    Delete(ProfileDelete),
+}
+
+#[derive(Parser)]
+pub struct ProfileAdd {
+    /// Profile name
+    pub name: String,
+    /// Region config of the profile (current one if not set)
+    #[clap(long)]
+    pub config: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct ProfileList {
+    /// Command output format
+    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "list"]).map(|s| s.parse::<OutputList>().unwrap()))]
+    pub output: Option<OutputList>,
+}
+
+#[derive(Parser)]
+pub struct ProfileUse {
+    /// Profile name
+    pub name: String,
+}
+
+#[derive(Parser)]
+pub struct ProfileDelete {
+    /// Profile name
+    pub name: String,
+    /// Never prompt confirmation
+    #[clap(long, short, action)]
+    pub script: bool,
+}
@@ -1,7 +1,11 @@
 use crate::options::utils::NameValue;
 use crate::options::{OutputList, OutputObject};
 use clap::builder::{PossibleValuesParser, TypedValueParser};
-use clap::Parser;
+use clap::{ArgGroup, Parser};
+use ovhdata_common::model::di::metadata_diff::DEFAULT_CARDINALITY_THRESHOLD;
+use ovhdata_common::model::di::metadata_schema::SchemaFormat;
+use ovhdata_common::utils::snapshot::LATEST_SNAPSHOT;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 pub struct DiShim {
@@ -29,6 +33,36 @@ pub enum DiSubCommands {
     /// Jobs information for a specific workflow
     #[clap(subcommand)]
     Job(DiSubJobCommands),
+    /// Create or update the sources, destinations and workflows declared in YAML manifests
+    Apply(DiApply),
+    /// Write the sources, destinations and workflows as YAML manifests
+    Export(DiExport),
+}
+
+#[derive(Parser)]
+pub struct DiApply {
+    /// Manifest file, directory of manifests, or '-' for the standard input (may be repeated)
+    #[clap(short, long = "file", value_name = "FILE", required = true)]
+    pub files: Vec<PathBuf>,
+    /// Only print the plan, without applying it
+    #[clap(long, action)]
+    pub dry_run: bool,
+    /// Update the existing secret parameters, their values can't be compared with the manifests
+    #[clap(long, action)]
+    pub force_secrets: bool,
+    /// Never prompt confirmation
+    #[clap(long, short, action)]
+    pub script: bool,
+}
+
+#[derive(Parser)]
+pub struct DiExport {
+    /// Directory where to write one manifest per resource, instead of a single bundle
+    #[clap(long, value_name = "DIR", conflicts_with = "file")]
+    pub dir: Option<PathBuf>,
+    /// File where to write the bundle (standard output if not set)
+    #[clap(short, long, value_name = "FILE")]
+    pub file: Option<PathBuf>,
 }
 
 #[derive(Parser)]
@@ -57,9 +91,11 @@ pub enum DiSubSourceCommands {
 #[derive(Parser)]
 pub enum SourceSubMetaCommands {
     /// Get the source's metadata
-    Get(SourceGet),
+    Get(MetadataGet),
     /// Trigger the source's metadata extraction
-    Extract(SourceGet),
+    Extract(MetadataExtract),
+    /// Compare the source's metadata with a snapshot saved by a previous get
+    Diff(MetadataDiff),
 }
 
 #[derive(Parser)]
@@ -67,6 +103,12 @@ pub struct SourceList {
     /// Filters to apply to the sources list (jsonpath filter)
     #[clap(long = "filter")]
     pub filter: Option<String>,
+    /// Maximum number of items to return (all items if not set)
+    #[clap(long)]
+    pub limit: Option<u32>,
+    /// Number of items fetched per page from the API (server default if not set)
+    #[clap(long)]
+    pub page_size: Option<u32>,
     /// Field by witch the list will be ordered (default: by name)
     #[clap(long, value_parser = PossibleValuesParser::new(["age", "update", "status", "connector", "name"]))]
     pub sort: Option<String>,
@@ -90,6 +132,54 @@ pub struct SourceGet {
     pub output: Option<OutputObject>,
 }
 
+#[derive(Parser)]
+pub struct MetadataGet {
+    /// Source ID (interactive input if not set)
+    pub id: Option<String>,
+    /// Command output format
+    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
+    pub output: Option<OutputObject>,
+    /// Export each table as a schema instead of the metadata
+    #[clap(long, conflicts_with = "output", value_parser = PossibleValuesParser::new(&["jsonschema", "sql-ddl", "avro"]).map(|s| s.parse::<SchemaFormat>().unwrap()))]
+    pub format: Option<SchemaFormat>,
+    /// Schema type of a metadata type, overriding the configured and default ones, a JSON object for JSON Schema and Avro
+    #[clap(long = "type-map", num_args = 1, value_name = "metadata_type=type", requires = "format")]
+    pub type_maps: Vec<NameValue>,
+    /// Directory to write a schema file per table in, instead of the standard output
+    #[clap(long, requires = "format")]
+    pub output_dir: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct MetadataDiff {
+    /// Source ID (interactive input if not set)
+    pub id: Option<String>,
+    /// Snapshot to compare with, the last one by default
+    #[clap(long, default_value = LATEST_SNAPSHOT, value_name = "snapshot|latest")]
+    pub against: String,
+    /// Relative change of a column cardinality to report, e.g. 0.5 for 50%
+    #[clap(long, default_value_t = DEFAULT_CARDINALITY_THRESHOLD)]
+    pub cardinality_threshold: f64,
+    /// Command output format
+    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "list"]).map(|s| s.parse::<OutputList>().unwrap()))]
+    pub output: Option<OutputList>,
+}
+
+#[derive(Parser)]
+#[clap(group = wait_group())]
+pub struct MetadataExtract {
+    /// Source ID (interactive input if not set)
+    pub id: Option<String>,
+    /// Wait for the extraction of every table, the exit code tells whether it succeeded
+    #[clap(long, action)]
+    pub wait: bool,
+    #[clap(flatten)]
+    pub wait_options: WaitOptions,
+    /// Command output format
+    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
+    pub output: Option<OutputObject>,
+}
+
 #[derive(Parser)]
 pub struct SourceDelete {
     /// Source ID (interactive input if not set)
@@ -106,9 +196,12 @@ pub struct SourceCreate {
     /// Connector ID (interactive input if not set)
     #[clap(long)]
     pub connector_id: Option<String>,
-    /// Connector parameters
+    /// Connector parameters, a value can reference an environment variable (@env:VARIABLE), a file (@file:PATH) or the standard input (-)
     #[clap(short, long = "parameter", num_args = 1, value_name = "name=value")]
     pub parameters: Vec<NameValue>,
+    /// YAML or JSON file of connector parameters by name, '-' for the standard input
+    #[clap(long, value_name = "FILE")]
+    pub parameters_file: Option<PathBuf>,
     /// Command output format
     #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
     pub output: Option<OutputObject>,
@@ -120,9 +213,12 @@ pub struct SourceUpdate {
     pub id: Option<String>,
     /// Source name
     pub name: Option<String>,
-    /// Connector parameters
+    /// Connector parameters, a value can reference an environment variable (@env:VARIABLE), a file (@file:PATH) or the standard input (-)
     #[clap(short, long = "parameter", num_args = 1, value_name = "name=value")]
     pub parameters: Vec<NameValue>,
+    /// YAML or JSON file of connector parameters by name, '-' for the standard input
+    #[clap(long, value_name = "FILE")]
+    pub parameters_file: Option<PathBuf>,
     /// Command output format
     #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
     pub output: Option<OutputObject>,
@@ -192,9 +288,12 @@ pub struct DestCreate {
     /// Connector ID (interactive input if not set)
     #[clap(long)]
     pub connector_id: Option<String>,
-    /// Connector parameters
+    /// Connector parameters, a value can reference an environment variable (@env:VARIABLE), a file (@file:PATH) or the standard input (-)
     #[clap(short, long = "parameter", num_args = 1, value_name = "name=value")]
     pub parameters: Vec<NameValue>,
+    /// YAML or JSON file of connector parameters by name, '-' for the standard input
+    #[clap(long, value_name = "FILE")]
+    pub parameters_file: Option<PathBuf>,
     /// Command output format
     #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
     pub output: Option<OutputObject>,
@@ -206,9 +305,12 @@ pub struct DestUpdate {
     pub id: Option<String>,
     /// Destination name
     pub name: Option<String>,
-    /// Connector parameters
+    /// Connector parameters, a value can reference an environment variable (@env:VARIABLE), a file (@file:PATH) or the standard input (-)
     #[clap(short, long = "parameter", num_args = 1, value_name = "name=value")]
     pub parameters: Vec<NameValue>,
+    /// YAML or JSON file of connector parameters by name, '-' for the standard input
+    #[clap(long, value_name = "FILE")]
+    pub parameters_file: Option<PathBuf>,
     /// Command output format
     #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
     pub output: Option<OutputObject>,
@@ -241,6 +343,12 @@ pub struct WorkflowList {
     /// Filters to apply to the workflows list (jsonpath filter)
     #[clap(long = "filter")]
     pub filter: Option<String>,
+    /// Maximum number of items to return (all items if not set)
+    #[clap(long)]
+    pub limit: Option<u32>,
+    /// Number of items fetched per page from the API (server default if not set)
+    #[clap(long)]
+    pub page_size: Option<u32>,
     /// Field by witch the list will be ordered (default: by name)
     #[clap(long, value_parser = PossibleValuesParser::new(["last-execution", "status", "enabled", "source-name", "destination-name"]))]
     pub sort: Option<String>,
@@ -265,9 +373,21 @@ pub struct WorkflowGet {
 }
 
 #[derive(Parser)]
+#[clap(group = wait_group())]
 pub struct WorkflowRun {
     /// Workflow ID (interactive input if not set)
     pub id: Option<String>,
+    /// Workflow parameters overridden for this job, a value can reference an environment variable (@env:VARIABLE), a file (@file:PATH) or the standard input (-)
+    #[clap(short, long = "parameter", num_args = 1, value_name = "name=value")]
+    pub parameters: Vec<NameValue>,
+    /// Region running the job instead of the workflow one
+    #[clap(long)]
+    pub region: Option<String>,
+    /// Wait for the end of the job, the exit code tells whether it completed
+    #[clap(long, action)]
+    pub wait: bool,
+    #[clap(flatten)]
+    pub wait_options: WaitOptions,
     /// Command output format
     #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
     pub output: Option<OutputObject>,
@@ -339,6 +459,8 @@ pub enum DiSubJobCommands {
     Get(JobGet),
     /// Stop a running job
     Stop(JobStop),
+    /// Wait for the end of a job, the exit code tells whether it completed
+    Wait(JobWait),
 }
 
 #[derive(Parser)]
@@ -349,6 +471,12 @@ pub struct JobList {
     /// Filters to apply to the jobs list (jsonpath filter)
     #[clap(long = "filter")]
     pub filter: Option<String>,
+    /// Maximum number of items to return (all items if not set)
+    #[clap(long)]
+    pub limit: Option<u32>,
+    /// Number of items fetched per page from the API (server default if not set)
+    #[clap(long)]
+    pub page_size: Option<u32>,
     /// Field by witch the list will be ordered (default: by age)
     #[clap(long, value_parser = PossibleValuesParser::new(["age", "status"]))]
     pub sort: Option<String>,
@@ -384,6 +512,39 @@ pub struct JobStop {
     pub workflow_id: Option<String>,
 }
 
+#[derive(Parser)]
+pub struct JobWait {
+    /// Job ID
+    pub id: Option<String>,
+    /// Workflow ID
+    #[clap(long)]
+    pub workflow_id: Option<String>,
+    #[clap(flatten)]
+    pub wait_options: WaitOptions,
+    /// Command output format
+    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
+    pub output: Option<OutputObject>,
+}
+
+/// Options of the commands waiting for a job or an extraction
+#[derive(Parser)]
+pub struct WaitOptions {
+    /// Maximum time to wait, in seconds (no limit if not set)
+    #[clap(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+    /// Time between two status checks, in seconds
+    #[clap(long, value_name = "SECONDS", default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    pub poll_interval: u64,
+}
+
+/// Wait options only allowed with the --wait flag of the command
+fn wait_group() -> ArgGroup {
+    ArgGroup::new("wait_options")
+        .args(["timeout", "poll_interval"])
+        .multiple(true)
+        .requires("wait")
+}
+
 #[derive(Parser)]
 pub enum DiSubSourceConnectorCommands {
     /// List source connectors
@@ -391,6 +552,8 @@ pub enum DiSubSourceConnectorCommands {
     List(SourceConnectorList),
     /// Get source connector
     Get(SourceConnectorGet),
+    /// Compare the source connectors with a saved snapshot or another config
+    Diff(ConnectorDiff),
 }
 
 #[derive(Parser)]
@@ -416,6 +579,8 @@ pub enum DiSubDestConnectorCommands {
     List(DestConnectorList),
     /// Get destination connector
     Get(DestConnectorGet),
+    /// Compare the destination connectors with a saved snapshot or another config
+    Diff(ConnectorDiff),
 }
 
 #[derive(Parser)]
@@ -433,3 +598,59 @@ pub struct DestConnectorGet {
     #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
     pub output: Option<OutputObject>,
 }
+
+#[derive(Parser)]
+pub struct ConnectorDiff {
+    /// Snapshot to compare with, the last one by default
+    #[clap(long, default_value = LATEST_SNAPSHOT, value_name = "snapshot|latest")]
+    pub against: String,
+    /// Config (region) to compare with instead of a snapshot, using its credentials and service name, e.g. before moving manifests to it
+    #[clap(long, conflicts_with = "against", value_name = "CONFIG_NAME")]
+    pub against_config: Option<String>,
+    /// Save the current catalog as a new snapshot once compared
+    #[clap(long, action)]
+    pub save: bool,
+    /// Command output format
+    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "list"]).map(|s| s.parse::<OutputList>().unwrap()))]
+    pub output: Option<OutputList>,
+}
+
+impl DiSubCommands {
+    /// Config a connector catalog is compared with, if any
+    pub fn against_config(&self) -> Option<&str> {
+        match self {
+            DiSubCommands::SourceConnector(DiSubSourceConnectorCommands::Diff(diff))
+            | DiSubCommands::DestinationConnector(DiSubDestConnectorCommands::Diff(diff)) => diff.against_config.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser};
+
+    use crate::options::Opts;
+
+    #[test]
+    fn wait_options_require_wait() {
+        Opts::command().debug_assert();
+
+        let parse = |args: &[&str]| Opts::try_parse_from([&["ovhdata-cli", "di"], args].concat()).is_ok();
+        assert!(parse(&["workflow", "run", "workflow-id"]));
+        assert!(!parse(&["workflow", "run", "workflow-id", "--timeout", "60"]));
+        assert!(!parse(&["source", "metadata", "extract", "source-id", "--poll-interval", "5"]));
+        assert!(parse(&[
+            "workflow",
+            "run",
+            "workflow-id",
+            "--wait",
+            "--timeout",
+            "60",
+            "--poll-interval",
+            "5"
+        ]));
+        assert!(parse(&["source", "metadata", "extract", "source-id", "--wait", "--timeout", "60"]));
+        assert!(parse(&["job", "wait", "job-id", "--workflow-id", "workflow-id", "--timeout", "60"]));
+    }
+}
@@ -1,9 +1,11 @@
+mod auth;
 mod config;
 mod di;
 mod opts;
 mod parse;
 pub mod utils;
 
+pub use auth::*;
 pub use config::*;
 pub use di::*;
 pub use opts::*;
@@ -2,9 +2,11 @@ use clap::builder::{PossibleValuesParser, TypedValueParser};
 use clap::{crate_version, ArgAction, Parser};
 use clap_complete::Shell;
 use lazy_static::lazy_static;
+use reqwest::Method;
+use std::path::PathBuf;
 use std::str::FromStr;
 
-use crate::options::{ConfigShim, DiShim, ParseError, ParseResult};
+use crate::options::{AuthShim, ConfigShim, DiShim, NameValue, ParseError, ParseResult};
 use crate::utils::ui::printer::{Output, Printer, HELP_COMPLETION_HOW_TO, HELP_LOGIN_HOW_TO, HELP_MAIN};
 
 lazy_static! {
@@ -20,6 +22,9 @@ pub struct Opts {
     /// OVHcloud service name to use
     #[clap(global = true, long)]
     pub service_name: Option<String>,
+    /// Profile to use instead of the current one
+    #[clap(global = true, long)]
+    pub profile: Option<String>,
     #[clap(subcommand)]
     pub subcmd: SubCommand,
     /// Level of verbosity, can be used multiple times
@@ -34,10 +39,29 @@ pub struct Opts {
     /// Remove spinner from output
     #[clap(global = true, long)]
     pub no_spinner: bool,
+    /// Maximum number of retries on transient API failures, overrides the configuration
+    #[clap(global = true, long)]
+    pub max_retries: Option<u32>,
+    /// Also retry non idempotent API requests (POST, DELETE) on transient failures
+    #[clap(global = true, long)]
+    pub retry_non_idempotent: bool,
+    /// Record the API requests and responses in the given directory, secrets removed
+    #[clap(global = true, long, value_name = "DIR", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+    /// Replay the API responses previously recorded in the given directory, without any network
+    #[clap(global = true, long, value_name = "DIR")]
+    pub replay: Option<PathBuf>,
+    /// Fetch the connector catalog from the API instead of the local cache
+    #[clap(global = true, long)]
+    pub refresh: bool,
 }
 
 #[derive(Parser)]
 pub enum SubCommand {
+    /// Sends a signed request to any OVHcloud API route
+    Api(Api),
+    /// Manages the storage of the OVHcloud API credentials
+    Auth(AuthShim),
     #[clap(before_help = BEFORE_HELP_COMPLETION.as_str())]
     /// Produces shell completion code for the specified shell
     Completion(Completion),
@@ -67,11 +91,17 @@ pub struct Login {
     #[clap(short, long)]
     pub application_key: Option<String>,
     /// OVH API Consumer key
-    #[clap(short, long)]
+    #[clap(short, long, conflicts_with = "create_credential")]
     pub consumer_key: Option<String>,
     /// OVH API Secret
     #[clap(short, long)]
     pub secret: Option<String>,
+    /// Create a consumer key restricted to the data integration routes, validated in a browser
+    #[clap(long)]
+    pub create_credential: bool,
+    /// Print where the current credentials come from (flag > env > ovh.conf file > context) and exit
+    #[clap(long, conflicts_with = "create_credential")]
+    pub show_sources: bool,
     /// Command output format
     #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "description"]).map(|s| s.parse::<OutputObject>().unwrap()))]
     pub output: Option<OutputObject>,
@@ -97,6 +127,24 @@ pub struct Me {
     pub output: Option<OutputObject>,
 }
 
+#[derive(Parser)]
+pub struct Api {
+    /// HTTP method (GET, POST, PUT, DELETE)
+    #[clap(value_parser = PossibleValuesParser::new(["GET", "POST", "PUT", "DELETE"]).map(|s| s.to_uppercase().parse::<Method>().unwrap()), ignore_case = true)]
+    pub method: Method,
+    /// API route, {serviceName} is replaced by the current service name (e.g. /cloud/project/{serviceName}/dataIntegration/workflows)
+    pub path: String,
+    /// JSON file holding the request body, '-' to read it from the standard input
+    #[clap(short, long, value_name = "FILE")]
+    pub body: Option<PathBuf>,
+    /// Query parameters
+    #[clap(short, long = "query", num_args = 1, value_name = "name=value")]
+    pub query: Vec<NameValue>,
+    /// Command output format
+    #[clap(short, long, value_parser = PossibleValuesParser::new(["json", "yaml"]).map(|s| s.parse::<OutputObject>().unwrap()))]
+    pub output: Option<OutputObject>,
+}
+
 #[derive(Parser)]
 pub struct Completion {
     /// Shell name (bash, fish, zsh, powershell)
@@ -113,23 +161,18 @@ pub struct SingleOutputObject {
 #[derive(Parser)]
 pub struct SingleOutputList {
     /// Command output format
-    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "table"]).map(|s| s.parse::<OutputList>().unwrap()))]
+    #[clap(short, long, value_parser = PossibleValuesParser::new(&["json", "yaml", "list"]).map(|s| s.parse::<OutputList>().unwrap()))]
     pub output: Option<OutputList>,
 }
 
-#[derive(Parser, Clone, Copy)]
+#[derive(Parser, Clone, Copy, Default)]
 pub enum OutputObject {
     Json,
     Yaml,
+    #[default]
     Description,
 }
 
-impl Default for OutputObject {
-    fn default() -> Self {
-        Self::Description
-    }
-}
-
 impl From<OutputObject> for Output {
     fn from(output: OutputObject) -> Self {
         match output {
@@ -153,19 +196,14 @@ impl FromStr for OutputObject {
     }
 }
 
-#[derive(Parser, Clone, Copy)]
+#[derive(Parser, Clone, Copy, Default)]
 pub enum OutputList {
     Json,
     Yaml,
+    #[default]
     List,
 }
 
-impl Default for OutputList {
-    fn default() -> Self {
-        Self::List
-    }
-}
-
 impl From<OutputList> for Output {
     fn from(output: OutputList) -> Self {
         match output {
@@ -35,25 +35,21 @@ impl From<NameValue> for Parameter {
             name: env.name,
             value: env.value,
             secret: false,
+            origin: None,
         }
     }
 }
 
-#[derive(Parser, Clone)]
+#[derive(Parser, Clone, Default)]
 pub enum OrderListSourceDest {
     Age,
     Update,
     Status,
     Connector,
+    #[default]
     Name,
 }
 
-impl Default for OrderListSourceDest {
-    fn default() -> Self {
-        Self::Name
-    }
-}
-
 impl FromStr for OrderListSourceDest {
     type Err = ParseError;
 
@@ -0,0 +1,158 @@
+use std::io::stdin;
+
+use crossterm::tty::IsTty;
+use openssl::base64::{decode_block, encode_block};
+use openssl::hash::MessageDigest;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::rand::rand_bytes;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Error, Result};
+use crate::utils::ui::printer::Printer;
+
+// Passphrase of the encrypted credentials, for non interactive usages (CI)
+pub const PASSPHRASE_ENV: &str = "OVHDATA_CLI_PASSPHRASE";
+
+const KEY_DERIVATION_ITERATIONS: u32 = 600_000;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Data encrypted with AES-256-GCM and a key derived from a passphrase (PBKDF2-HMAC-SHA256)
+#[derive(Deserialize, Serialize, Clone)]
+pub struct EncryptedData {
+    pub iterations: u32,
+    pub salt: String,
+    pub nonce: String,
+    // Cipher text followed by the authentication tag
+    pub data: String,
+}
+
+/// Key derived from a passphrase, kept in memory only
+#[derive(Clone)]
+pub struct SecretKey {
+    key: Vec<u8>,
+    salt: Vec<u8>,
+    iterations: u32,
+}
+
+impl SecretKey {
+    /// Derive a new key from the passphrase with a random salt
+    pub fn generate(passphrase: &str) -> Result<Self> {
+        let mut salt = vec![0u8; SALT_LEN];
+        rand_bytes(&mut salt).map_err(crypto_error)?;
+        Self::derive(passphrase, salt, KEY_DERIVATION_ITERATIONS)
+    }
+
+    fn derive(passphrase: &str, salt: Vec<u8>, iterations: u32) -> Result<Self> {
+        let mut key = vec![0u8; KEY_LEN];
+        pbkdf2_hmac(passphrase.as_bytes(), &salt, iterations as usize, MessageDigest::sha256(), &mut key).map_err(crypto_error)?;
+        Ok(Self { key, salt, iterations })
+    }
+
+    /// Encrypt the data with a new nonce
+    pub fn encrypt(&self, plain: &[u8]) -> Result<EncryptedData> {
+        let mut nonce = vec![0u8; NONCE_LEN];
+        rand_bytes(&mut nonce).map_err(crypto_error)?;
+        let mut tag = vec![0u8; TAG_LEN];
+        let mut data = encrypt_aead(Cipher::aes_256_gcm(), &self.key, Some(&nonce), &[], plain, &mut tag).map_err(crypto_error)?;
+        data.extend(tag);
+
+        Ok(EncryptedData {
+            iterations: self.iterations,
+            salt: encode_block(&self.salt),
+            nonce: encode_block(&nonce),
+            data: encode_block(&data),
+        })
+    }
+}
+
+impl EncryptedData {
+    /// Decrypt the data, the derived key is returned to encrypt it again later
+    pub fn decrypt(&self, passphrase: &str) -> Result<(Vec<u8>, SecretKey)> {
+        let salt = decode_block(&self.salt).map_err(crypto_error)?;
+        let nonce = decode_block(&self.nonce).map_err(crypto_error)?;
+        let data = decode_block(&self.data).map_err(crypto_error)?;
+        if data.len() < TAG_LEN {
+            return Err(Error::Credentials("encrypted credentials are corrupted".to_string()));
+        }
+
+        let key = SecretKey::derive(passphrase, salt, self.iterations)?;
+        let (cipher_text, tag) = data.split_at(data.len() - TAG_LEN);
+        let plain = decrypt_aead(Cipher::aes_256_gcm(), &key.key, Some(&nonce), &[], cipher_text, tag)
+            .map_err(|_| Error::Credentials("wrong passphrase".to_string()))?;
+        Ok((plain, key))
+    }
+}
+
+/// Passphrase from the environment, or asked to the user (twice for a new one)
+pub fn passphrase(new: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+
+    if !stdin().is_tty() {
+        return Err(Error::Credentials(format!(
+            "the credentials are encrypted, set the passphrase in the {} environment variable",
+            PASSPHRASE_ENV
+        )));
+    }
+
+    let passphrase = match new {
+        true => Printer::ask_new_password("New credentials passphrase"),
+        false => Printer::ask_password("Credentials passphrase", false),
+    };
+    passphrase.ok_or(Error::Credentials("a passphrase is required".to_string()))
+}
+
+fn crypto_error(error: openssl::error::ErrorStack) -> Error {
+    Error::Credentials(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::base64::{decode_block, encode_block};
+
+    use crate::config::Error;
+    use crate::utils::crypto::SecretKey;
+
+    // Few iterations to keep the tests fast
+    fn secret_key(passphrase: &str) -> SecretKey {
+        SecretKey::derive(passphrase, b"0123456789abcdef".to_vec(), 1_000).unwrap()
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let encrypted = secret_key("p4ss").encrypt(b"credentials").unwrap();
+        assert_eq!(encrypted.iterations, 1_000);
+
+        let (plain, key) = encrypted.decrypt("p4ss").unwrap();
+        assert_eq!(plain, b"credentials");
+
+        // The returned key encrypts again with the same salt and a new nonce
+        let encrypted_again = key.encrypt(b"credentials").unwrap();
+        assert_eq!(encrypted_again.salt, encrypted.salt);
+        assert_ne!(encrypted_again.nonce, encrypted.nonce);
+        assert_eq!(encrypted_again.decrypt("p4ss").unwrap().0, b"credentials");
+    }
+
+    #[test]
+    fn decrypt_with_wrong_passphrase() {
+        let encrypted = secret_key("p4ss").encrypt(b"credentials").unwrap();
+        assert!(matches!(encrypted.decrypt("pass"), Err(Error::Credentials(message)) if message == "wrong passphrase"));
+    }
+
+    #[test]
+    fn decrypt_tampered_data() {
+        let mut encrypted = secret_key("p4ss").encrypt(b"credentials").unwrap();
+        let mut data = decode_block(&encrypted.data).unwrap();
+        data[0] ^= 1;
+        encrypted.data = encode_block(&data);
+        assert!(matches!(encrypted.decrypt("p4ss"), Err(Error::Credentials(message)) if message == "wrong passphrase"));
+
+        encrypted.data = encode_block(&data[..4]);
+        assert!(matches!(encrypted.decrypt("p4ss"), Err(Error::Credentials(message)) if message == "encrypted credentials are corrupted"));
+    }
+}
@@ -1,7 +1,16 @@
 use std::fmt::{Debug, Display};
 
+use ovhdata_common::api;
+use ovhdata_common::model::utils::ResponseError;
+use reqwest::StatusCode;
 use thiserror::Error as ThisError;
 
+use crate::config::CLI_NAME;
+use crate::{
+    ExitCode, EXIT_CODE_BREAKING_CHANGES, EXIT_CODE_ERROR, EXIT_CODE_EXTRACTION_FAILED, EXIT_CODE_JOB_FAILED, EXIT_CODE_JOB_INTERRUPTED,
+    EXIT_CODE_TIMEOUT,
+};
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(ThisError, Debug)]
@@ -20,10 +29,77 @@ pub enum Error {
     OvhcloudConfig(#[from] ovhdata_common::config::Error),
     #[error("Data API error: {0}")]
     DataApi(#[from] ovhdata_common::api::Error),
+    #[error("Job {0} ended with status {1}")]
+    JobFailed(String, String),
+    #[error("Job {0} was interrupted")]
+    JobInterrupted(String),
+    #[error("Job {0} still {1} after {2} seconds")]
+    JobTimeout(String, String, u64),
+    #[error("Metadata extraction of the source {0} failed for the tables {1}")]
+    ExtractionFailed(String, String),
+    #[error("The source {0} has no table to extract metadata from")]
+    NoTable(String),
+    #[error("Metadata extraction of the source {0} still running after {1} seconds")]
+    ExtractionTimeout(String, u64),
+    #[error("The metadata of the source {0} are not completely extracted, extract them again before comparing them")]
+    IncompleteMetadata(String),
+    #[error("{1} breaking changes in {0}")]
+    BreakingChanges(String, usize),
+    #[error("Snapshot error: {0}")]
+    Snapshot(#[from] ovhdata_common::utils::snapshot::SnapshotError),
 }
 
 impl Error {
     pub fn custom(err: impl Display) -> Self {
         Self::Custom(err.to_string())
     }
+
+    /// Exit code of the command, scripts can tell a failed job or extraction from a CLI error
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Error::JobFailed(..) => EXIT_CODE_JOB_FAILED,
+            Error::JobInterrupted(_) => EXIT_CODE_JOB_INTERRUPTED,
+            Error::JobTimeout(..) | Error::ExtractionTimeout(..) => EXIT_CODE_TIMEOUT,
+            Error::ExtractionFailed(..) => EXIT_CODE_EXTRACTION_FAILED,
+            Error::BreakingChanges(..) => EXIT_CODE_BREAKING_CHANGES,
+            _ => EXIT_CODE_ERROR,
+        }
+    }
+
+    /// Error returned by the OVH API, if any
+    fn response_error(&self) -> Option<(&StatusCode, &ResponseError)> {
+        match self {
+            Error::DataApi(api::Error::Response(status, error)) => Some((status, error)),
+            _ => None,
+        }
+    }
+
+    /// Query ID of the failed OVH API call, to give to the support
+    pub fn query_id(&self) -> Option<&str> {
+        self.response_error().and_then(|(_, error)| error.query_id.as_deref())
+    }
+
+    /// Hint to solve an error returned by the OVH API
+    pub fn hint(&self) -> Option<String> {
+        let (status, error) = self.response_error()?;
+        let error_code = error.error_code.as_deref().unwrap_or_default();
+        let class = error.class.as_deref().unwrap_or_default();
+
+        let hint = match (error_code, class) {
+            ("QUERY_TIME_OUT", _) => "The clock of this computer is too far from the OVH API one, check the system time".to_string(),
+            ("INVALID_SIGNATURE", _) => "The request signature is invalid, check the application secret and the clock of this computer".to_string(),
+            ("INVALID_KEY", _) => format!("The application key is invalid, log in again with '{} login'", CLI_NAME),
+            ("INVALID_CREDENTIAL" | "NOT_CREDENTIAL", _) | (_, "Client::Unauthorized") => {
+                format!("The credential is invalid or expired, log in again with '{} login'", CLI_NAME)
+            }
+            ("NOT_GRANTED_CALL", _) | (_, "Client::Forbidden") => format!(
+                "The credential is not allowed to call this route, check its access rules with '{} login' or create a new one",
+                CLI_NAME
+            ),
+            (_, "Client::NotFound") => "The resource doesn't exist, check the service name and the given IDs".to_string(),
+            _ if status.is_server_error() || class.starts_with("Server::") => "The OVH API is unavailable, try again later".to_string(),
+            _ => return None,
+        };
+        Some(hint)
+    }
 }
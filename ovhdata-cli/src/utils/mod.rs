@@ -1,4 +1,6 @@
+pub mod crypto;
 mod error;
+pub mod parameters;
 pub mod ui;
 
 pub use error::*;
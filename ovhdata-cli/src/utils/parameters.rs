@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use ovhdata_common::model::di::common::Parameter;
+use ovhdata_common::utils::reference::{ValueReference, STDIN_REFERENCE};
+use serde_yaml::Value;
+
+use crate::options::NameValue;
+use crate::utils::{Error, Result};
+
+/// Connector parameters given on the command line, the references being replaced by their value.
+/// The parameters file is read first, the `--parameter` options override it.
+pub fn input_parameters(parameters: &[NameValue], parameters_file: Option<&Path>) -> Result<Vec<Parameter>> {
+    let stdin_count = parameters.iter().filter(|parameter| parameter.value == STDIN_REFERENCE).count()
+        + parameters_file.filter(|file| *file == Path::new(STDIN_REFERENCE)).iter().count();
+    if stdin_count > 1 {
+        return Err(Error::custom(
+            "The standard input can be read only once, for a single parameter or the parameters file",
+        ));
+    }
+
+    let mut input: Vec<Parameter> = match parameters_file {
+        Some(file) => read_parameters_file(file)?,
+        None => vec![],
+    };
+
+    for name_value in parameters {
+        let mut parameter: Parameter = name_value.clone().into();
+        if let Some(reference) = ValueReference::parse(&parameter.value) {
+            parameter.value = resolve(&parameter.name, &reference)?;
+            parameter.origin = Some(format!("--parameter {}={}", parameter.name, reference));
+        }
+        input.retain(|existing| existing.name != parameter.name);
+        input.push(parameter);
+    }
+    Ok(input)
+}
+
+/// Parameters of a YAML or JSON file, by name
+fn read_parameters_file(file: &Path) -> Result<Vec<Parameter>> {
+    let content = match file == Path::new(STDIN_REFERENCE) {
+        true => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            content
+        }
+        false => std::fs::read_to_string(file).map_err(|error| Error::custom(format!("Unable to read {:?}: {}", file, error)))?,
+    };
+    let values = serde_yaml::from_str::<BTreeMap<String, Value>>(&content)
+        .map_err(|error| Error::custom(format!("Invalid parameters file {:?}: {}", file, error)))?;
+
+    let origin = format!("--parameters-file {}", file.display());
+    values
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(value) => match ValueReference::parse(&value) {
+                    // Only the file itself may come from the standard input
+                    Some(ValueReference::Stdin) | None => value,
+                    Some(reference) => resolve(&name, &reference)?,
+                },
+                Value::Bool(value) => value.to_string(),
+                Value::Number(value) => value.to_string(),
+                Value::Null => String::new(),
+                _ => return Err(Error::custom(format!("The parameter {} of {:?} is not a single value", name, file))),
+            };
+            Ok(Parameter {
+                name,
+                value,
+                secret: false,
+                origin: Some(origin.clone()),
+            })
+        })
+        .collect()
+}
+
+fn resolve(name: &str, reference: &ValueReference) -> Result<String> {
+    reference
+        .resolve()
+        .map_err(|error| Error::custom(format!("The parameter {} can't be resolved, {}", name, error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::options::NameValue;
+    use crate::utils::parameters::input_parameters;
+    use crate::utils::Error;
+
+    fn name_value(value: &str) -> NameValue {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn options_override_the_file() {
+        let path = std::env::temp_dir().join(format!("ovhdata-parameters-{}.yaml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "host: db.local\nport: 5432\nssl: true\n").unwrap();
+        let parameters = input_parameters(&[name_value("port=3306"), name_value("user=admin")], Some(&path));
+        std::fs::remove_file(&path).unwrap();
+
+        let origin = format!("--parameters-file {}", path.display());
+        let values = parameters
+            .unwrap()
+            .into_iter()
+            .map(|parameter| (parameter.name, parameter.value, parameter.origin))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                ("host".to_string(), "db.local".to_string(), Some(origin.clone())),
+                ("ssl".to_string(), "true".to_string(), Some(origin)),
+                ("port".to_string(), "3306".to_string(), None),
+                ("user".to_string(), "admin".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn stdin_read_once() {
+        let message = "The standard input can be read only once, for a single parameter or the parameters file";
+        assert!(matches!(
+            input_parameters(&[name_value("password=-"), name_value("token=-")], None),
+            Err(Error::Custom(error)) if error == message
+        ));
+        assert!(matches!(
+            input_parameters(&[name_value("password=-")], Some(Path::new("-"))),
+            Err(Error::Custom(error)) if error == message
+        ));
+    }
+}
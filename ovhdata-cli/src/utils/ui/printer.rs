@@ -17,11 +17,11 @@ use spinners::{Spinner, Spinners};
 
 use ovhdata_common::model::di::common::EnsureSecret;
 use ovhdata_common::model::di::common::Parameter;
-use ovhdata_common::model::di::connector::{ConnectorParameter, ConnectorValidator};
+use ovhdata_common::model::di::connector::{validate_parameters, ConnectorParameter, ConnectorValidator};
+use ovhdata_common::utils::cron::CronSchedule;
 use ovhdata_common::BUG;
 
 use crate::config::{Context, Toggle};
-use crate::options::NameValue;
 use crate::utils::{Error, Result};
 use crate::CLI_NAME;
 
@@ -62,6 +62,16 @@ impl Printer {
         }
     }
 
+    /// Spinner showing the time elapsed since its start
+    pub fn start_timer_spinner(message: &str) -> Option<Spinner> {
+        let no_spinner = NO_SPINNER.read().unwrap().to_owned();
+        if no_spinner {
+            None
+        } else {
+            Some(Spinner::with_timer(Spinners::Pong, message.to_string()))
+        }
+    }
+
     pub fn stop_spinner(spinner: Option<Spinner>) {
         if let Some(mut spin) = spinner {
             spin.stop_with_newline();
@@ -103,14 +113,15 @@ impl Printer {
     }
 
     pub fn ask_connector_parameters(
-        input: &[NameValue],
+        input: &[Parameter],
         api: Option<&Vec<Parameter>>,
         connector_parameters: &[ConnectorParameter],
     ) -> Result<Vec<Parameter>> {
-        let input_parameters: HashMap<String, Parameter> = input
-            .iter()
-            .map(|parameter| (parameter.clone().name, parameter.clone().into()))
-            .collect::<HashMap<_, _>>();
+        // Non interactive mode
+        // input_parameter have always the priority
+        if !input.is_empty() {
+            return validate_parameters(connector_parameters, input, api.map(Vec::as_slice)).map_err(Error::custom);
+        }
 
         let api_parameters: HashMap<String, Parameter> = if let Some(parameters) = api {
             parameters
@@ -123,26 +134,6 @@ impl Printer {
 
         let mut parameters: Vec<Parameter> = Vec::new();
 
-        // Non interactive mode
-        // input_parameter have always the priority
-        if !input_parameters.is_empty() {
-            let params = connector_parameters
-                .iter()
-                .filter(|connector_parameter| input_parameters.contains_key(&connector_parameter.name))
-                .map(|connector_parameter| {
-                    // Adds the secret field to the parameter
-                    // used internally for never display secret
-                    let mut param = input_parameters.get(&connector_parameter.name).unwrap().to_owned();
-
-                    if connector_parameter.type_name == "secret" {
-                        param.secret = true;
-                    }
-                    param
-                })
-                .collect();
-            return Ok(params);
-        }
-
         // Interactive mode (input_parameter empty)
         // Can be use for create, update or delete a field for a given connector_parameters
         //  - Create: no api_parameter (not existing remotely, default from connector_parameters)
@@ -209,7 +200,7 @@ impl Printer {
         let mut parameter_secret = false;
         let parameter_value = match connector_parameter.type_name.clone().as_str() {
             "string" => {
-                let value = Printer::ask_input_string(&prompt, current_value.clone(), connector_parameter.mandatory.not(), default_value);
+                let value = Printer::ask_input_parameter(&prompt, current_value.clone(), connector_parameter, default_value);
                 if value.is_some() {
                     value
                 } else if !connector_parameter.mandatory && value.is_none() && current_value.is_some() {
@@ -221,17 +212,10 @@ impl Printer {
             }
             "secret" => {
                 parameter_secret = true;
-                Printer::ask_password(&prompt, connector_parameter.mandatory.not())
+                Printer::ask_password_parameter(&prompt, connector_parameter)
             }
             "int" => {
-                let validator = connector_parameter.validator.clone().unwrap();
-                let value = Printer::ask_input_integer(
-                    &prompt,
-                    current_value.clone(),
-                    connector_parameter.mandatory.not(),
-                    &validator,
-                    default_value,
-                );
+                let value = Printer::ask_input_parameter(&prompt, current_value.clone(), connector_parameter, default_value);
                 if value.is_some() {
                     value
                 } else if !connector_parameter.mandatory && value.is_none() && current_value.is_some() {
@@ -264,6 +248,7 @@ impl Printer {
                 name: connector_parameter.name.clone(),
                 value,
                 secret: parameter_secret,
+                origin: None,
             };
             Ok(Option::from(param))
         } else {
@@ -288,33 +273,21 @@ impl Printer {
         input_binding.interact_text().map(|s: String| s.is_empty().not().then_some(s)).unwrap()
     }
 
-    fn ask_input_integer(
+    /// Ask a parameter value until it matches the type and the validator of the connector parameter
+    fn ask_input_parameter(
         prompt: &str,
         initial_text: Option<String>,
-        allow_empty: bool,
-        validator: &ConnectorValidator,
+        connector_parameter: &ConnectorParameter,
         default: Option<String>,
     ) -> Option<String> {
         let color_binding = ColorfulTheme::default();
         let mut input_binding = Input::with_theme(&color_binding);
 
-        input_binding.with_prompt(prompt).report(false).allow_empty(allow_empty).validate_with({
-            move |input: &String| -> std::result::Result<(), String> {
-                let test = input.parse::<i64>();
-                match test {
-                    Ok(value) => {
-                        if value < validator.min {
-                            Err(format!("The value can not be lower than {}", validator.min))
-                        } else if value > validator.max {
-                            Err(format!("The value can not be upper than {}", validator.max))
-                        } else {
-                            Ok(())
-                        }
-                    }
-                    Err(_) => Err("Invalid value, this is not an integer".to_string()),
-                }
-            }
-        });
+        input_binding
+            .with_prompt(prompt)
+            .report(false)
+            .allow_empty(connector_parameter.mandatory.not())
+            .validate_with(Printer::parameter_validator(connector_parameter));
 
         if let Some(default_input) = default {
             input_binding.default(default_input);
@@ -327,6 +300,52 @@ impl Printer {
         input_binding.interact_text().map(|s: String| s.is_empty().not().then_some(s)).unwrap()
     }
 
+    fn ask_password_parameter(prompt: &str, connector_parameter: &ConnectorParameter) -> Option<String> {
+        let color_binding = ColorfulTheme::default();
+        let mut password_binding = Password::with_theme(&color_binding);
+
+        password_binding
+            .with_prompt(prompt)
+            .allow_empty_password(connector_parameter.mandatory.not())
+            .validate_with(Printer::parameter_validator(connector_parameter))
+            .report(false)
+            .interact()
+            .map(|s: String| s.is_empty().not().then_some(s))
+            .unwrap()
+    }
+
+    fn parameter_validator(connector_parameter: &ConnectorParameter) -> impl Fn(&String) -> std::result::Result<(), String> + '_ {
+        move |input: &String| -> std::result::Result<(), String> {
+            match input.is_empty() {
+                // Checked by the prompt, empty is either the default value or allowed
+                true => Ok(()),
+                false => connector_parameter.check(input).map_err(|error| error.to_string()),
+            }
+        }
+    }
+
+    pub fn ask_input_schedule(prompt: &str, initial_text: Option<String>) -> Option<String> {
+        let color_binding = ColorfulTheme::default();
+        let mut input_binding = Input::with_theme(&color_binding);
+
+        input_binding.with_prompt(prompt).report(false).allow_empty(true).validate_with({
+            move |input: &String| -> std::result::Result<(), String> {
+                match input.is_empty() {
+                    true => Ok(()),
+                    false => CronSchedule::parse(input)
+                        .map(|_| ())
+                        .map_err(|error| format!("Invalid schedule, {}", error)),
+                }
+            }
+        });
+
+        if let Some(text) = initial_text {
+            input_binding.with_initial_text(text);
+        }
+
+        input_binding.interact_text().map(|s: String| s.is_empty().not().then_some(s)).unwrap()
+    }
+
     pub fn ask_input_boolean(prompt: &str, default: bool) -> Result<bool> {
         match Printer::ask_select(prompt, &["False", "True"], default as usize).unwrap() {
             0 => Ok(false),
@@ -367,6 +386,18 @@ impl Printer {
             .unwrap()
     }
 
+    pub fn ask_new_password(prompt: &str) -> Option<String> {
+        let color_binding = ColorfulTheme::default();
+        let mut password_binding = Password::with_theme(&color_binding);
+
+        password_binding
+            .with_prompt(prompt)
+            .with_confirmation("Confirm the passphrase", "The passphrases don't match")
+            .report(false)
+            .interact()
+            .ok()
+    }
+
     pub fn println_success(write: &mut dyn Write, msg: &str) {
         writeln!(write, "{}{}", VALID.dark_green(), msg.bold()).expect("can't write on stdout");
     }
@@ -553,10 +584,8 @@ impl Printer {
                         }
                         write!(&mut output, "{}\n\n", Attribute::NormalIntensity).unwrap();
                     }
-                    Tag::Heading(HeadingLevel::H2, ..) => {
-                        if md_style.h2.is_some() {
-                            writeln!(&mut output, "{}", SetForegroundColor(foreground)).unwrap();
-                        }
+                    Tag::Heading(HeadingLevel::H2, ..) if md_style.h2.is_some() => {
+                        writeln!(&mut output, "{}", SetForegroundColor(foreground)).unwrap();
                     }
                     Tag::CodeBlock(_) => {
                         writeln!(&mut output).unwrap();
@@ -570,10 +599,8 @@ impl Printer {
                     Tag::Strong => {
                         write!(&mut output, "{}", Attribute::NormalIntensity).unwrap();
                     }
-                    Tag::Emphasis => {
-                        if md_style.emphasis.is_some() {
-                            write!(&mut output, "{}", SetForegroundColor(foreground)).unwrap();
-                        }
+                    Tag::Emphasis if md_style.emphasis.is_some() => {
+                        write!(&mut output, "{}", SetForegroundColor(foreground)).unwrap();
                     }
                     _ => {}
                 },
@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use hyper::http;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Request, Response, ResponseBuilderExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::api::{Error, Result};
+use crate::BUG;
+
+// Value written in place of the secrets
+pub const REDACTED: &str = "[redacted]";
+
+// Headers never written in a cassette
+const SENSITIVE_HEADERS: [&str; 6] = [
+    "x-ovh-application",
+    "x-ovh-consumer",
+    "x-ovh-signature",
+    "authorization",
+    "cookie",
+    "set-cookie",
+];
+
+// Fields and parameters holding a secret, matched on their lowercase name
+const SENSITIVE_NAMES: [&str; 6] = ["password", "secret", "token", "consumerkey", "applicationkey", "accesskey"];
+
+/// HTTP interactions recorded in or replayed from a directory, one JSON file per request
+#[derive(Debug)]
+pub enum Cassette {
+    Record { dir: PathBuf, index: AtomicUsize },
+    Replay { interactions: Mutex<Vec<(Interaction, bool)>> },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "RecordedBody::is_empty")]
+    pub body: RecordedBody,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "RecordedBody::is_empty")]
+    pub body: RecordedBody,
+}
+
+/// Body of a request or a response, kept as JSON when possible so that cassettes stay readable
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordedBody {
+    #[default]
+    Empty,
+    Json(Value),
+    Text(String),
+}
+
+impl RecordedBody {
+    pub fn is_empty(&self) -> bool {
+        *self == RecordedBody::Empty
+    }
+
+    /// Read a body and remove the secrets it contains
+    fn redacted(bytes: &[u8]) -> Self {
+        if bytes.is_empty() {
+            return RecordedBody::Empty;
+        }
+        match serde_json::from_slice::<Value>(bytes) {
+            Ok(mut value) => {
+                redact_value(&mut value);
+                RecordedBody::Json(value)
+            }
+            Err(_) => RecordedBody::Text(String::from_utf8_lossy(bytes).to_string()),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            RecordedBody::Empty => vec![],
+            RecordedBody::Json(value) => serde_json::to_vec(value).expect(BUG),
+            RecordedBody::Text(text) => text.clone().into_bytes(),
+        }
+    }
+}
+
+impl RecordedRequest {
+    fn from(request: &Request) -> Self {
+        let body = request.body().and_then(|body| body.as_bytes()).unwrap_or_default();
+        Self {
+            method: request.method().to_string(),
+            path: request.url().path().to_string(),
+            query: request.url().query().map(redacted_query),
+            headers: redacted_headers(request.headers()),
+            body: RecordedBody::redacted(body),
+        }
+    }
+
+    /// Replay matches requests on method, path, query and body
+    fn matches(&self, other: &RecordedRequest) -> bool {
+        self.method == other.method && self.path == other.path && self.query == other.query && self.body == other.body
+    }
+
+    fn file_name(&self, index: usize) -> String {
+        let path = self
+            .path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<&str>>()
+            .join("_");
+        let path: String = path.chars().take(100).collect();
+        format!("{:04}-{}-{}.json", index, self.method, path)
+    }
+}
+
+impl RecordedResponse {
+    fn into_response(self, request: &Request) -> Result<Response> {
+        let mut builder = http::Response::builder().status(self.status).url(request.url().clone());
+        // The body may have been redacted, its length is not the recorded one anymore
+        for (name, value) in self
+            .headers
+            .iter()
+            .filter(|(name, _)| *name != "content-length" && *name != "transfer-encoding")
+        {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .body(self.body.to_bytes())
+            .map_err(|error| Error::Cassette(format!("invalid recorded response: {}", error)))?;
+        Ok(Response::from(response))
+    }
+}
+
+impl Cassette {
+    /// Record the interactions in the given directory, after the ones it may already hold
+    pub fn record(dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir).map_err(|error| Error::Cassette(format!("unable to create {:?}: {}", dir, error)))?;
+        let index = Self::files(&dir)?.len();
+        Ok(Cassette::Record {
+            dir,
+            index: AtomicUsize::new(index),
+        })
+    }
+
+    /// Replay the interactions previously recorded in the given directory
+    pub fn replay(dir: PathBuf) -> Result<Self> {
+        let interactions = Self::files(&dir)?
+            .into_iter()
+            .map(|file| {
+                let content = std::fs::read_to_string(&file).map_err(|error| Error::Cassette(format!("unable to read {:?}: {}", file, error)))?;
+                let interaction = serde_json::from_str::<Interaction>(&content)
+                    .map_err(|error| Error::Cassette(format!("unable to read {:?}: {}", file, error)))?;
+                Ok((interaction, false))
+            })
+            .collect::<Result<Vec<(Interaction, bool)>>>()?;
+        Ok(Cassette::Replay {
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    /// Sorted list of the interaction files of a directory
+    fn files(dir: &Path) -> Result<Vec<PathBuf>> {
+        let entries = std::fs::read_dir(dir).map_err(|error| Error::Cassette(format!("unable to read {:?}: {}", dir, error)))?;
+        let mut files = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map(|extension| extension == "json").unwrap_or(false))
+            .collect::<Vec<PathBuf>>();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Send the request through the cassette
+    /// Record mode sends the request then saves the interaction, replay mode serves the recorded response
+    pub async fn send(&self, client: &Client, request: Request) -> Result<Response> {
+        let recorded_request = RecordedRequest::from(&request);
+        match self {
+            Cassette::Record { dir, index } => {
+                let url = request.url().clone();
+                let response = client.execute(request).await.map_err(Error::Request)?;
+
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = response.bytes().await.map_err(Error::Request)?;
+                let interaction = Interaction {
+                    request: recorded_request,
+                    response: RecordedResponse {
+                        status: status.as_u16(),
+                        headers: redacted_headers(&headers),
+                        body: RecordedBody::redacted(&body),
+                    },
+                };
+
+                let file = dir.join(interaction.request.file_name(index.fetch_add(1, Ordering::SeqCst)));
+                let content = serde_json::to_vec_pretty(&interaction).expect(BUG);
+                std::fs::write(&file, content).map_err(|error| Error::Cassette(format!("unable to write {:?}: {}", file, error)))?;
+                info!(file = %file.display(), "Interaction recorded");
+
+                // Serve the original response, not the redacted one
+                let mut builder = http::Response::builder().status(status).url(url);
+                if let Some(builder_headers) = builder.headers_mut() {
+                    *builder_headers = headers;
+                }
+                let response = builder
+                    .body(body)
+                    .map_err(|error| Error::Cassette(format!("invalid response: {}", error)))?;
+                Ok(Response::from(response))
+            }
+            Cassette::Replay { interactions } => {
+                let mut interactions = interactions.lock().expect(BUG);
+                // Serve the recorded responses in order, the last one is served again once all were used (polling)
+                let position = interactions
+                    .iter()
+                    .position(|(interaction, used)| !used && interaction.request.matches(&recorded_request))
+                    .or_else(|| {
+                        interactions
+                            .iter()
+                            .rposition(|(interaction, _)| interaction.request.matches(&recorded_request))
+                    })
+                    .ok_or(Error::Cassette(format!(
+                        "no recorded response for {} {}{}",
+                        recorded_request.method,
+                        recorded_request.path,
+                        recorded_request.query.as_ref().map(|query| format!("?{}", query)).unwrap_or_default()
+                    )))?;
+
+                let (interaction, used) = &mut interactions[position];
+                *used = true;
+                info!(request = %interaction.request.path, "Interaction replayed");
+                interaction.response.clone().into_response(&request)
+            }
+        }
+    }
+}
+
+fn redacted_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = match SENSITIVE_HEADERS.contains(&name.as_str()) {
+                true => REDACTED.to_string(),
+                false => value.to_str().unwrap_or_default().to_string(),
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Query string with the value of the secret parameters removed, the other ones kept as sent
+fn redacted_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if is_sensitive_name(name) => format!("{}={}", name, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_NAMES.iter().any(|sensitive| name.contains(sensitive))
+}
+
+/// Remove the secrets of a JSON value: secret fields and the value of secret parameters
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let secret_parameter =
+                map.get("secret") == Some(&Value::Bool(true)) || map.get("name").and_then(Value::as_str).map(is_sensitive_name).unwrap_or(false);
+            for (key, field) in map.iter_mut() {
+                let secret_field = is_sensitive_name(key) || (secret_parameter && key == "value");
+                match field {
+                    Value::String(_) | Value::Number(_) if secret_field => *field = Value::String(REDACTED.to_string()),
+                    _ => redact_value(field),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::api::cassette::{redact_value, redacted_query, RecordedBody, RecordedRequest, REDACTED};
+
+    #[test]
+    fn redact_secret_parameters() {
+        let mut value = json!({
+            "name": "my-source",
+            "parameters": [
+                { "name": "host", "value": "localhost" },
+                { "name": "password", "value": "p4ssw0rd" },
+                { "name": "key", "value": "abc", "secret": true }
+            ],
+            "consumerKey": "ck",
+        });
+        redact_value(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "name": "my-source",
+                "parameters": [
+                    { "name": "host", "value": "localhost" },
+                    { "name": "password", "value": REDACTED },
+                    { "name": "key", "value": REDACTED, "secret": true }
+                ],
+                "consumerKey": REDACTED,
+            })
+        );
+    }
+
+    #[test]
+    fn redact_and_match_queries() {
+        assert_eq!(
+            redacted_query("name=my-source&accessKey=abc&status=READY"),
+            format!("name=my-source&accessKey={}&status=READY", REDACTED)
+        );
+
+        let request = |query: Option<&str>| RecordedRequest {
+            method: "GET".to_string(),
+            path: "/cloud/project/abc/dataIntegration/sources".to_string(),
+            query: query.map(redacted_query),
+            headers: Default::default(),
+            body: RecordedBody::Empty,
+        };
+        assert!(request(Some("token=abc&page=1")).matches(&request(Some("token=def&page=1"))));
+        assert!(!request(Some("page=1")).matches(&request(Some("page=2"))));
+        assert!(!request(Some("page=1")).matches(&request(None)));
+    }
+}
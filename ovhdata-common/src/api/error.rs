@@ -1,3 +1,5 @@
+use crate::model::utils::ResponseError;
+use crate::utils::http::UrlError;
 use crate::utils::jsonpath::JsonPathError;
 use reqwest::StatusCode;
 use thiserror::Error as ThisError;
@@ -7,11 +9,22 @@ pub enum Error {
     #[error("request error: {0}")]
     Request(reqwest::Error),
     #[error("response error: {}: {1}", .0.as_u16())]
-    Response(StatusCode, String),
+    Response(StatusCode, Box<ResponseError>),
     #[error("deserialize error: {0}, string is {1}")]
     DeserializeContent(serde_json::Error, String),
     #[error("filtering error: {0}")]
     FilterContent(JsonPathError),
+    #[error("url error: {0}")]
+    Url(UrlError),
+    #[error("cassette error: {0}")]
+    Cassette(String),
+}
+
+impl Error {
+    /// Response error with only a message
+    pub fn response(status: StatusCode, message: impl Into<String>) -> Self {
+        Error::Response(status, Box::new(ResponseError::new(message)))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
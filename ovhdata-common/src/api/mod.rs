@@ -1,5 +1,11 @@
+mod cassette;
 mod error;
 mod reqwest_wrapper;
+mod response_cache;
+mod retry;
 
+pub use cassette::*;
 pub use error::*;
 pub use reqwest_wrapper::*;
+pub use response_cache::*;
+pub use retry::*;
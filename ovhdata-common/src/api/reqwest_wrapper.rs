@@ -1,16 +1,19 @@
 use std::fmt::{Display, Formatter};
 use std::ops::{Deref, DerefMut};
 
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
 use reqwest::header::HeaderMap;
 use reqwest::{Client, Request, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::api::{Error, Result};
-use crate::model::utils::ResponseError;
+use crate::api::{Cassette, Error, Result, RetryPolicy};
+use crate::model::utils::{Page, ResponseError};
+use crate::ovhapi::HEADER_OVH_QUERY_ID;
 use crate::utils::jsonpath;
 use crate::REQUEST_ID;
 
@@ -31,62 +34,161 @@ where
     serde_json::from_value(new_body.clone()).map_err(|e| Error::DeserializeContent(e, new_body.to_string()))
 }
 
-// /// Deserialize response body as JSON
-// pub async fn parse_value<T>(value: Value) -> Result<T>
-// where
-//     T: DeserializeOwned,
-// {
-//     serde_json::from_value(value).map_err(|e| Error::DeserializeContent(e, value.clone().as_str()))
-// }
+/// Deserialize a JSON value
+pub fn parse_value<T>(value: Value) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(&value).map_err(|e| Error::DeserializeContent(e, value.to_string()))
+}
+
+/// Deserialize response body as a page of items
+/// A plain JSON array is read as a unique page
+pub async fn parse_page<T>(response: ResponseWrapper) -> Result<Page<T>>
+where
+    T: DeserializeOwned,
+{
+    let body_string = response.body_text().await;
+    let body: Value = serde_json::from_str(body_string.as_str()).map_err(|e| Error::DeserializeContent(e, body_string))?;
+
+    match body.is_array() {
+        true => Ok(Page::new_unique(parse_value(body)?)),
+        false => parse_value(body),
+    }
+}
+
+/// Collect the items of a stream, filter them with a jsonpath filter then keep at most `limit` items
+/// The filter is applied on the items of every page at once
+pub async fn collect_items<T>(stream: BoxStream<'_, Result<Value>>, filter: Option<String>, limit: Option<u32>) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    let limit = limit.map(|limit| limit as usize).unwrap_or(usize::MAX);
+
+    let items: Vec<Value> = match filter {
+        // Without filter there is no need to fetch more pages than necessary
+        None => stream.take(limit).try_collect().await?,
+        Some(_) => {
+            let items: Vec<Value> = stream.try_collect().await?;
+            if items.is_empty() {
+                items
+            } else {
+                let filtered = jsonpath::json_parse_task(Value::Array(items), filter).map_err(Error::FilterContent)?;
+                parse_value::<Vec<Value>>(filtered)?.into_iter().take(limit).collect()
+            }
+        }
+    };
+
+    items.into_iter().map(parse_value).collect()
+}
+
+/// Signs the requests of an API, called again before each retry so that every attempt has a fresh signature
+pub trait RequestSigner: Sync {
+    fn sign(&self, request: &mut Request);
+}
 
 /// Send HTTP request with optional body and return a response
+/// Transient failures are retried according to the retry policy
+/// When a cassette is given, the request goes through it to be recorded or replayed
 #[tracing::instrument(
-    skip(client, request, allowed_statuses),
+    skip(client, request, allowed_statuses, retry_policy, cassette, signer),
     fields(
         request_id = %request.request_id(),
         method = %request.method(),
         url = %request.url(),
     )
 )]
-pub async fn send_request(client: &Client, request: RequestWrapper, allowed_statuses: &[StatusCode]) -> Result<ResponseWrapper> {
+pub async fn send_request(
+    client: &Client,
+    request: RequestWrapper,
+    allowed_statuses: &[StatusCode],
+    retry_policy: &RetryPolicy,
+    cassette: Option<&Cassette>,
+    signer: Option<&dyn RequestSigner>,
+) -> Result<ResponseWrapper> {
     let request_id = *request.request_id();
     let request_method = request.method().clone();
-    info!(
-        http = "request",
-        headers = %request.headers(),
-        body = %request.body_str(),
-        "SEND {} {} {}={}", request.method(), request.url(), REQUEST_ID, request.request_id()
-    );
-    let response = match client.execute(request.into()).await {
-        Ok(response) => {
-            let status = response.status();
-            let response = ResponseWrapper::from(request_id, response);
-            info!(
-                http = "response",
-                status_int = %response.status().as_u16(),
-                headers = %response.headers_map_wrapper(),
-                "[{:6}] {} {} {}={}", response.status(), request_method, response.url(), REQUEST_ID, response.request_id
-            );
-            // Handle response status
-            if status.is_success() || allowed_statuses.contains(&status) {
-                Ok(response)
-            } else {
-                let body_string = response.body_text().await;
+    let retry_allowed = retry_policy.allows_method(&request_method);
+    let mut request = request;
+    let mut retry = 0;
+
+    loop {
+        // Keep a copy of the request as long as it may be sent again
+        let next_request = match retry_allowed && retry < retry_policy.max_retries {
+            true => request.try_clone(),
+            false => None,
+        };
+
+        info!(
+            http = "request",
+            attempt = retry + 1,
+            headers = %request.headers(),
+            body = %request.body_str(),
+            "SEND {} {} {}={}", request.method(), request.url(), REQUEST_ID, request.request_id()
+        );
+        let sent = match cassette {
+            Some(cassette) => cassette.send(client, request.into()).await,
+            None => client.execute(request.into()).await.map_err(Error::Request),
+        };
+        let (error, retryable, retry_headers) = match sent {
+            Ok(response) => {
+                let status = response.status();
+                let headers = response.headers().clone();
+                let response = ResponseWrapper::from(request_id, response);
+                info!(
+                    http = "response",
+                    attempt = retry + 1,
+                    status_int = %response.status().as_u16(),
+                    headers = %response.headers_map_wrapper(),
+                    "[{:6}] {} {} {}={}", response.status(), request_method, response.url(), REQUEST_ID, response.request_id
+                );
+                // Handle response status
+                if status.is_success() || allowed_statuses.contains(&status) {
+                    return Ok(response);
+                }
 
-                let body_string = match serde_json::from_str::<ResponseError>(body_string.as_str()) {
-                    Ok(response_error) => response_error.message,
-                    Err(_) => body_string,
+                let body_string = response.body_text().await;
+                let mut response_error = match serde_json::from_str::<ResponseError>(body_string.as_str()) {
+                    Ok(response_error) => response_error,
+                    Err(_) => ResponseError::new(body_string),
                 };
-                Err(Error::Response(status, body_string))
+                response_error.query_id = headers.get(HEADER_OVH_QUERY_ID).and_then(|value| value.to_str().ok()).map(String::from);
+                (
+                    Error::Response(status, Box::new(response_error)),
+                    RetryPolicy::is_retryable_status(status),
+                    Some(headers),
+                )
             }
-        }
-        Err(err) => {
-            error!(error = %err, "[KO] Was unable to execute request !");
-            Err(Error::Request(err))
-        }
-    }?;
+            Err(Error::Request(err)) => {
+                error!(error = %err, attempt = retry + 1, "[KO] Was unable to execute request !");
+                let retryable = RetryPolicy::is_retryable_error(&err);
+                (Error::Request(err), retryable, None)
+            }
+            Err(err) => {
+                error!(error = %err, attempt = retry + 1, "[KO] Was unable to execute request !");
+                (err, false, None)
+            }
+        };
 
-    Ok(response)
+        match next_request {
+            Some(mut next_request) if retryable => {
+                retry += 1;
+                let backoff = retry_policy.backoff(retry, retry_headers.as_ref());
+                warn!(
+                    error = %error,
+                    attempt = retry + 1,
+                    backoff_ms = backoff.as_millis() as u64,
+                    "Transient failure, retry {}/{} in {:?} {}={}", retry, retry_policy.max_retries, backoff, REQUEST_ID, request_id
+                );
+                tokio::time::sleep(backoff).await;
+                if let Some(signer) = signer {
+                    signer.sign(&mut next_request);
+                }
+                request = RequestWrapper::from(request_id, next_request);
+            }
+            _ => return Err(error),
+        }
+    }
 }
 
 pub struct RequestWrapper(Uuid, Request);
@@ -117,8 +219,15 @@ impl RequestWrapper {
         &self.0
     }
 
-    pub async fn send(self, client: &Client, allowed_status: &[StatusCode]) -> Result<ResponseWrapper> {
-        send_request(client, self, allowed_status).await
+    pub async fn send(
+        self,
+        client: &Client,
+        allowed_status: &[StatusCode],
+        retry_policy: &RetryPolicy,
+        cassette: Option<&Cassette>,
+        signer: Option<&dyn RequestSigner>,
+    ) -> Result<ResponseWrapper> {
+        send_request(client, self, allowed_status, retry_policy, cassette, signer).await
     }
 }
 
@@ -173,6 +282,10 @@ impl ResponseWrapper {
     pub async fn parse_with_filter<T: DeserializeOwned>(self, filter: Option<String>) -> Result<T> {
         parse_response::<T>(self, filter).await
     }
+
+    pub async fn parse_page<T: DeserializeOwned>(self) -> Result<Page<T>> {
+        parse_page::<T>(self).await
+    }
 }
 
 impl Deref for ResponseWrapper {
@@ -206,3 +319,87 @@ impl Display for HeaderMapWrapper {
         f.write_str(string.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::stream::{self, StreamExt};
+    use reqwest::header::HeaderValue;
+    use reqwest::{Client, Request};
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use uuid::Uuid;
+
+    use crate::api::{collect_items, RequestSigner, RequestWrapper, Result, RetryPolicy};
+
+    fn items() -> Vec<Result<Value>> {
+        (0..5).map(|i| Ok(json!({ "id": i, "even": i % 2 == 0 }))).collect()
+    }
+
+    #[tokio::test]
+    async fn collect_items_with_limit() {
+        let items: Vec<Value> = collect_items(stream::iter(items()).boxed(), None, Some(2)).await.unwrap();
+        assert_eq!(items, vec![json!({ "id": 0, "even": true }), json!({ "id": 1, "even": false })]);
+    }
+
+    #[tokio::test]
+    async fn collect_items_filter_before_limit() {
+        let filter = Some("$[?(@.even == true)]".to_string());
+        let items: Vec<Value> = collect_items(stream::iter(items()).boxed(), filter, Some(2)).await.unwrap();
+        assert_eq!(items, vec![json!({ "id": 0, "even": true }), json!({ "id": 2, "even": true })]);
+    }
+
+    // Numbers the signatures of the requests
+    struct CountingSigner(AtomicUsize);
+
+    impl RequestSigner for CountingSigner {
+        fn sign(&self, request: &mut Request) {
+            let count = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            request.headers_mut().insert("x-signature", HeaderValue::from(count));
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_are_signed_again() {
+        // Unavailable once, then OK, the signature of each attempt is sent back
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            for status in ["503 Service Unavailable", "200 OK"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0u8; 4096];
+                let size = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..size]).to_lowercase();
+                let signature = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("x-signature: "))
+                    .unwrap_or_default()
+                    .to_string();
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    signature.len(),
+                    signature
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let client = Client::new();
+        let signer = CountingSigner(AtomicUsize::new(0));
+        let mut request = client.get(url).build().unwrap();
+        signer.sign(&mut request);
+        let retry_policy = RetryPolicy {
+            initial_backoff_ms: 1,
+            ..RetryPolicy::default()
+        };
+        let response = RequestWrapper::from(Uuid::new_v4(), request)
+            .send(&client, &[], &retry_policy, None, Some(&signer))
+            .await
+            .unwrap();
+        assert_eq!(response.body_text().await, "2");
+    }
+}
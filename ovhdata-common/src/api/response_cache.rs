@@ -0,0 +1,129 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::BUG;
+
+/// GET responses kept on disk, one JSON file per API path
+/// An entry is served as is during its time to live, then revalidated with its ETag
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    // Ignore the cached entries, the fetched responses are still stored
+    refresh: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CachedResponse {
+    pub fetched_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub body: Value,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl, refresh: false }
+    }
+
+    /// Fetch every response again instead of serving the cached ones
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Cached response of an API path, whatever its age
+    pub fn get(&self, path: &[&str]) -> Option<CachedResponse> {
+        if self.refresh {
+            return None;
+        }
+        let content = std::fs::read_to_string(self.file(path)).ok()?;
+        serde_json::from_str::<CachedResponse>(&content).ok()
+    }
+
+    /// A cached response younger than the time to live is served without request
+    pub fn is_fresh(&self, cached: &CachedResponse) -> bool {
+        let age = Utc::now().signed_duration_since(cached.fetched_at);
+        age.to_std().map(|age| age < self.ttl).unwrap_or(true)
+    }
+
+    /// Store the response of an API path, a failure only costs a request next time
+    pub fn put(&self, path: &[&str], cached: &CachedResponse) {
+        let file = self.file(path);
+        let written = file
+            .parent()
+            .map(std::fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| std::fs::write(&file, serde_json::to_vec_pretty(cached).expect(BUG)));
+        match written {
+            Ok(_) => debug!(file = %file.display(), "Response cached"),
+            Err(error) => warn!(file = %file.display(), error = %error, "Unable to cache the response"),
+        }
+    }
+
+    /// Cache file of an API path, e.g. cloud/project/<service>/dataIntegration/sourceConnectors.json
+    fn file(&self, path: &[&str]) -> PathBuf {
+        let mut file = self.dir.clone();
+        for segment in path {
+            file.push(sanitize(segment));
+        }
+        file.set_extension("json");
+        file
+    }
+}
+
+/// Keep a path segment inside its directory
+fn sanitize(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::Utc;
+    use serde_json::json;
+
+    use crate::api::response_cache::{CachedResponse, ResponseCache};
+
+    #[test]
+    fn cache_entries() {
+        let dir = std::env::temp_dir().join(format!("ovhdata-cache-{}", uuid::Uuid::new_v4()));
+        let cache = ResponseCache::new(dir.clone(), Duration::from_secs(60));
+        let path = ["cloud", "project", "abc", "dataIntegration", "sourceConnectors", "../id"];
+        assert_eq!(
+            cache.file(&path),
+            dir.join("cloud/project/abc/dataIntegration/sourceConnectors/___id.json")
+        );
+        assert_eq!(cache.get(&path), None);
+
+        let cached = CachedResponse {
+            fetched_at: Utc::now() - chrono::Duration::seconds(120),
+            etag: Some("\"v1\"".to_string()),
+            body: json!([{ "id": "id" }]),
+        };
+        cache.put(&path, &cached);
+        assert_eq!(cache.get(&path), Some(cached.clone()));
+        assert!(!cache.is_fresh(&cached));
+        assert!(cache.is_fresh(&CachedResponse {
+            fetched_at: Utc::now(),
+            ..cached
+        }));
+
+        let refreshed = ResponseCache::new(dir.clone(), Duration::from_secs(60)).with_refresh(true);
+        assert_eq!(refreshed.get(&path), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+pub const DEFAULT_INITIAL_BACKOFF_MS: u64 = 500;
+pub const DEFAULT_MAX_BACKOFF_MS: u64 = 30_000;
+
+/// Retry policy applied when sending a request to the API
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt (0 disables retries)
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled on each retry
+    pub initial_backoff_ms: u64,
+    /// Upper bound of the backoff, also applied to the Retry-After header
+    pub max_backoff_ms: u64,
+    /// Also retry non idempotent requests (POST, DELETE, PATCH)
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_backoff_ms: DEFAULT_INITIAL_BACKOFF_MS,
+            max_backoff_ms: DEFAULT_MAX_BACKOFF_MS,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Return true if a request with the given method can be sent again
+    pub fn allows_method(&self, method: &Method) -> bool {
+        self.retry_non_idempotent || matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT)
+    }
+
+    /// Return true if the response status is worth a retry
+    pub fn is_retryable_status(status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::INTERNAL_SERVER_ERROR
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    /// Return true if the transport error is worth a retry: the connection failed or timed out
+    pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+        error.is_connect() || error.is_timeout()
+    }
+
+    /// Delay to wait before the given retry (starting at 1)
+    /// The Retry-After header has the priority, otherwise an exponential backoff with full jitter is used
+    pub fn backoff(&self, retry: u32, headers: Option<&HeaderMap>) -> Duration {
+        let max_backoff = Duration::from_millis(self.max_backoff_ms);
+
+        if let Some(retry_after) = headers.and_then(retry_after) {
+            return retry_after.min(max_backoff);
+        }
+
+        let exponential = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff_ms);
+        Duration::from_millis(rand::thread_rng().gen_range(0..=exponential))
+    }
+}
+
+/// Parse the Retry-After header, either a number of seconds or an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    (date - Utc::now()).to_std().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use reqwest::Method;
+    use std::time::Duration;
+
+    use crate::api::RetryPolicy;
+
+    #[test]
+    fn idempotent_methods_only_by_default() {
+        let policy = RetryPolicy::default();
+        assert!(policy.allows_method(&Method::GET));
+        assert!(policy.allows_method(&Method::PUT));
+        assert!(!policy.allows_method(&Method::POST));
+        assert!(!policy.allows_method(&Method::DELETE));
+
+        let policy = RetryPolicy {
+            retry_non_idempotent: true,
+            ..RetryPolicy::default()
+        };
+        assert!(policy.allows_method(&Method::POST));
+        assert!(policy.allows_method(&Method::DELETE));
+    }
+
+    #[test]
+    fn backoff_is_bounded() {
+        let policy = RetryPolicy::default();
+        for retry in 1..20 {
+            assert!(policy.backoff(retry, None) <= Duration::from_millis(policy.max_backoff_ms));
+        }
+    }
+
+    #[test]
+    fn backoff_uses_retry_after() {
+        let policy = RetryPolicy::default();
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(policy.backoff(1, Some(&headers)), Duration::from_secs(7));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+        assert_eq!(policy.backoff(1, Some(&headers)), Duration::from_millis(policy.max_backoff_ms));
+    }
+}
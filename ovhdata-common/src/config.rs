@@ -1,12 +1,22 @@
 use std::collections::hash_map::HashMap;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use descriptor::{Describe, Descriptor};
+use reqwest::{Certificate, Client, NoProxy, Proxy};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error as ThisError;
 
+use crate::api::RetryPolicy;
+
+const PEM_CERTIFICATE_END: &str = "-----END CERTIFICATE-----";
+
+// Connectors rarely change, their catalog is cached for a day by default
+const DEFAULT_CONNECTORS_TTL_SECS: u64 = 86_400;
+
 #[derive(ThisError, Debug, PartialEq)]
 pub enum Error {
     #[error("Unable to find any config with name: {}", .0.as_str())]
@@ -17,6 +27,8 @@ pub enum Error {
     ReadFileError(String),
     #[error("Unable to save configuration into file : {0:?}, message was : {1}")]
     SaveConfigError(PathBuf, String),
+    #[error("Invalid http configuration: {0}")]
+    HttpConfigError(String),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
@@ -129,6 +141,10 @@ impl AllConfig {
     pub fn add_config(&mut self, config_name: impl Into<ConfigName>, config: impl Into<Config>) {
         self.configs.insert(config_name.into(), config.into());
     }
+
+    pub fn remove_config(&mut self, config_name: impl Into<ConfigName>) -> Option<Config> {
+        self.configs.remove(&config_name.into())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Descriptor)]
@@ -139,6 +155,18 @@ pub struct Config {
     pub auth_method: String,
     #[descriptor(rename_header = "ENDPOINT")]
     pub ovhapiv6: ConfigOVHapiV6,
+    #[serde(default)]
+    #[descriptor(skip)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    #[descriptor(skip)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    #[descriptor(skip)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    #[descriptor(skip)]
+    pub schema_types: SchemaTypesConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Descriptor)]
@@ -148,3 +176,147 @@ pub struct ConfigOVHapiV6 {
     #[descriptor(skip)]
     pub create_token_url: String,
 }
+
+/// Network settings of the HTTP clients
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Proxy used for every request, e.g. http://proxy.example.com:3128
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Hosts or domains reached without the proxy
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+    /// PEM files of the certificate authorities to trust in addition to the system ones
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ca_files: Vec<PathBuf>,
+    /// Timeout of the connection, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+    /// Timeout of the whole request, in seconds, not applied to downloads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_timeout_secs: Option<u64>,
+}
+
+/// Settings of the local cache of the API responses
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Time to live of the cached connector catalog, in seconds
+    pub connectors_ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            connectors_ttl_secs: DEFAULT_CONNECTORS_TTL_SECS,
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn connectors_ttl(&self) -> Duration {
+        Duration::from_secs(self.connectors_ttl_secs)
+    }
+}
+
+/// Types of the exported metadata schemas, by metadata type, overriding the default ones
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct SchemaTypesConfig {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub json_schema: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub sql_ddl: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub avro: BTreeMap<String, String>,
+}
+
+impl HttpConfig {
+    /// Build an HTTP client applying these settings
+    pub fn client(&self) -> Result<Client, Error> {
+        let mut builder = Client::builder();
+
+        if let Some(proxy_url) = &self.proxy {
+            let proxy = Proxy::all(proxy_url).map_err(|error| Error::HttpConfigError(format!("proxy {}: {}", proxy_url, error)))?;
+            builder = builder.proxy(proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(","))));
+        }
+
+        for ca_file in &self.ca_files {
+            for certificate in Self::read_certificates(ca_file)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(connect_timeout) = self.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(connect_timeout));
+        }
+        if let Some(request_timeout) = self.request_timeout_secs {
+            builder = builder.timeout(Duration::from_secs(request_timeout));
+        }
+
+        builder.build().map_err(|error| Error::HttpConfigError(error.to_string()))
+    }
+
+    /// Build an HTTP client applying these settings but the request timeout, for downloads whose length is unknown
+    pub fn download_client(&self) -> Result<Client, Error> {
+        Self {
+            request_timeout_secs: None,
+            ..self.clone()
+        }
+        .client()
+    }
+
+    /// Read every certificate of a PEM bundle
+    fn read_certificates(ca_file: &Path) -> Result<Vec<Certificate>, Error> {
+        let pem = std::fs::read_to_string(ca_file).map_err(|error| Error::HttpConfigError(format!("CA file {:?}: {}", ca_file, error)))?;
+
+        let certificates = pem
+            .split_inclusive(PEM_CERTIFICATE_END)
+            .filter(|block| block.contains(PEM_CERTIFICATE_END))
+            .map(|block| Certificate::from_pem(block.as_bytes()).map_err(|error| Error::HttpConfigError(format!("CA file {:?}: {}", ca_file, error))))
+            .collect::<Result<Vec<Certificate>, Error>>()?;
+
+        if certificates.is_empty() {
+            return Err(Error::HttpConfigError(format!("CA file {:?}: no certificate found", ca_file)));
+        }
+        Ok(certificates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::config::{CacheConfig, Config, Error, HttpConfig, SchemaTypesConfig};
+
+    #[test]
+    fn config_without_http_settings() {
+        let config: Config = serde_json::from_str(
+            r#"{"cli_release_url": "", "auth_method": "", "ovhapiv6": {"endpoint_url": "https://eu.api.ovh.com/1.0", "create_token_url": ""}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.http, HttpConfig::default());
+        assert_eq!(config.cache, CacheConfig::default());
+        assert_eq!(config.schema_types, SchemaTypesConfig::default());
+    }
+
+    #[test]
+    fn http_client() {
+        let http = HttpConfig {
+            proxy: Some("http://proxy.example.com:3128".to_string()),
+            no_proxy: vec!["localhost".to_string(), ".internal".to_string()],
+            connect_timeout_secs: Some(5),
+            request_timeout_secs: Some(30),
+            ..HttpConfig::default()
+        };
+        assert!(http.client().is_ok());
+        assert!(http.download_client().is_ok());
+
+        let http = HttpConfig {
+            ca_files: vec![PathBuf::from("/does/not/exist.pem")],
+            ..HttpConfig::default()
+        };
+        assert!(matches!(http.client(), Err(Error::HttpConfigError(_))));
+    }
+}
@@ -21,9 +21,52 @@ pub struct CredentialDetails {
     pub rules: Vec<AccessRule>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Descriptor)]
+#[derive(Debug, Clone, Deserialize, Serialize, Descriptor, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AccessRule {
     pub method: String,
     pub path: String,
 }
+
+impl AccessRule {
+    pub fn new(method: &str, path: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    /// Least privilege rules: the data integration routes and the list of the projects.
+    /// The wildcard of a rule matches several path segments, so the details of a project (`/cloud/project/*`) are not granted,
+    /// that would grant every route of every project.
+    pub fn data_integration() -> Vec<AccessRule> {
+        let mut rules = vec![
+            AccessRule::new("GET", "/auth/currentCredential"),
+            AccessRule::new("GET", "/auth/details"),
+            AccessRule::new("GET", "/cloud/project"),
+        ];
+        for method in ["GET", "POST", "PUT", "DELETE"] {
+            rules.push(AccessRule::new(method, "/cloud/project/*/dataIntegration/*"));
+        }
+        rules
+    }
+}
+
+/// Consumer key creation request
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialRequest {
+    pub access_rules: Vec<AccessRule>,
+    /// Url the user is redirected to once the consumer key is validated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirection: Option<String>,
+}
+
+/// Consumer key waiting for the user validation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialValidation {
+    pub consumer_key: String,
+    pub state: String,
+    pub validation_url: String,
+}
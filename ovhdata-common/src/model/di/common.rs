@@ -33,6 +33,12 @@ pub struct Parameter {
     // Internal use only (skip serialization for api, json & yaml output)
     #[serde(default, skip_serializing)]
     pub secret: bool,
+
+    // Internal use only, command line argument the value comes from (@env:, @file:...),
+    // echoed in the commands instead of the value
+    #[serde(skip)]
+    #[descriptor(skip)]
+    pub origin: Option<String>,
 }
 
 impl EnsureSecret<Parameter> for Parameter {
@@ -49,19 +55,21 @@ pub struct ParametersWrapper(pub Vec<Parameter>);
 
 impl fmt::Display for ParametersWrapper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.0
-                .iter()
-                .map(|param| {
+        let mut arguments: Vec<String> = Vec::new();
+        for param in &self.0 {
+            let argument = match &param.origin {
+                Some(origin) => origin.clone(),
+                None => {
                     let value = if param.secret { "[secret_hidden]" } else { &param.value };
-
                     format!("--parameter {}={}", param.name, value)
-                })
-                .collect::<Vec<_>>()
-                .join(" ")
-        )
+                }
+            };
+            // Parameters from the same file share the same argument
+            if !arguments.contains(&argument) {
+                arguments.push(argument);
+            }
+        }
+        write!(f, "{}", arguments.join(" "))
     }
 }
 
@@ -79,3 +87,33 @@ pub struct ErrorDetails {
     pub code: String,
     pub description: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::model::di::common::{Parameter, ParametersWrapper};
+    use crate::model::di::fixtures;
+
+    fn parameter(name: &str, value: &str, secret: bool, origin: Option<&str>) -> Parameter {
+        Parameter {
+            secret,
+            origin: origin.map(str::to_string),
+            ..fixtures::parameter(name, value)
+        }
+    }
+
+    #[test]
+    fn parameters_command() {
+        let parameters = vec![
+            parameter("host", "db", false, None),
+            parameter("password", "p4ss", true, Some("--parameter password=@env:PASSWORD")),
+            parameter("token", "t0k3n", true, None),
+            parameter("port", "3306", false, Some("--parameters-file params.yaml")),
+            parameter("user", "admin", false, Some("--parameters-file params.yaml")),
+        ];
+
+        assert_eq!(
+            ParametersWrapper(parameters).to_string(),
+            "--parameter host=db --parameter password=@env:PASSWORD --parameter token=[secret_hidden] --parameters-file params.yaml"
+        );
+    }
+}
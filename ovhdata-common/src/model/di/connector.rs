@@ -1,9 +1,43 @@
-use crate::model::di::common::EnsureSecret;
+use crate::model::di::common::{EnsureSecret, Parameter};
 use descriptor::Descriptor;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+use tracing::warn;
 
 use ovhdata_macros::PrintObjectCompletely;
 
+// Type of the connector parameters holding a secret
+pub const SECRET_PARAMETER_TYPE: &str = "secret";
+
+// Largest edit distance of a parameter name suggested for an unknown one
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+#[derive(ThisError, Debug, PartialEq)]
+pub enum ParameterError {
+    #[error("The parameter {0} is unknown{}", did_you_mean(.1))]
+    Unknown(String, Option<String>),
+    #[error("The mandatory parameter {0} is missing")]
+    Missing(String),
+    #[error("The parameter {0} must be an integer, {1} is not")]
+    NotInteger(String, String),
+    #[error("The parameter {0} must be between {1} and {2}, {3} is not")]
+    OutOfRange(String, i64, i64, i64),
+    #[error("The parameter {0} must be true or false, {1} is not")]
+    NotBoolean(String, String),
+    #[error("The parameter {0} doesn't match {1}")]
+    RegexMismatch(String, String),
+}
+
+/// All the problems of the parameters, reported at once
+#[derive(ThisError, Debug, PartialEq)]
+#[error("Invalid parameters:\n  {}", .0.iter().map(ToString::to_string).collect::<Vec<String>>().join("\n  "))]
+pub struct ParameterErrors(pub Vec<ParameterError>);
+
+fn did_you_mean(suggestion: &Option<String>) -> String {
+    suggestion.as_ref().map(|name| format!(", did you mean {} ?", name)).unwrap_or_default()
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, Descriptor, PrintObjectCompletely)]
 #[serde(rename_all = "camelCase")]
 #[descriptor(default_headers = ["name", "id", "version"])]
@@ -55,3 +89,205 @@ pub struct ConnectorValidator {
     pub max: i64,
     pub regex: Option<String>,
 }
+
+impl ConnectorParameter {
+    /// Check a value against the type and the validator of the parameter
+    pub fn check(&self, value: &str) -> Result<(), ParameterError> {
+        // An empty value removes an optional parameter
+        if value.is_empty() {
+            return match self.mandatory {
+                true => Err(ParameterError::Missing(self.name.clone())),
+                false => Ok(()),
+            };
+        }
+
+        match self.type_name.as_str() {
+            "int" => {
+                let integer = value
+                    .parse::<i64>()
+                    .map_err(|_| ParameterError::NotInteger(self.name.clone(), value.to_string()))?;
+                // The same min and max mean any integer
+                if let Some(validator) = self.validator.as_ref().filter(|validator| validator.min != validator.max) {
+                    if integer < validator.min || integer > validator.max {
+                        return Err(ParameterError::OutOfRange(self.name.clone(), validator.min, validator.max, integer));
+                    }
+                }
+            }
+            "boolean" if value.to_lowercase().parse::<bool>().is_err() => {
+                return Err(ParameterError::NotBoolean(self.name.clone(), value.to_string()));
+            }
+            _ => {}
+        }
+
+        let regex = self.validator.as_ref().and_then(|validator| validator.regex.as_deref());
+        match regex.filter(|regex| !regex.is_empty()).map(Regex::new) {
+            Some(Ok(regex)) if !regex.is_match(value) => Err(ParameterError::RegexMismatch(self.name.clone(), regex.to_string())),
+            Some(Err(error)) => {
+                warn!(parameter = %self.name, error = %error, "Invalid regex of the connector parameter, not checked");
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.type_name == SECRET_PARAMETER_TYPE
+    }
+}
+
+/// Unknown parameter, with the closest known name as suggestion
+pub(crate) fn unknown_parameter(name: &str, known_names: &[&str]) -> ParameterError {
+    let suggestion = known_names
+        .iter()
+        .map(|known_name| (strsim::levenshtein(known_name, name), *known_name))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min()
+        .map(|(_, known_name)| known_name.to_string());
+    ParameterError::Unknown(name.to_string(), suggestion)
+}
+
+/// Check the parameters given to create or update a source or a destination, against the connector ones.
+/// The defaults of the connector are added for the parameters neither given nor already set.
+pub fn validate_parameters(
+    connector_parameters: &[ConnectorParameter],
+    parameters: &[Parameter],
+    existing: Option<&[Parameter]>,
+) -> Result<Vec<Parameter>, ParameterErrors> {
+    let mut errors = vec![];
+
+    let known_names = connector_parameters
+        .iter()
+        .map(|connector_parameter| connector_parameter.name.as_str())
+        .collect::<Vec<&str>>();
+    for parameter in parameters {
+        if !known_names.contains(&parameter.name.as_str()) {
+            errors.push(unknown_parameter(&parameter.name, &known_names));
+        }
+    }
+
+    let mut validated = vec![];
+    for connector_parameter in connector_parameters {
+        let given = parameters.iter().find(|parameter| parameter.name == connector_parameter.name);
+        let already_set = existing.is_some_and(|existing| existing.iter().any(|parameter| parameter.name == connector_parameter.name));
+        let default = connector_parameter.default.as_ref().filter(|default| !default.is_empty());
+
+        let (value, origin) = match (given, default) {
+            (Some(parameter), _) => (parameter.value.clone(), parameter.origin.clone()),
+            (None, _) if already_set => continue,
+            (None, Some(default)) => (default.clone(), None),
+            (None, None) if connector_parameter.mandatory => {
+                errors.push(ParameterError::Missing(connector_parameter.name.clone()));
+                continue;
+            }
+            (None, None) => continue,
+        };
+
+        if let Err(error) = connector_parameter.check(&value) {
+            errors.push(error);
+        }
+        validated.push(Parameter {
+            name: connector_parameter.name.clone(),
+            value,
+            secret: connector_parameter.is_secret(),
+            origin,
+        });
+    }
+
+    match errors.is_empty() {
+        true => Ok(validated),
+        false => Err(ParameterErrors(errors)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::di::connector::{validate_parameters, ConnectorParameter, ConnectorValidator, ParameterError, ParameterErrors};
+    use crate::model::di::fixtures::{self, parameter};
+
+    fn connector_parameter(
+        name: &str,
+        type_name: &str,
+        mandatory: bool,
+        default: Option<&str>,
+        validator: Option<ConnectorValidator>,
+    ) -> ConnectorParameter {
+        ConnectorParameter {
+            validator,
+            ..fixtures::connector_parameter(name, type_name, mandatory, default)
+        }
+    }
+
+    fn connector_parameters() -> Vec<ConnectorParameter> {
+        vec![
+            connector_parameter(
+                "host",
+                "string",
+                true,
+                None,
+                Some(ConnectorValidator {
+                    min: 0,
+                    max: 0,
+                    regex: Some("^[a-z.]+$".to_string()),
+                }),
+            ),
+            connector_parameter(
+                "port",
+                "int",
+                true,
+                Some("5432"),
+                Some(ConnectorValidator {
+                    min: 1,
+                    max: 65535,
+                    regex: None,
+                }),
+            ),
+            connector_parameter("password", "secret", true, None, None),
+            connector_parameter("ssl", "boolean", false, None, None),
+        ]
+    }
+
+    #[test]
+    fn validate_with_defaults() {
+        let parameters = vec![parameter("host", "db.local"), parameter("password", "p4ss"), parameter("ssl", "True")];
+        let validated = validate_parameters(&connector_parameters(), &parameters, None).unwrap();
+
+        let values = validated
+            .iter()
+            .map(|parameter| (parameter.name.as_str(), parameter.value.as_str(), parameter.secret))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                ("host", "db.local", false),
+                ("port", "5432", false),
+                ("password", "p4ss", true),
+                ("ssl", "True", false)
+            ]
+        );
+
+        // Already set parameters are not required on update
+        let validated = validate_parameters(&connector_parameters(), &[parameter("port", "3306")], Some(&parameters)).unwrap();
+        assert_eq!(validated.len(), 1);
+    }
+
+    #[test]
+    fn validate_reports_every_error() {
+        let parameters = vec![
+            parameter("hots", "DB"),
+            parameter("host", "DB"),
+            parameter("port", "70000"),
+            parameter("ssl", "yes"),
+        ];
+
+        assert_eq!(
+            validate_parameters(&connector_parameters(), &parameters, None).unwrap_err(),
+            ParameterErrors(vec![
+                ParameterError::Unknown("hots".to_string(), Some("host".to_string())),
+                ParameterError::RegexMismatch("host".to_string(), "^[a-z.]+$".to_string()),
+                ParameterError::OutOfRange("port".to_string(), 1, 65535, 70000),
+                ParameterError::Missing("password".to_string()),
+                ParameterError::NotBoolean("ssl".to_string(), "yes".to_string()),
+            ])
+        );
+    }
+}
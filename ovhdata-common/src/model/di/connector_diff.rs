@@ -0,0 +1,302 @@
+use std::fmt::{Display, Formatter};
+
+use descriptor::{Describe, Descriptor};
+use serde::{Deserialize, Serialize};
+
+use crate::model::di::connector::{ConnectorParameter, ConnectorValidator, DestinationConnector, SourceConnector};
+
+/// Connector of a catalog, the source and destination ones being compared alike
+pub trait CatalogConnector {
+    fn name(&self) -> &str;
+    fn version(&self) -> &str;
+    fn parameters(&self) -> &[ConnectorParameter];
+}
+
+impl CatalogConnector for SourceConnector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn parameters(&self) -> &[ConnectorParameter] {
+        &self.parameters
+    }
+}
+
+impl CatalogConnector for DestinationConnector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn parameters(&self) -> &[ConnectorParameter] {
+        &self.parameters
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConnectorChangeKind {
+    ConnectorAdded,
+    ConnectorRemoved,
+    VersionChanged,
+    ParameterAdded,
+    ParameterRemoved,
+    ParameterChanged,
+}
+
+impl Display for ConnectorChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            ConnectorChangeKind::ConnectorAdded => "connector added",
+            ConnectorChangeKind::ConnectorRemoved => "connector removed",
+            ConnectorChangeKind::VersionChanged => "version changed",
+            ConnectorChangeKind::ParameterAdded => "parameter added",
+            ConnectorChangeKind::ParameterRemoved => "parameter removed",
+            ConnectorChangeKind::ParameterChanged => "parameter changed",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+impl Describe for ConnectorChangeKind {
+    fn to_field(&self, _: &str) -> String {
+        self.to_string()
+    }
+}
+
+/// Difference between two catalogs of connectors, breaking when the sources or destinations using them have to change
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Descriptor)]
+#[serde(rename_all = "camelCase")]
+#[descriptor(default_headers = ["change", "connector", "parameter", "field", "before", "after", "breaking"])]
+pub struct ConnectorChange {
+    pub change: ConnectorChangeKind,
+    pub connector: String,
+    pub parameter: Option<String>,
+    /// Changed field of the parameter: mandatory, type, validator or default
+    pub field: Option<String>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub breaking: bool,
+}
+
+impl ConnectorChange {
+    fn connector(change: ConnectorChangeKind, connector: &str, before: Option<String>, after: Option<String>) -> Self {
+        Self {
+            change,
+            connector: connector.to_string(),
+            parameter: None,
+            field: None,
+            before,
+            after,
+            breaking: change == ConnectorChangeKind::ConnectorRemoved,
+        }
+    }
+
+    fn parameter(change: ConnectorChangeKind, connector: &str, parameter: &str, breaking: bool) -> Self {
+        Self {
+            change,
+            connector: connector.to_string(),
+            parameter: Some(parameter.to_string()),
+            field: None,
+            before: None,
+            after: None,
+            breaking,
+        }
+    }
+
+    fn field(connector: &str, parameter: &str, field: &str, before: String, after: String, breaking: bool) -> Self {
+        Self {
+            field: Some(field.to_string()),
+            before: Some(before),
+            after: Some(after),
+            ..Self::parameter(ConnectorChangeKind::ParameterChanged, connector, parameter, breaking)
+        }
+    }
+}
+
+/// Changes of the connectors, matched by name, from a catalog to another one
+pub fn diff_connectors<T: CatalogConnector>(before: &[T], after: &[T]) -> Vec<ConnectorChange> {
+    let mut changes = vec![];
+
+    for old_connector in before {
+        let name = old_connector.name();
+        let new_connector = match after.iter().find(|connector| connector.name() == name) {
+            Some(new_connector) => new_connector,
+            None => {
+                let version = Some(old_connector.version().to_string());
+                changes.push(ConnectorChange::connector(ConnectorChangeKind::ConnectorRemoved, name, version, None));
+                continue;
+            }
+        };
+
+        if old_connector.version() != new_connector.version() {
+            let (old_version, new_version) = (Some(old_connector.version().to_string()), Some(new_connector.version().to_string()));
+            changes.push(ConnectorChange::connector(
+                ConnectorChangeKind::VersionChanged,
+                name,
+                old_version,
+                new_version,
+            ));
+        }
+        changes.extend(diff_parameters(name, old_connector.parameters(), new_connector.parameters()));
+    }
+
+    for new_connector in after {
+        if !before.iter().any(|connector| connector.name() == new_connector.name()) {
+            let version = Some(new_connector.version().to_string());
+            changes.push(ConnectorChange::connector(
+                ConnectorChangeKind::ConnectorAdded,
+                new_connector.name(),
+                None,
+                version,
+            ));
+        }
+    }
+
+    changes
+}
+
+fn diff_parameters(connector: &str, before: &[ConnectorParameter], after: &[ConnectorParameter]) -> Vec<ConnectorChange> {
+    let mut changes = vec![];
+
+    for old_parameter in before {
+        let name = old_parameter.name.as_str();
+        let new_parameter = match after.iter().find(|parameter| parameter.name == name) {
+            Some(new_parameter) => new_parameter,
+            None => {
+                // The sources or destinations still setting it are rejected
+                changes.push(ConnectorChange::parameter(ConnectorChangeKind::ParameterRemoved, connector, name, true));
+                continue;
+            }
+        };
+
+        if old_parameter.mandatory != new_parameter.mandatory {
+            let breaking = new_parameter.mandatory && !has_default(new_parameter);
+            let (old_mandatory, new_mandatory) = (old_parameter.mandatory.to_string(), new_parameter.mandatory.to_string());
+            changes.push(ConnectorChange::field(
+                connector,
+                name,
+                "mandatory",
+                old_mandatory,
+                new_mandatory,
+                breaking,
+            ));
+        }
+        if old_parameter.type_name != new_parameter.type_name {
+            let (old_type, new_type) = (old_parameter.type_name.clone(), new_parameter.type_name.clone());
+            changes.push(ConnectorChange::field(connector, name, "type", old_type, new_type, true));
+        }
+        let (old_validator, new_validator) = (validator(&old_parameter.validator), validator(&new_parameter.validator));
+        if old_validator != new_validator {
+            changes.push(ConnectorChange::field(connector, name, "validator", old_validator, new_validator, false));
+        }
+        let (old_default, new_default) = (
+            old_parameter.default.clone().unwrap_or_default(),
+            new_parameter.default.clone().unwrap_or_default(),
+        );
+        if old_default != new_default {
+            changes.push(ConnectorChange::field(connector, name, "default", old_default, new_default, false));
+        }
+    }
+
+    for new_parameter in after {
+        if !before.iter().any(|parameter| parameter.name == new_parameter.name) {
+            let breaking = new_parameter.mandatory && !has_default(new_parameter);
+            changes.push(ConnectorChange::parameter(
+                ConnectorChangeKind::ParameterAdded,
+                connector,
+                &new_parameter.name,
+                breaking,
+            ));
+        }
+    }
+
+    changes
+}
+
+fn has_default(parameter: &ConnectorParameter) -> bool {
+    parameter.default.as_ref().is_some_and(|default| !default.is_empty())
+}
+
+fn validator(validator: &Option<ConnectorValidator>) -> String {
+    match validator {
+        Some(validator) => {
+            let regex = validator.regex.as_deref().filter(|regex| !regex.is_empty());
+            format!(
+                "min={} max={}{}",
+                validator.min,
+                validator.max,
+                regex.map(|regex| format!(" regex={}", regex)).unwrap_or_default()
+            )
+        }
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::di::connector_diff::{diff_connectors, ConnectorChangeKind};
+    use crate::model::di::fixtures::{connector_parameter as parameter, source_connector as connector};
+
+    #[test]
+    fn diff_catalogs() {
+        let before = vec![
+            connector(
+                "MySQL",
+                "1.0",
+                vec![
+                    parameter("host", "string", true, None),
+                    parameter("port", "int", false, Some("3306")),
+                    parameter("ssl", "boolean", false, None),
+                ],
+            ),
+            connector("Oracle", "1.0", vec![]),
+        ];
+        let after = vec![
+            connector(
+                "MySQL",
+                "2.0",
+                vec![
+                    parameter("host", "string", true, None),
+                    parameter("port", "string", false, Some("3307")),
+                    parameter("database", "string", true, None),
+                    parameter("timeout", "int", true, Some("30")),
+                ],
+            ),
+            connector("PostgreSQL", "1.0", vec![]),
+        ];
+
+        let changes = diff_connectors(&before, &after)
+            .into_iter()
+            .map(|change| {
+                (
+                    change.change,
+                    change.parameter.unwrap_or_default(),
+                    change.field.unwrap_or_default(),
+                    change.breaking,
+                )
+            })
+            .collect::<Vec<_>>();
+        let change = |kind, parameter: &str, field: &str, breaking| (kind, parameter.to_string(), field.to_string(), breaking);
+        assert_eq!(
+            changes,
+            vec![
+                change(ConnectorChangeKind::VersionChanged, "", "", false),
+                change(ConnectorChangeKind::ParameterChanged, "port", "type", true),
+                change(ConnectorChangeKind::ParameterChanged, "port", "default", false),
+                change(ConnectorChangeKind::ParameterRemoved, "ssl", "", true),
+                change(ConnectorChangeKind::ParameterAdded, "database", "", true),
+                change(ConnectorChangeKind::ParameterAdded, "timeout", "", false),
+                change(ConnectorChangeKind::ConnectorRemoved, "", "", true),
+                change(ConnectorChangeKind::ConnectorAdded, "", "", false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use crate::model::di::common::Parameter;
+use crate::model::di::connector::{ConnectorParameter, SourceConnector};
+use crate::model::di::source_metadata::{Metadata, TableMeta};
+
+/// Parameter neither secret nor given on the command line
+pub fn parameter(name: &str, value: &str) -> Parameter {
+    Parameter {
+        name: name.to_string(),
+        value: value.to_string(),
+        secret: false,
+        origin: None,
+    }
+}
+
+/// Connector parameter without validator
+pub fn connector_parameter(name: &str, type_name: &str, mandatory: bool, default: Option<&str>) -> ConnectorParameter {
+    ConnectorParameter {
+        name: name.to_string(),
+        default: default.map(str::to_string),
+        mandatory,
+        type_name: type_name.to_string(),
+        validator: None,
+        description: String::new(),
+    }
+}
+
+/// Source connector identified by its lowercase name
+pub fn source_connector(name: &str, version: &str, parameters: Vec<ConnectorParameter>) -> SourceConnector {
+    SourceConnector {
+        id: name.to_lowercase(),
+        name: name.to_string(),
+        version: version.to_string(),
+        description: String::new(),
+        documentation_url: None,
+        parameters,
+    }
+}
+
+/// Table successfully extracted, with the name, type and cardinality of each column
+pub fn table(name: &str, columns: &[(&str, &str, i64)]) -> TableMeta {
+    TableMeta {
+        table_name: Some(name.to_string()),
+        status: "SUCCESS".to_string(),
+        error: None,
+        error_code: None,
+        started_at: None,
+        ended_at: None,
+        metadata: columns
+            .iter()
+            .map(|(name, type_name, cardinality)| Metadata {
+                name: name.to_string(),
+                type_name: type_name.to_string(),
+                cardinality: *cardinality,
+                min: 0,
+                max: *cardinality,
+            })
+            .collect(),
+    }
+}
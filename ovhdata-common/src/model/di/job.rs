@@ -22,11 +22,33 @@ pub struct Job {
     pub ended_at: Option<DateTime<Utc>>,
 }
 
+/// Final state of a job
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobOutcome {
+    Completed,
+    Failed,
+    Interrupted,
+}
+
+impl Job {
+    /// Final state of the job, none while it is pending or running
+    pub fn outcome(&self) -> Option<JobOutcome> {
+        match self.status.as_str() {
+            "COMPLETED" | "DONE" => Some(JobOutcome::Completed),
+            "FAILED" | "ERROR" => Some(JobOutcome::Failed),
+            "INTERRUPTED" | "CANCELLED" => Some(JobOutcome::Interrupted),
+            // Unknown status of an ended job
+            _ if self.ended_at.is_some() => Some(JobOutcome::Failed),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use descriptor::{object_describe_to_string, table_describe_to_string};
 
-    use crate::model::di::job::Job;
+    use crate::model::di::job::{Job, JobOutcome};
     use crate::utils::date::datetime_micro;
 
     fn create_job() -> Job {
@@ -57,6 +79,19 @@ Ended At:   03-06-21 12:29:46
         )
     }
 
+    #[test]
+    fn job_outcome() {
+        let mut job = create_job();
+        assert_eq!(job.outcome(), Some(JobOutcome::Completed));
+
+        job.status = "INTERRUPTED".to_string();
+        assert_eq!(job.outcome(), Some(JobOutcome::Interrupted));
+
+        job.status = "RUNNING".to_string();
+        job.ended_at = None;
+        assert_eq!(job.outcome(), None);
+    }
+
     #[test]
     fn table_job() {
         let job = create_job();
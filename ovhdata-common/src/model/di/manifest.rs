@@ -0,0 +1,681 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+use crate::model::di::common::Parameter;
+use crate::model::di::destination::{Destination, DestinationSpec};
+use crate::model::di::source::{Source, SourceSpec};
+use crate::model::di::workflow::{Workflow, WorkflowPatch, WorkflowSpec};
+use crate::utils::cron::{CronError, CronSchedule};
+use crate::utils::reference::{ReferenceError, ValueReference};
+
+#[derive(ThisError, Debug, PartialEq)]
+pub enum Error {
+    #[error("The {0} {1} is declared several times")]
+    Duplicate(ResourceKind, String),
+    #[error("Several existing {0}s are named {1}, rename them to apply the manifests")]
+    Ambiguous(ResourceKind, String),
+    #[error("The workflow {0} references the {1} {2} which doesn't exist")]
+    UnknownReference(String, ResourceKind, String),
+    #[error("The {2} of the {0} {1} can't be changed, delete it first")]
+    Immutable(ResourceKind, String, &'static str),
+    #[error("The parameter {2} of the {0} {1} can't be resolved, {3}")]
+    UnresolvedReference(ResourceKind, String, String, ReferenceError),
+    #[error("The schedule of the workflow {0} is invalid: {1}")]
+    InvalidSchedule(String, CronError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResourceKind {
+    Source,
+    Destination,
+    Workflow,
+}
+
+impl Display for ResourceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceKind::Source => f.write_str("source"),
+            ResourceKind::Destination => f.write_str("destination"),
+            ResourceKind::Workflow => f.write_str("workflow"),
+        }
+    }
+}
+
+/// DI resources declared in a manifest, the workflows reference their source and destination by name
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<ConnectionManifest>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub destinations: Vec<ConnectionManifest>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workflows: Vec<WorkflowManifest>,
+}
+
+/// Source or destination of a manifest
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ConnectionManifest {
+    pub name: String,
+    pub connector_id: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WorkflowManifest {
+    pub name: String,
+    pub region: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Name of the source
+    pub source: String,
+    /// Name of the destination
+    pub destination: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    #[serde(default = "default_as_true")]
+    pub enabled: bool,
+}
+
+/// What applying a manifest does to a resource
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Create,
+    /// ID of the existing resource and the fields to update
+    Update(String, Vec<String>),
+    /// ID of the existing resource
+    Unchanged(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    pub action: Action,
+    pub manifest: T,
+}
+
+/// Changes needed for the existing resources to match the manifests, in the order to apply them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    pub sources: Vec<Change<ConnectionManifest>>,
+    pub destinations: Vec<Change<ConnectionManifest>>,
+    pub workflows: Vec<Change<WorkflowManifest>>,
+}
+
+// Fields of an existing source or destination
+struct Existing<'a> {
+    id: &'a str,
+    name: &'a str,
+    connector_id: &'a str,
+    parameters: &'a [Parameter],
+}
+
+impl Manifest {
+    /// Manifest of existing resources, without their volatile fields (status, dates...)
+    /// The secret parameters become references to environment variables, and the workflows reference their source and destination by name
+    pub fn export(sources: &[Source], destinations: &[Destination], workflows: &[Workflow], is_secret: impl Fn(&str, &Parameter) -> bool) -> Self {
+        let connection = |name: &str, connector_id: &str, parameters: &[Parameter]| ConnectionManifest {
+            name: name.to_string(),
+            connector_id: connector_id.to_string(),
+            parameters: parameters
+                .iter()
+                .map(|parameter| {
+                    let value = match parameter.secret || is_secret(connector_id, parameter) {
+                        true => ValueReference::Env(secret_variable(name, &parameter.name)).to_string(),
+                        false => parameter.value.clone(),
+                    };
+                    (parameter.name.clone(), value)
+                })
+                .collect(),
+        };
+        // Name of a source or destination, the one known by the workflow if it doesn't exist anymore
+        let name_of = |id: &Option<String>, name: &Option<String>, names: &[(&str, &str)]| {
+            names
+                .iter()
+                .find(|(existing_id, _)| Some(*existing_id) == id.as_deref())
+                .map(|(_, name)| name.to_string())
+                .or(name.clone())
+                .or(id.clone())
+                .unwrap_or_default()
+        };
+        let source_names = sources
+            .iter()
+            .map(|source| (source.id.as_str(), source.name.as_str()))
+            .collect::<Vec<_>>();
+        let destination_names = destinations
+            .iter()
+            .map(|destination| (destination.id.as_str(), destination.name.as_str()))
+            .collect::<Vec<_>>();
+
+        let mut manifest = Manifest {
+            sources: sources
+                .iter()
+                .map(|source| connection(&source.name, &source.connector_id, &source.parameters))
+                .collect(),
+            destinations: destinations
+                .iter()
+                .map(|destination| connection(&destination.name, &destination.connector_id, &destination.parameters))
+                .collect(),
+            workflows: workflows
+                .iter()
+                .map(|workflow| WorkflowManifest {
+                    name: workflow.name.clone(),
+                    region: workflow.region.clone(),
+                    description: workflow.description.clone().filter(|description| !description.is_empty()),
+                    source: name_of(&workflow.source_id, &workflow.source_name, &source_names),
+                    destination: name_of(&workflow.destination_id, &workflow.destination_name, &destination_names),
+                    schedule: workflow.schedule.clone().filter(|schedule| !schedule.is_empty()),
+                    enabled: workflow.enabled,
+                })
+                .collect(),
+        };
+        manifest.sources.sort_by(|left, right| left.name.cmp(&right.name));
+        manifest.destinations.sort_by(|left, right| left.name.cmp(&right.name));
+        manifest.workflows.sort_by(|left, right| left.name.cmp(&right.name));
+        manifest
+    }
+
+    /// Replace the references to environment variables and files by their value.
+    /// A `-` value is kept as it is, the standard input may hold the manifest itself.
+    pub fn resolve_references(&mut self, resolve: impl Fn(&ValueReference) -> Result<String, ReferenceError>) -> Result<(), Error> {
+        let connections = self
+            .sources
+            .iter_mut()
+            .map(|source| (ResourceKind::Source, source))
+            .chain(self.destinations.iter_mut().map(|destination| (ResourceKind::Destination, destination)));
+        for (kind, connection) in connections {
+            for (name, value) in connection.parameters.iter_mut() {
+                match ValueReference::parse(value) {
+                    None | Some(ValueReference::Stdin) => {}
+                    Some(reference) => {
+                        *value =
+                            resolve(&reference).map_err(|error| Error::UnresolvedReference(kind, connection.name.clone(), name.clone(), error))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the resources of another manifest
+    pub fn extend(&mut self, other: Manifest) {
+        self.sources.extend(other.sources);
+        self.destinations.extend(other.destinations);
+        self.workflows.extend(other.workflows);
+    }
+
+    /// Match the manifests to the existing resources by name, and list what has to be created or updated.
+    /// The existing secret parameters can't be compared, they are only updated when forced.
+    pub fn plan(&self, sources: &[Source], destinations: &[Destination], workflows: &[Workflow], force_secrets: bool) -> Result<Plan, Error> {
+        let sources = sources
+            .iter()
+            .map(|source| Existing {
+                id: &source.id,
+                name: &source.name,
+                connector_id: &source.connector_id,
+                parameters: &source.parameters,
+            })
+            .collect::<Vec<Existing>>();
+        let destinations = destinations
+            .iter()
+            .map(|destination| Existing {
+                id: &destination.id,
+                name: &destination.name,
+                connector_id: &destination.connector_id,
+                parameters: &destination.parameters,
+            })
+            .collect::<Vec<Existing>>();
+
+        Ok(Plan {
+            sources: Self::plan_connections(ResourceKind::Source, &self.sources, &sources, force_secrets)?,
+            destinations: Self::plan_connections(ResourceKind::Destination, &self.destinations, &destinations, force_secrets)?,
+            workflows: self.plan_workflows(&sources, &destinations, workflows)?,
+        })
+    }
+
+    fn plan_connections(
+        kind: ResourceKind,
+        manifests: &[ConnectionManifest],
+        existing: &[Existing],
+        force_secrets: bool,
+    ) -> Result<Vec<Change<ConnectionManifest>>, Error> {
+        check_unique(kind, manifests.iter().map(|manifest| manifest.name.as_str()))?;
+
+        manifests
+            .iter()
+            .map(|manifest| {
+                let action = match find_by_name(kind, existing, &manifest.name, |existing| existing.name)? {
+                    None => Action::Create,
+                    Some(existing) if existing.connector_id != manifest.connector_id => {
+                        return Err(Error::Immutable(kind, manifest.name.clone(), "connector"));
+                    }
+                    Some(existing) => {
+                        let fields = manifest.changed_parameters(existing.parameters, force_secrets);
+                        match fields.is_empty() {
+                            true => Action::Unchanged(existing.id.to_string()),
+                            false => Action::Update(existing.id.to_string(), fields),
+                        }
+                    }
+                };
+                Ok(Change {
+                    action,
+                    manifest: manifest.clone(),
+                })
+            })
+            .collect()
+    }
+
+    fn plan_workflows(
+        &self,
+        sources: &[Existing],
+        destinations: &[Existing],
+        workflows: &[Workflow],
+    ) -> Result<Vec<Change<WorkflowManifest>>, Error> {
+        check_unique(ResourceKind::Workflow, self.workflows.iter().map(|manifest| manifest.name.as_str()))?;
+
+        self.workflows
+            .iter()
+            .map(|manifest| {
+                if let Some(schedule) = &manifest.schedule {
+                    CronSchedule::parse(schedule).map_err(|error| Error::InvalidSchedule(manifest.name.clone(), error))?;
+                }
+
+                // ID of the referenced source and destination, none if they are created by the manifests
+                let source_id = self.resolve(ResourceKind::Source, sources, manifest)?;
+                let destination_id = self.resolve(ResourceKind::Destination, destinations, manifest)?;
+
+                let existing = find_by_name(ResourceKind::Workflow, workflows, &manifest.name, |workflow| workflow.name.as_str())?;
+                let action = match existing {
+                    None => Action::Create,
+                    Some(existing) => {
+                        let immutable = |field| Err(Error::Immutable(ResourceKind::Workflow, manifest.name.clone(), field));
+                        if existing.region != manifest.region {
+                            return immutable("region");
+                        }
+                        if existing.source_id.as_deref() != source_id {
+                            return immutable("source");
+                        }
+                        if existing.destination_id.as_deref() != destination_id {
+                            return immutable("destination");
+                        }
+
+                        let fields = manifest.changed_fields(existing);
+                        match fields.is_empty() {
+                            true => Action::Unchanged(existing.id.clone()),
+                            false => Action::Update(existing.id.clone(), fields),
+                        }
+                    }
+                };
+                Ok(Change {
+                    action,
+                    manifest: manifest.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// ID of the existing source or destination of a workflow, an error if it is neither existing nor declared
+    fn resolve<'a>(&self, kind: ResourceKind, existing: &[Existing<'a>], workflow: &WorkflowManifest) -> Result<Option<&'a str>, Error> {
+        let (name, manifests) = match kind {
+            ResourceKind::Source => (&workflow.source, &self.sources),
+            _ => (&workflow.destination, &self.destinations),
+        };
+        match find_by_name(kind, existing, name, |existing| existing.name)? {
+            Some(existing) => Ok(Some(existing.id)),
+            None if manifests.iter().any(|manifest| &manifest.name == name) => Ok(None),
+            None => Err(Error::UnknownReference(workflow.name.clone(), kind, name.clone())),
+        }
+    }
+}
+
+impl ConnectionManifest {
+    pub fn parameters(&self) -> Vec<Parameter> {
+        self.parameters
+            .iter()
+            .map(|(name, value)| Parameter {
+                name: name.clone(),
+                value: value.clone(),
+                secret: false,
+                origin: None,
+            })
+            .collect()
+    }
+
+    pub fn source_spec(&self) -> SourceSpec {
+        SourceSpec {
+            name: self.name.clone(),
+            connector_id: Some(self.connector_id.clone()),
+            parameters: self.parameters(),
+        }
+    }
+
+    pub fn destination_spec(&self) -> DestinationSpec {
+        DestinationSpec {
+            name: self.name.clone(),
+            connector_id: Some(self.connector_id.clone()),
+            parameters: self.parameters(),
+        }
+    }
+
+    /// Parameters added, removed or changed, the existing secret values can't be compared and are changed only when forced
+    fn changed_parameters(&self, existing: &[Parameter], force_secrets: bool) -> Vec<String> {
+        let mut changed = self
+            .parameters
+            .iter()
+            .filter(|(name, value)| {
+                !existing.iter().any(|parameter| {
+                    &parameter.name == *name
+                        && match parameter.secret {
+                            true => !force_secrets,
+                            false => &parameter.value == *value,
+                        }
+                })
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<String>>();
+        changed.extend(
+            existing
+                .iter()
+                .filter(|parameter| !self.parameters.contains_key(&parameter.name))
+                .map(|parameter| parameter.name.clone()),
+        );
+        changed.sort();
+        changed.into_iter().map(|name| format!("parameters.{}", name)).collect()
+    }
+}
+
+impl WorkflowManifest {
+    pub fn spec(&self, source_id: String, destination_id: String) -> WorkflowSpec {
+        WorkflowSpec {
+            name: self.name.clone(),
+            region: self.region.clone(),
+            description: self.description.clone(),
+            source_id,
+            destination_id,
+            schedule: self.schedule.clone(),
+            enabled: self.enabled,
+        }
+    }
+
+    /// Patch matching the manifest, a description or schedule missing from it is cleared with an empty value
+    pub fn patch(&self) -> WorkflowPatch {
+        WorkflowPatch {
+            name: None,
+            description: Some(self.description.clone().unwrap_or_default()),
+            schedule: Some(self.schedule.clone().unwrap_or_default()),
+            enabled: Some(self.enabled),
+        }
+    }
+
+    fn changed_fields(&self, existing: &Workflow) -> Vec<String> {
+        // An empty value is the same as no value
+        let same = |left: &Option<String>, right: &Option<String>| {
+            left.as_deref().filter(|value| !value.is_empty()) == right.as_deref().filter(|value| !value.is_empty())
+        };
+
+        let mut fields = vec![];
+        if !same(&self.description, &existing.description) {
+            fields.push("description".to_string());
+        }
+        if !same(&self.schedule, &existing.schedule) {
+            fields.push("schedule".to_string());
+        }
+        if self.enabled != existing.enabled {
+            fields.push("enabled".to_string());
+        }
+        fields
+    }
+}
+
+impl Plan {
+    /// Number of resources to create or update
+    pub fn change_count(&self) -> usize {
+        let actions = self
+            .sources
+            .iter()
+            .chain(self.destinations.iter())
+            .map(|change| &change.action)
+            .chain(self.workflows.iter().map(|change| &change.action));
+        actions.filter(|action| !matches!(action, Action::Unchanged(_))).count()
+    }
+}
+
+fn check_unique<'a>(kind: ResourceKind, names: impl Iterator<Item = &'a str>) -> Result<(), Error> {
+    let mut seen = vec![];
+    for name in names {
+        if seen.contains(&name) {
+            return Err(Error::Duplicate(kind, name.to_string()));
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+/// The existing resource with the given name, if any
+fn find_by_name<'a, T>(kind: ResourceKind, items: &'a [T], name: &str, item_name: impl Fn(&T) -> &str) -> Result<Option<&'a T>, Error> {
+    let mut matches = items.iter().filter(|item| item_name(item) == name);
+    match (matches.next(), matches.next()) {
+        (Some(_), Some(_)) => Err(Error::Ambiguous(kind, name.to_string())),
+        (item, _) => Ok(item),
+    }
+}
+
+/// Environment variable holding a secret parameter, e.g. OVHDATA_SECRET_MY_SOURCE_PASSWORD
+fn secret_variable(resource_name: &str, parameter_name: &str) -> String {
+    format!("OVHDATA_SECRET_{}_{}", resource_name, parameter_name)
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect()
+}
+
+fn default_as_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::model::di::fixtures::parameter;
+    use crate::model::di::manifest::{Action, Error, Manifest, ResourceKind};
+    use crate::model::di::source::Source;
+    use crate::model::di::workflow::Workflow;
+    use crate::utils::cron::CronError;
+    use crate::utils::reference::{ReferenceError, ValueReference};
+    use std::collections::BTreeMap;
+
+    const MANIFEST: &str = r#"{
+        "sources": [
+            { "name": "s3", "connectorId": "s3-connector", "parameters": { "bucket": "data", "region": "gra" } },
+            { "name": "mysql", "connectorId": "mysql-connector", "parameters": { "host": "db" } }
+        ],
+        "destinations": [{ "name": "lake", "connectorId": "iceberg-connector" }],
+        "workflows": [{ "name": "daily", "region": "GRA", "source": "s3", "destination": "lake", "schedule": "0 1 * * *" }]
+    }"#;
+
+    fn source(id: &str, name: &str, parameters: &[(&str, &str)]) -> Source {
+        Source {
+            id: id.to_string(),
+            name: name.to_string(),
+            status: "CONNECTION_SUCCEED".to_string(),
+            creation_date: Utc::now(),
+            last_update_date: None,
+            connector_id: format!("{}-connector", name),
+            parameters: parameters.iter().map(|(name, value)| parameter(name, value)).collect(),
+        }
+    }
+
+    #[test]
+    fn plan_changes() {
+        let manifest = serde_json::from_str::<Manifest>(MANIFEST).unwrap();
+        assert!(manifest.workflows[0].enabled);
+
+        let sources = vec![
+            source("s3-id", "s3", &[("bucket", "data"), ("region", "gra")]),
+            source("mysql-id", "mysql", &[("host", "old-db"), ("port", "3306")]),
+        ];
+        let plan = manifest.plan(&sources, &[], &[], false).unwrap();
+        assert_eq!(plan.sources[0].action, Action::Unchanged("s3-id".to_string()));
+        assert_eq!(
+            plan.sources[1].action,
+            Action::Update("mysql-id".to_string(), vec!["parameters.host".to_string(), "parameters.port".to_string()])
+        );
+        assert_eq!(plan.destinations[0].action, Action::Create);
+        assert_eq!(plan.workflows[0].action, Action::Create);
+        assert_eq!(plan.change_count(), 3);
+
+        let mut secret_sources = vec![source("mysql-id", "mysql", &[("host", "secret-db")])];
+        secret_sources[0].parameters[0].secret = true;
+        let plan = manifest.plan(&secret_sources, &[], &[], false).unwrap();
+        assert_eq!(plan.sources[1].action, Action::Unchanged("mysql-id".to_string()));
+        let plan = manifest.plan(&secret_sources, &[], &[], true).unwrap();
+        assert_eq!(
+            plan.sources[1].action,
+            Action::Update("mysql-id".to_string(), vec!["parameters.host".to_string()])
+        );
+
+        let workflow = Workflow {
+            id: "daily-id".to_string(),
+            name: "daily".to_string(),
+            description: Some("".to_string()),
+            region: "GRA".to_string(),
+            source_id: Some("s3-id".to_string()),
+            source_name: Some("s3".to_string()),
+            destination_id: Some("lake-id".to_string()),
+            destination_name: Some("lake".to_string()),
+            parameters: vec![],
+            last_execution_date: None,
+            schedule: Some("0 2 * * *".to_string()),
+            enabled: true,
+            status: None,
+            error_details: None,
+        };
+        assert_eq!(
+            manifest.plan(&sources, &[], &[workflow], false),
+            Err(Error::Immutable(ResourceKind::Workflow, "daily".to_string(), "destination"))
+        );
+    }
+
+    #[test]
+    fn plan_and_patch_cleared_fields() {
+        let mut manifest = serde_json::from_str::<Manifest>(MANIFEST).unwrap();
+        manifest.workflows[0].schedule = None;
+        let sources = vec![source("s3-id", "s3", &[("bucket", "data"), ("region", "gra")])];
+        let workflow = Workflow {
+            id: "daily-id".to_string(),
+            name: "daily".to_string(),
+            description: Some("Daily copy".to_string()),
+            region: "GRA".to_string(),
+            source_id: Some("s3-id".to_string()),
+            source_name: Some("s3".to_string()),
+            destination_id: None,
+            destination_name: None,
+            parameters: vec![],
+            last_execution_date: None,
+            schedule: Some("0 2 * * *".to_string()),
+            enabled: true,
+            status: None,
+            error_details: None,
+        };
+
+        let plan = manifest.plan(&sources, &[], &[workflow], false).unwrap();
+        assert_eq!(
+            plan.workflows[0].action,
+            Action::Update("daily-id".to_string(), vec!["description".to_string(), "schedule".to_string()])
+        );
+        let patch = plan.workflows[0].manifest.patch();
+        assert_eq!((patch.description, patch.schedule), (Some(String::new()), Some(String::new())));
+    }
+
+    #[test]
+    fn plan_errors() {
+        let mut manifest = serde_json::from_str::<Manifest>(MANIFEST).unwrap();
+        manifest.workflows[0].source = "unknown".to_string();
+        assert_eq!(
+            manifest.plan(&[], &[], &[], false),
+            Err(Error::UnknownReference("daily".to_string(), ResourceKind::Source, "unknown".to_string()))
+        );
+
+        manifest.extend(serde_json::from_str::<Manifest>(r#"{ "destinations": [{ "name": "lake", "connectorId": "other" }] }"#).unwrap());
+        assert_eq!(
+            manifest.plan(&[], &[], &[], false),
+            Err(Error::Duplicate(ResourceKind::Destination, "lake".to_string()))
+        );
+
+        let manifest = serde_json::from_str::<Manifest>(MANIFEST).unwrap();
+        assert_eq!(
+            manifest.plan(&[source("id", "s3", &[]), source("other-id", "s3", &[])], &[], &[], false),
+            Err(Error::Ambiguous(ResourceKind::Source, "s3".to_string()))
+        );
+
+        let mut manifest = serde_json::from_str::<Manifest>(MANIFEST).unwrap();
+        manifest.workflows[0].schedule = Some("0 1 * *".to_string());
+        assert_eq!(
+            manifest.plan(&[], &[], &[], false),
+            Err(Error::InvalidSchedule("daily".to_string(), CronError::FieldCount(4)))
+        );
+    }
+
+    #[test]
+    fn export_manifest() {
+        let mut mysql = source("mysql-id", "my-sql", &[("host", "db"), ("password", "p4ss")]);
+        mysql.parameters[1].secret = true;
+        let sources = vec![source("s3-id", "s3", &[("key", "abc")]), mysql];
+        let workflow = Workflow {
+            id: "daily-id".to_string(),
+            name: "daily".to_string(),
+            description: Some("".to_string()),
+            region: "GRA".to_string(),
+            source_id: Some("s3-id".to_string()),
+            source_name: None,
+            destination_id: Some("deleted-id".to_string()),
+            destination_name: Some("lake".to_string()),
+            parameters: vec![],
+            last_execution_date: None,
+            schedule: None,
+            enabled: false,
+            status: Some("READY".to_string()),
+            error_details: None,
+        };
+
+        let mut manifest = Manifest::export(&sources, &[], &[workflow], |connector_id, parameter| {
+            connector_id == "s3-connector" && parameter.name == "key"
+        });
+        assert_eq!(manifest.sources[0].name, "my-sql");
+        assert_eq!(
+            manifest.sources[0].parameters,
+            BTreeMap::from([
+                ("host".to_string(), "db".to_string()),
+                ("password".to_string(), "@env:OVHDATA_SECRET_MY_SQL_PASSWORD".to_string())
+            ])
+        );
+        assert_eq!(manifest.sources[1].parameters["key"], "@env:OVHDATA_SECRET_S3_KEY");
+        assert_eq!(
+            serde_json::to_value(&manifest.workflows).unwrap(),
+            serde_json::json!([{ "name": "daily", "region": "GRA", "source": "s3", "destination": "lake", "enabled": false }])
+        );
+
+        assert_eq!(
+            manifest.resolve_references(|reference| match reference {
+                ValueReference::Env(variable) if variable == "OVHDATA_SECRET_MY_SQL_PASSWORD" => Ok("p4ss".to_string()),
+                _ => Err(ReferenceError::MissingEnv("OVHDATA_SECRET_S3_KEY".to_string())),
+            }),
+            Err(Error::UnresolvedReference(
+                ResourceKind::Source,
+                "s3".to_string(),
+                "key".to_string(),
+                ReferenceError::MissingEnv("OVHDATA_SECRET_S3_KEY".to_string())
+            ))
+        );
+        manifest.resolve_references(|_| Ok("p4ss".to_string())).unwrap();
+        assert_eq!(manifest.sources[0].parameters["password"], "p4ss");
+    }
+}
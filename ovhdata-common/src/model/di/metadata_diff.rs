@@ -0,0 +1,181 @@
+use std::fmt::{Display, Formatter};
+
+use descriptor::{Describe, Descriptor};
+use serde::{Deserialize, Serialize};
+
+use crate::model::di::source_metadata::TableMeta;
+
+// Relative change of the cardinality of a column reported by default
+pub const DEFAULT_CARDINALITY_THRESHOLD: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangeKind {
+    TableAdded,
+    TableRemoved,
+    ColumnAdded,
+    ColumnRemoved,
+    TypeChanged,
+    CardinalityShift,
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            ChangeKind::TableAdded => "table added",
+            ChangeKind::TableRemoved => "table removed",
+            ChangeKind::ColumnAdded => "column added",
+            ChangeKind::ColumnRemoved => "column removed",
+            ChangeKind::TypeChanged => "type changed",
+            ChangeKind::CardinalityShift => "cardinality shift",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+impl Describe for ChangeKind {
+    fn to_field(&self, _: &str) -> String {
+        self.to_string()
+    }
+}
+
+impl ChangeKind {
+    /// Removed tables or columns and type changes break the workflows reading them
+    pub fn is_breaking(&self) -> bool {
+        matches!(self, ChangeKind::TableRemoved | ChangeKind::ColumnRemoved | ChangeKind::TypeChanged)
+    }
+}
+
+/// Difference between two metadata extractions of a source
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Descriptor)]
+#[serde(rename_all = "camelCase")]
+#[descriptor(default_headers = ["change", "table", "column", "before", "after", "breaking"])]
+pub struct MetadataChange {
+    pub change: ChangeKind,
+    pub table: String,
+    pub column: Option<String>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub breaking: bool,
+}
+
+impl MetadataChange {
+    fn new(change: ChangeKind, table: &str, column: Option<&str>, before: Option<String>, after: Option<String>) -> Self {
+        Self {
+            change,
+            table: table.to_string(),
+            column: column.map(str::to_string),
+            before,
+            after,
+            breaking: change.is_breaking(),
+        }
+    }
+}
+
+/// Changes of the tables and columns from a metadata extraction to another one.
+/// A cardinality is reported when it changes by at least the threshold, relatively to the previous one.
+pub fn diff_metadata(before: &[TableMeta], after: &[TableMeta], cardinality_threshold: f64) -> Vec<MetadataChange> {
+    let name = |table: &TableMeta| table.table_name.clone().unwrap_or_default();
+    let mut changes = vec![];
+
+    for old_table in before {
+        let table_name = name(old_table);
+        let new_table = match after.iter().find(|table| name(table) == table_name) {
+            Some(new_table) => new_table,
+            None => {
+                changes.push(MetadataChange::new(ChangeKind::TableRemoved, &table_name, None, None, None));
+                continue;
+            }
+        };
+
+        for old_column in &old_table.metadata {
+            let column = Some(old_column.name.as_str());
+            let new_column = match new_table.metadata.iter().find(|new_column| new_column.name == old_column.name) {
+                Some(new_column) => new_column,
+                None => {
+                    let change = MetadataChange::new(ChangeKind::ColumnRemoved, &table_name, column, Some(old_column.type_name.clone()), None);
+                    changes.push(change);
+                    continue;
+                }
+            };
+
+            if !new_column.type_name.eq_ignore_ascii_case(&old_column.type_name) {
+                let (old_type, new_type) = (Some(old_column.type_name.clone()), Some(new_column.type_name.clone()));
+                changes.push(MetadataChange::new(ChangeKind::TypeChanged, &table_name, column, old_type, new_type));
+            }
+
+            // Nothing to compare a new cardinality to when there was none
+            let shift = (new_column.cardinality - old_column.cardinality).abs() as f64;
+            if old_column.cardinality > 0 && shift / old_column.cardinality as f64 >= cardinality_threshold {
+                let (old_cardinality, new_cardinality) = (Some(old_column.cardinality.to_string()), Some(new_column.cardinality.to_string()));
+                changes.push(MetadataChange::new(
+                    ChangeKind::CardinalityShift,
+                    &table_name,
+                    column,
+                    old_cardinality,
+                    new_cardinality,
+                ));
+            }
+        }
+
+        for new_column in &new_table.metadata {
+            if !old_table.metadata.iter().any(|old_column| old_column.name == new_column.name) {
+                let column = Some(new_column.name.as_str());
+                changes.push(MetadataChange::new(
+                    ChangeKind::ColumnAdded,
+                    &table_name,
+                    column,
+                    None,
+                    Some(new_column.type_name.clone()),
+                ));
+            }
+        }
+    }
+
+    for new_table in after {
+        let table_name = name(new_table);
+        if !before.iter().any(|table| name(table) == table_name) {
+            changes.push(MetadataChange::new(ChangeKind::TableAdded, &table_name, None, None, None));
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::di::fixtures::table;
+    use crate::model::di::metadata_diff::{diff_metadata, ChangeKind, DEFAULT_CARDINALITY_THRESHOLD};
+
+    #[test]
+    fn diff_tables_and_columns() {
+        let before = vec![
+            table("users", &[("id", "num", 100), ("name", "txt", 90), ("age", "num", 50)]),
+            table("orders", &[("id", "num", 10)]),
+        ];
+        let after = vec![
+            table("users", &[("id", "num", 120), ("name", "num", 90), ("email", "txt", 100)]),
+            table("products", &[("id", "num", 5)]),
+        ];
+
+        let changes = diff_metadata(&before, &after, DEFAULT_CARDINALITY_THRESHOLD)
+            .into_iter()
+            .map(|change| (change.change, change.table, change.column.unwrap_or_default(), change.breaking))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                (ChangeKind::TypeChanged, "users".to_string(), "name".to_string(), true),
+                (ChangeKind::ColumnRemoved, "users".to_string(), "age".to_string(), true),
+                (ChangeKind::ColumnAdded, "users".to_string(), "email".to_string(), false),
+                (ChangeKind::TableRemoved, "orders".to_string(), String::new(), true),
+                (ChangeKind::TableAdded, "products".to_string(), String::new(), false),
+            ]
+        );
+
+        let shifts = diff_metadata(&before, &after, 0.1);
+        assert!(shifts
+            .iter()
+            .any(|change| change.change == ChangeKind::CardinalityShift && change.before.as_deref() == Some("100") && !change.breaking));
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde_json::{json, Map, Value};
+use thiserror::Error as ThisError;
+
+use crate::model::di::source_metadata::TableMeta;
+
+/// JSON Schema, SQL and Avro types of some metadata types
+struct DefaultType {
+    names: &'static [&'static str],
+    json_schema: Value,
+    sql: &'static str,
+    avro: Value,
+}
+
+impl DefaultType {
+    fn new(names: &'static [&'static str], json_schema: Value, sql: &'static str, avro: Value) -> Self {
+        Self {
+            names,
+            json_schema,
+            sql,
+            avro,
+        }
+    }
+}
+
+/// Default types of the metadata types
+fn default_type_rows() -> Vec<DefaultType> {
+    vec![
+        DefaultType::new(
+            &["num", "number", "float", "double", "decimal"],
+            json!("number"),
+            "DOUBLE PRECISION",
+            json!("double"),
+        ),
+        DefaultType::new(&["int", "integer", "long", "bigint"], json!("integer"), "BIGINT", json!("long")),
+        fallback_type(),
+        DefaultType::new(&["bool", "boolean"], json!("boolean"), "BOOLEAN", json!("boolean")),
+        DefaultType::new(
+            &["date"],
+            json!({ "type": "string", "format": "date" }),
+            "DATE",
+            json!({ "type": "int", "logicalType": "date" }),
+        ),
+        DefaultType::new(
+            &["datetime", "timestamp"],
+            json!({ "type": "string", "format": "date-time" }),
+            "TIMESTAMP",
+            json!({ "type": "long", "logicalType": "timestamp-millis" }),
+        ),
+        DefaultType::new(&["binary", "bytes", "blob"], json!("string"), "BYTEA", json!("bytes")),
+    ]
+}
+
+/// Text types, also used for the unknown metadata types
+fn fallback_type() -> DefaultType {
+    DefaultType::new(&["txt", "text", "string", "varchar", "char"], json!("string"), "TEXT", json!("string"))
+}
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(ThisError, Debug, PartialEq)]
+pub enum SchemaError {
+    #[error("Unknown schema format {0}, expected jsonschema, sql-ddl or avro")]
+    UnknownFormat(String),
+    #[error("Invalid type {1} for the metadata type {0}: {2}")]
+    InvalidType(String, String, String),
+}
+
+/// Schema a table metadata can be exported to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaFormat {
+    JsonSchema,
+    SqlDdl,
+    Avro,
+}
+
+/// Type of each metadata type in a schema format, the fallback one being used for the unknown types
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeMap {
+    types: BTreeMap<String, Value>,
+    fallback: Value,
+}
+
+impl FromStr for SchemaFormat {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jsonschema" => Ok(SchemaFormat::JsonSchema),
+            "sql-ddl" => Ok(SchemaFormat::SqlDdl),
+            "avro" => Ok(SchemaFormat::Avro),
+            _ => Err(SchemaError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+impl SchemaFormat {
+    /// Extension of the schema files
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SchemaFormat::JsonSchema => "schema.json",
+            SchemaFormat::SqlDdl => "sql",
+            SchemaFormat::Avro => "avsc",
+        }
+    }
+
+    pub fn default_types(&self) -> TypeMap {
+        let pick = |row: &DefaultType| match self {
+            SchemaFormat::JsonSchema => row.json_schema.clone(),
+            SchemaFormat::SqlDdl => Value::String(row.sql.to_string()),
+            SchemaFormat::Avro => row.avro.clone(),
+        };
+        let types = default_type_rows()
+            .iter()
+            .flat_map(|row| row.names.iter().map(move |name| (name.to_string(), pick(row))))
+            .collect();
+
+        TypeMap {
+            types,
+            fallback: pick(&fallback_type()),
+        }
+    }
+
+    /// Types of the format, the given ones overriding the default ones
+    pub fn types(&self, overrides: &BTreeMap<String, String>) -> Result<TypeMap, SchemaError> {
+        let mut types = self.default_types();
+        for (name, type_name) in overrides {
+            let value = match type_name.trim_start().starts_with('{') && *self != SchemaFormat::SqlDdl {
+                true => serde_json::from_str::<Value>(type_name)
+                    .map_err(|error| SchemaError::InvalidType(name.clone(), type_name.clone(), error.to_string()))?,
+                false => Value::String(type_name.clone()),
+            };
+            types.types.insert(name.to_lowercase(), value);
+        }
+        Ok(types)
+    }
+
+    /// Schema of a single table
+    pub fn render(&self, table: &TableMeta, types: &TypeMap) -> String {
+        match self {
+            SchemaFormat::JsonSchema => to_json(&json_schema(table, types)),
+            SchemaFormat::SqlDdl => sql_ddl(table, types),
+            SchemaFormat::Avro => to_json(&avro_schema(table, types)),
+        }
+    }
+
+    /// Schemas of several tables in a single document
+    pub fn render_all(&self, tables: &[TableMeta], types: &TypeMap) -> String {
+        match self {
+            SchemaFormat::JsonSchema => to_json(&Value::Array(tables.iter().map(|table| json_schema(table, types)).collect())),
+            SchemaFormat::SqlDdl => tables.iter().map(|table| sql_ddl(table, types)).collect::<Vec<String>>().join("\n"),
+            SchemaFormat::Avro => to_json(&Value::Array(tables.iter().map(|table| avro_schema(table, types)).collect())),
+        }
+    }
+}
+
+impl TypeMap {
+    fn get(&self, type_name: &str) -> &Value {
+        self.types.get(&type_name.to_lowercase()).unwrap_or(&self.fallback)
+    }
+}
+
+fn table_name(table: &TableMeta) -> &str {
+    table.table_name.as_deref().unwrap_or_default()
+}
+
+fn json_schema(table: &TableMeta, types: &TypeMap) -> Value {
+    let properties = table
+        .metadata
+        .iter()
+        .map(|column| {
+            let property = match types.get(&column.type_name) {
+                Value::String(type_name) => json!({ "type": type_name }),
+                value => value.clone(),
+            };
+            (column.name.clone(), property)
+        })
+        .collect::<Map<String, Value>>();
+
+    json!({
+        "$schema": JSON_SCHEMA_DIALECT,
+        "title": table_name(table),
+        "type": "object",
+        "properties": properties,
+    })
+}
+
+fn sql_ddl(table: &TableMeta, types: &TypeMap) -> String {
+    let columns = table
+        .metadata
+        .iter()
+        .map(|column| {
+            let type_name = match types.get(&column.type_name) {
+                Value::String(type_name) => type_name.clone(),
+                value => value.to_string(),
+            };
+            format!("  {} {}", sql_identifier(&column.name), type_name)
+        })
+        .collect::<Vec<String>>();
+
+    format!("CREATE TABLE {} (\n{}\n);\n", sql_identifier(table_name(table)), columns.join(",\n"))
+}
+
+/// Columns are nullable, the metadata don't tell otherwise
+fn avro_schema(table: &TableMeta, types: &TypeMap) -> Value {
+    let fields = table
+        .metadata
+        .iter()
+        .map(|column| {
+            json!({
+                "name": avro_name(&column.name),
+                "type": ["null", types.get(&column.type_name)],
+                "default": null,
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "type": "record",
+        "name": avro_name(table_name(table)),
+        "fields": fields,
+    })
+}
+
+fn sql_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Avro names only have letters, digits and underscores, and don't start with a digit
+fn avro_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+    match name.chars().next() {
+        Some(first) if !first.is_ascii_digit() => name,
+        _ => format!("_{}", name),
+    }
+}
+
+fn to_json(value: &Value) -> String {
+    format!("{}\n", serde_json::to_string_pretty(value).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::{json, Value};
+
+    use crate::model::di::fixtures::table;
+    use crate::model::di::metadata_schema::SchemaFormat;
+    use crate::model::di::source_metadata::TableMeta;
+
+    fn create_table() -> TableMeta {
+        table(
+            "user-orders",
+            &[("id", "num", 10), ("created", "timestamp", 10), ("comment", "geometry", 10)],
+        )
+    }
+
+    #[test]
+    fn json_schema() {
+        let format = SchemaFormat::JsonSchema;
+        let schema = serde_json::from_str::<Value>(&format.render(&create_table(), &format.default_types())).unwrap();
+        assert_eq!(
+            schema["properties"],
+            json!({
+                "id": { "type": "number" },
+                "created": { "type": "string", "format": "date-time" },
+                "comment": { "type": "string" }
+            })
+        );
+    }
+
+    #[test]
+    fn sql_ddl() {
+        let format = SchemaFormat::SqlDdl;
+        let types = format
+            .types(&BTreeMap::from([("NUM".to_string(), "NUMERIC(10, 2)".to_string())]))
+            .unwrap();
+        assert_eq!(
+            format.render(&create_table(), &types),
+            "CREATE TABLE \"user-orders\" (\n  \"id\" NUMERIC(10, 2),\n  \"created\" TIMESTAMP,\n  \"comment\" TEXT\n);\n"
+        );
+    }
+
+    #[test]
+    fn avro_schema() {
+        let format = SchemaFormat::Avro;
+        let schema = serde_json::from_str::<Value>(&format.render(&create_table(), &format.default_types())).unwrap();
+        assert_eq!(schema["name"], "user_orders");
+        assert_eq!(
+            schema["fields"][1],
+            json!({ "name": "created", "type": ["null", { "type": "long", "logicalType": "timestamp-millis" }], "default": null })
+        );
+
+        assert!(format.types(&BTreeMap::from([("num".to_string(), "{ invalid".to_string())])).is_err());
+    }
+}
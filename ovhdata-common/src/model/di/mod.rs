@@ -1,7 +1,14 @@
 pub mod common;
 pub mod connector;
+pub mod connector_diff;
 pub mod destination;
+// Test values shared by the tests of the DI models
+#[cfg(test)]
+pub mod fixtures;
 pub mod job;
+pub mod manifest;
+pub mod metadata_diff;
+pub mod metadata_schema;
 pub mod source;
 pub mod source_metadata;
 pub mod workflow;
@@ -65,11 +65,13 @@ mod tests {
                     name: "parameter_1".to_string(),
                     value: "value_1".to_string(),
                     secret: false,
+                    origin: None,
                 },
                 Parameter {
                     name: "parameter_2".to_string(),
                     value: "value_2".to_string(),
                     secret: false,
+                    origin: None,
                 },
             ],
         }
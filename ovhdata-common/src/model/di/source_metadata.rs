@@ -37,11 +37,29 @@ pub struct Metadata {
     pub max: i64,
 }
 
+impl TableMeta {
+    /// The extraction of the table failed
+    pub fn has_failed(&self) -> bool {
+        let is_set = |value: &Option<String>| value.as_ref().is_some_and(|value| !value.is_empty());
+        is_set(&self.error) || is_set(&self.error_code) || matches!(self.status.to_uppercase().as_str(), "ERROR" | "FAILED")
+    }
+
+    /// The extraction of the table is over, successfully or not
+    pub fn is_ended(&self) -> bool {
+        self.has_failed() || self.ended_at.is_some() || matches!(self.status.to_uppercase().as_str(), "SUCCESS" | "DONE" | "COMPLETED")
+    }
+}
+
+/// The metadata of every table were extracted successfully
+pub fn is_extraction_complete(tables: &[TableMeta]) -> bool {
+    !tables.is_empty() && tables.iter().all(|table| table.is_ended() && !table.has_failed())
+}
+
 #[cfg(test)]
 mod tests {
     use descriptor::object_describe_to_string;
 
-    use crate::model::di::source_metadata::{Metadata, TableMeta};
+    use crate::model::di::source_metadata::{is_extraction_complete, Metadata, TableMeta};
     use crate::utils::date::datetime_micro;
 
     fn create_metadata() -> TableMeta {
@@ -161,4 +179,21 @@ Metadata:
 "#
         )
     }
+
+    #[test]
+    fn table_extraction_state() {
+        let mut table = create_metadata_empty();
+        assert!(!table.is_ended());
+
+        table.error_code = Some("CONNECTION_REFUSED".to_string());
+        assert!(table.is_ended() && table.has_failed());
+
+        let table = create_metadata();
+        assert!(table.is_ended() && !table.has_failed());
+
+        assert!(is_extraction_complete(&[create_metadata()]));
+        assert!(!is_extraction_complete(&[create_metadata(), create_metadata_empty()]));
+        assert!(!is_extraction_complete(&[create_metadata(), create_metadata_error()]));
+        assert!(!is_extraction_complete(&[]));
+    }
 }
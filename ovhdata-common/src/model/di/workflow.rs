@@ -5,11 +5,12 @@ use serde::{Deserialize, Serialize};
 use ovhdata_macros::PrintObjectCompletely;
 
 use crate::model::di::common::{EnsureSecret, ErrorDetails, Parameter};
+use crate::model::di::connector::{unknown_parameter, ParameterError, ParameterErrors};
 use crate::model::utils::{AgeEntity, DescribedDateTime};
 
 #[derive(Debug, Clone, Deserialize, Serialize, Descriptor, PrintObjectCompletely)]
 #[serde(rename_all = "camelCase")]
-#[descriptor(default_headers = ["name", "enabled", "id", "source_name", "destination_name", "schedule", "last_execution", "status"])]
+#[descriptor(default_headers = ["name", "enabled", "id", "source_name", "destination_name", "schedule", "runs", "last_execution", "status"])]
 #[descriptor(extra_fields = AgeEntity)]
 pub struct Workflow {
     pub id: String,
@@ -50,17 +51,39 @@ pub struct WorkflowPatch {
     pub enabled: Option<bool>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Descriptor, PrintObjectCompletely)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Descriptor, PrintObjectCompletely)]
 #[serde(rename_all = "camelCase")]
 pub struct JobPost {
-    // api mandatory parameter, not used yet
-    pub parameters: Vec<String>,
+    // Workflow parameters overridden for this job only, the body stays `{"parameters": []}` without override
+    pub parameters: Vec<Parameter>,
+    // Region running the job, the workflow one if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+impl Workflow {
+    /// Job of the workflow with some of its parameters overridden, they must be known by the workflow
+    pub fn job_post(&self, parameters: Vec<Parameter>, region: Option<String>) -> Result<JobPost, ParameterErrors> {
+        let known_names = self.parameters.iter().map(|parameter| parameter.name.as_str()).collect::<Vec<&str>>();
+        let errors = parameters
+            .iter()
+            .filter(|parameter| !known_names.contains(&parameter.name.as_str()))
+            .map(|parameter| unknown_parameter(&parameter.name, &known_names))
+            .collect::<Vec<ParameterError>>();
+
+        match errors.is_empty() {
+            true => Ok(JobPost { parameters, region }),
+            false => Err(ParameterErrors(errors)),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use descriptor::{object_describe_to_string, table_describe_to_string};
 
+    use crate::model::di::connector::{ParameterError, ParameterErrors};
+    use crate::model::di::fixtures::parameter;
     use crate::model::di::workflow::Workflow;
     use crate::utils::date::datetime_micro;
 
@@ -117,9 +140,32 @@ Error Details:       ~
         println!("{}", table);
         assert_eq!(
             table,
-            r#"NAME          ENABLED ID                                   SOURCE_NAME DESTINATION_NAME SCHEDULE  LAST_EXECUTION STATUS
-test-workflow true    d2671df0-6718-400e-a3d5-1242a49d464c source-name destination-name 5 4 * * * 34d            READY
+            r#"NAME          ENABLED ID                                   SOURCE_NAME DESTINATION_NAME SCHEDULE  RUNS               LAST_EXECUTION STATUS
+test-workflow true    d2671df0-6718-400e-a3d5-1242a49d464c source-name destination-name 5 4 * * * daily at 04:05 UTC 34d            READY
 "#
         )
     }
+
+    #[test]
+    fn job_post() {
+        let mut workflow = create_workflow();
+        workflow.parameters = vec![parameter("start_date", "2023-01-01"), parameter("end_date", "2023-02-01")];
+
+        // Same body as before the overrides were supported
+        let job = workflow.job_post(vec![], None).unwrap();
+        assert_eq!(serde_json::to_value(&job).unwrap(), serde_json::json!({ "parameters": [] }));
+
+        let job = workflow
+            .job_post(vec![parameter("start_date", "2022-01-01")], Some("SBG".to_string()))
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&job).unwrap(),
+            serde_json::json!({ "parameters": [{ "name": "start_date", "value": "2022-01-01" }], "region": "SBG" })
+        );
+
+        assert_eq!(
+            workflow.job_post(vec![parameter("end-date", "2022-02-01")], None).unwrap_err(),
+            ParameterErrors(vec![ParameterError::Unknown("end-date".to_string(), Some("end_date".to_string()))])
+        );
+    }
 }
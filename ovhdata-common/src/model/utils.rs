@@ -1,9 +1,11 @@
 use std::fmt::{Display, Formatter};
 
+use crate::utils::cron::CronSchedule;
 use crate::utils::date::{age, duration};
 use chrono::{DateTime, Utc};
 use descriptor::{Describe, Descriptor};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::model::di::destination::Destination;
 use crate::model::di::job::Job;
@@ -12,9 +14,37 @@ use crate::model::di::workflow::Workflow;
 
 pub const DEFAULT_PAGE_SIZE: u32 = 100;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Error returned by the OVH API
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct ResponseError {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+    /// Value of the X-Ovh-Queryid header, asked by the support
+    #[serde(skip)]
+    pub query_id: Option<String>,
+}
+
+impl ResponseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Display for ResponseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message.as_str())
+    }
 }
 
 //impl oauth2::ErrorResponse for ResponseError {}
@@ -56,6 +86,21 @@ impl<T> Page<T> {
     }
 }
 
+/// Pagination of a list call
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pagination {
+    /// Number of items asked for each page (server default if not set)
+    pub page_size: Option<u32>,
+    /// Maximum number of items to return (all items if not set)
+    pub limit: Option<u32>,
+}
+
+impl Pagination {
+    pub fn new(page_size: Option<u32>, limit: Option<u32>) -> Self {
+        Self { page_size, limit }
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PageMetadata {
@@ -81,6 +126,8 @@ pub struct AgeEntity {
     pub duration: Option<String>,
     #[descriptor(skip_description, rename_header = "LAST_EXECUTION")]
     pub last_execution: Option<String>,
+    #[descriptor(skip_description, rename_header = "RUNS")]
+    pub runs: Option<String>,
 }
 
 impl From<&Source> for AgeEntity {
@@ -90,6 +137,7 @@ impl From<&Source> for AgeEntity {
             last_update: Some(age(&s.last_update_date.unwrap_or(Utc::now()))),
             duration: None,
             last_execution: None,
+            runs: None,
         }
     }
 }
@@ -101,6 +149,7 @@ impl From<&Destination> for AgeEntity {
             last_update: Some(age(&d.last_update_date.unwrap_or(Utc::now()))),
             duration: None,
             last_execution: None,
+            runs: None,
         }
     }
 }
@@ -112,6 +161,7 @@ impl From<&Job> for AgeEntity {
             last_update: None,
             duration: Some(duration(&j.started_at, &j.ended_at)),
             last_execution: None,
+            runs: None,
         }
     }
 }
@@ -123,6 +173,11 @@ impl From<&Workflow> for AgeEntity {
             last_update: None,
             duration: None,
             last_execution: Some(age(&w.last_execution_date.unwrap_or(Utc::now()))),
+            // Unparsable schedules are shown as they are
+            runs: w.schedule.as_ref().map(|schedule| match CronSchedule::parse(schedule) {
+                Ok(schedule) => schedule.describe(),
+                Err(_) => schedule.clone(),
+            }),
         }
     }
 }
@@ -252,3 +307,21 @@ pub fn sort_job(mut list: Vec<Job>, order_by: &str, desc: bool) -> Vec<Job> {
     }
     list
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::model::utils::ResponseError;
+
+    #[test]
+    fn deserialize_response_error() {
+        let body = r#"{"class":"Client::Forbidden","message":"This call has not been granted","httpCode":"403 Forbidden","errorCode":"NOT_GRANTED_CALL","details":{}}"#;
+        let error = serde_json::from_str::<ResponseError>(body).unwrap();
+        assert_eq!(error.class.as_deref(), Some("Client::Forbidden"));
+        assert_eq!(error.error_code.as_deref(), Some("NOT_GRANTED_CALL"));
+        assert_eq!(error.to_string(), "This call has not been granted");
+
+        let error = serde_json::from_str::<ResponseError>(r#"{"message":"Query out of time"}"#).unwrap();
+        assert_eq!(error.message, "Query out of time");
+        assert_eq!(error.error_code, None);
+    }
+}
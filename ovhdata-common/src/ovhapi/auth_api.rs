@@ -4,7 +4,7 @@ use reqwest::Method;
 
 use crate::api::Result;
 use crate::api::EMPTY_BODY;
-use crate::model::auth::CredentialDetails;
+use crate::model::auth::{CredentialDetails, CredentialRequest, CredentialValidation};
 use crate::model::me::Me;
 use crate::ovhapi::OVHapiV6Client;
 
@@ -14,23 +14,31 @@ pub trait AuthApi {
     async fn me(&self) -> Result<Me>;
 
     async fn current_credential(&self) -> Result<CredentialDetails>;
+
+    /// Create a consumer key with the given access rules, to be validated by the user
+    async fn request_credential(&self, request: &CredentialRequest) -> Result<CredentialValidation>;
 }
 
 #[async_trait]
 impl AuthApi for OVHapiV6Client {
     async fn me(&self) -> Result<Me> {
-        let request = self
-            .build_request(Method::GET, &["auth", "details"], &[], &HeaderMap::new(), EMPTY_BODY)
+        let response = self
+            .execute(Method::GET, &["auth", "details"], &[], &HeaderMap::new(), EMPTY_BODY)
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn current_credential(&self) -> Result<CredentialDetails> {
-        let request = self
-            .build_request(Method::GET, &["auth", "currentCredential"], &[], &HeaderMap::new(), EMPTY_BODY)
+        let response = self
+            .execute(Method::GET, &["auth", "currentCredential"], &[], &HeaderMap::new(), EMPTY_BODY)
+            .await?;
+        response.parse().await
+    }
+
+    async fn request_credential(&self, request: &CredentialRequest) -> Result<CredentialValidation> {
+        let response = self
+            .execute_without_consumer_key(Method::POST, &["auth", "credential"], &[], &HeaderMap::new(), Some(request))
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 }
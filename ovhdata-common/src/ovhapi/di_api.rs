@@ -1,8 +1,11 @@
 use async_trait::async_trait;
+use futures::future;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
 use hyper::HeaderMap;
 use reqwest::Method;
 
-use crate::api::{Result, EMPTY_BODY};
+use crate::api::{collect_items, parse_value, Result, EMPTY_BODY};
 use crate::model::di::common::Status;
 use crate::model::di::connector::{DestinationConnector, SourceConnector};
 use crate::model::di::destination::{Destination, DestinationSpec};
@@ -10,6 +13,7 @@ use crate::model::di::job::Job;
 use crate::model::di::source::{Source, SourceSpec};
 use crate::model::di::source_metadata::TablesMeta;
 use crate::model::di::workflow::{JobPost, Workflow, WorkflowPatch, WorkflowSpec};
+use crate::model::utils::Pagination;
 use crate::ovhapi::OVHapiV6Client;
 
 #[async_trait]
@@ -25,7 +29,9 @@ pub trait DiApi {
     async fn di_destination_connector(&self, service_name: &str, id: &str) -> Result<DestinationConnector>;
 
     /// List all the sources for a service name and filter it with jsonpath filter
-    async fn di_sources(&self, service_name: &str, filter: Option<String>) -> Result<Vec<Source>>;
+    async fn di_sources(&self, service_name: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Source>>;
+    /// Stream all the sources for a service name, page by page
+    fn di_sources_stream(&self, service_name: &str, page_size: Option<u32>) -> BoxStream<'_, Result<Source>>;
     /// Get a source by ID
     async fn di_source(&self, service_name: &str, id: &str) -> Result<Source>;
     // Get a source status by ID
@@ -59,7 +65,9 @@ pub trait DiApi {
     async fn di_destination_test(&self, service_name: &str, id: &str) -> Result<Status>;
 
     /// List all the workflows for a service name and filter it with jsonpath filter
-    async fn di_workflows(&self, service_name: &str, filter: Option<String>) -> Result<Vec<Workflow>>;
+    async fn di_workflows(&self, service_name: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Workflow>>;
+    /// Stream all the workflows for a service name, page by page
+    fn di_workflows_stream(&self, service_name: &str, page_size: Option<u32>) -> BoxStream<'_, Result<Workflow>>;
     /// Get a workflow by ID
     async fn di_workflow(&self, service_name: &str, id: &str) -> Result<Workflow>;
     /// Create a new workflow for a service name
@@ -70,11 +78,13 @@ pub trait DiApi {
     async fn di_workflow_put(&self, service_name: &str, id: &str, spec: &WorkflowPatch) -> Result<Workflow>;
 
     /// List all the jobs for a service name and a workflow id then filter it with jsonpath filter
-    async fn di_jobs(&self, service_name: &str, workflow_id: &str, filter: Option<String>) -> Result<Vec<Job>>;
+    async fn di_jobs(&self, service_name: &str, workflow_id: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Job>>;
+    /// Stream all the jobs for a service name and a workflow id, page by page
+    fn di_jobs_stream(&self, service_name: &str, workflow_id: &str, page_size: Option<u32>) -> BoxStream<'_, Result<Job>>;
     /// Get a job by ID
     async fn di_job(&self, service_name: &str, id: &str, workflow_id: &str) -> Result<Job>;
     /// Start a new job for a workflow
-    async fn di_job_post(&self, service_name: &str, workflow_id: &str) -> Result<Job>;
+    async fn di_job_post(&self, service_name: &str, workflow_id: &str, job: &JobPost) -> Result<Job>;
     /// Stop a job for a workflow
     async fn di_job_delete(&self, service_name: &str, workflow_id: &str, id: &str) -> Result<()>;
 }
@@ -82,78 +92,39 @@ pub trait DiApi {
 #[async_trait]
 impl DiApi for OVHapiV6Client {
     async fn di_source_connectors(&self, service_name: &str) -> Result<Vec<SourceConnector>> {
-        let request = self
-            .build_request(
-                Method::GET,
-                &["cloud", "project", service_name, "dataIntegration", "sourceConnectors"],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        let response = request.send(&self.client, &[]).await?;
-        response.parse().await
+        self.get_cached(&["cloud", "project", service_name, "dataIntegration", "sourceConnectors"])
+            .await
     }
 
     async fn di_source_connector(&self, service_name: &str, id: &str) -> Result<SourceConnector> {
-        let request = self
-            .build_request(
-                Method::GET,
-                &["cloud", "project", service_name, "dataIntegration", "sourceConnectors", id],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        let response = request.send(&self.client, &[]).await?;
-        response.parse().await
+        self.get_cached(&["cloud", "project", service_name, "dataIntegration", "sourceConnectors", id])
+            .await
     }
 
     async fn di_destination_connectors(&self, service_name: &str) -> Result<Vec<DestinationConnector>> {
-        let request = self
-            .build_request(
-                Method::GET,
-                &["cloud", "project", service_name, "dataIntegration", "destinationConnectors"],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        let response = request.send(&self.client, &[]).await?;
-        response.parse().await
+        self.get_cached(&["cloud", "project", service_name, "dataIntegration", "destinationConnectors"])
+            .await
     }
 
     async fn di_destination_connector(&self, service_name: &str, id: &str) -> Result<DestinationConnector> {
-        let request = self
-            .build_request(
-                Method::GET,
-                &["cloud", "project", service_name, "dataIntegration", "destinationConnectors", id],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        let response = request.send(&self.client, &[]).await?;
-        response.parse().await
+        self.get_cached(&["cloud", "project", service_name, "dataIntegration", "destinationConnectors", id])
+            .await
     }
 
-    async fn di_sources(&self, service_name: &str, filter: Option<String>) -> Result<Vec<Source>> {
-        let request = self
-            .build_request(
-                Method::GET,
-                &["cloud", "project", service_name, "dataIntegration", "sources"],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        let response = request.send(&self.client, &[]).await?;
-        response.parse_with_filter(filter).await
+    async fn di_sources(&self, service_name: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Source>> {
+        let stream = self.stream_items(&["cloud", "project", service_name, "dataIntegration", "sources"], pagination.page_size);
+        collect_items(stream, filter, pagination.limit).await
+    }
+
+    fn di_sources_stream(&self, service_name: &str, page_size: Option<u32>) -> BoxStream<'_, Result<Source>> {
+        self.stream_items(&["cloud", "project", service_name, "dataIntegration", "sources"], page_size)
+            .and_then(|item| future::ready(parse_value(item)))
+            .boxed()
     }
 
     async fn di_source(&self, service_name: &str, id: &str) -> Result<Source> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::GET,
                 &["cloud", "project", service_name, "dataIntegration", "sources", id],
                 &[],
@@ -161,13 +132,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_source_test(&self, service_name: &str, id: &str) -> Result<Status> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::POST,
                 &["cloud", "project", service_name, "dataIntegration", "sources", id, "connection"],
                 &[],
@@ -175,13 +145,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_source_status(&self, service_name: &str, id: &str) -> Result<Status> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::GET,
                 &["cloud", "project", service_name, "dataIntegration", "sources", id, "connection"],
                 &[],
@@ -189,13 +158,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_source_metadata(&self, service_name: &str, id: &str) -> Result<TablesMeta> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::GET,
                 &["cloud", "project", service_name, "dataIntegration", "sources", id, "metadata"],
                 &[],
@@ -203,13 +171,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_source_metadata_post(&self, service_name: &str, id: &str) -> Result<TablesMeta> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::POST,
                 &["cloud", "project", service_name, "dataIntegration", "sources", id, "metadata"],
                 &[],
@@ -217,27 +184,24 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_source_delete(&self, service_name: &str, id: &str) -> Result<()> {
-        let request = self
-            .build_request(
-                Method::DELETE,
-                &["cloud", "project", service_name, "dataIntegration", "sources", id],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        request.send(&self.client, &[]).await?;
+        self.execute(
+            Method::DELETE,
+            &["cloud", "project", service_name, "dataIntegration", "sources", id],
+            &[],
+            &HeaderMap::new(),
+            EMPTY_BODY,
+        )
+        .await?;
         Ok(())
     }
 
     async fn di_source_post(&self, service_name: &str, spec: &SourceSpec) -> Result<Source> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::POST,
                 &["cloud", "project", service_name, "dataIntegration", "sources"],
                 &[],
@@ -245,13 +209,12 @@ impl DiApi for OVHapiV6Client {
                 Some(spec),
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_source_update(&self, service_name: &str, id: &str, spec: &SourceSpec) -> Result<Source> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::PUT,
                 &["cloud", "project", service_name, "dataIntegration", "sources", id],
                 &[],
@@ -259,13 +222,12 @@ impl DiApi for OVHapiV6Client {
                 Some(spec),
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_destinations(&self, service_name: &str, filter: Option<String>) -> Result<Vec<Destination>> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::GET,
                 &["cloud", "project", service_name, "dataIntegration", "destinations"],
                 &[],
@@ -273,13 +235,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse_with_filter(filter).await
     }
 
     async fn di_destination(&self, service_name: &str, id: &str) -> Result<Destination> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::GET,
                 &["cloud", "project", service_name, "dataIntegration", "destinations", id],
                 &[],
@@ -287,13 +248,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_destination_test(&self, service_name: &str, id: &str) -> Result<Status> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::POST,
                 &["cloud", "project", service_name, "dataIntegration", "destinations", id, "connection"],
                 &[],
@@ -301,13 +261,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_destination_status(&self, service_name: &str, id: &str) -> Result<Status> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::GET,
                 &["cloud", "project", service_name, "dataIntegration", "destinations", id, "connection"],
                 &[],
@@ -315,13 +274,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_destination_post(&self, service_name: &str, spec: &DestinationSpec) -> Result<Destination> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::POST,
                 &["cloud", "project", service_name, "dataIntegration", "destinations"],
                 &[],
@@ -329,27 +287,24 @@ impl DiApi for OVHapiV6Client {
                 Some(spec),
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_destination_delete(&self, service_name: &str, id: &str) -> Result<()> {
-        let request = self
-            .build_request(
-                Method::DELETE,
-                &["cloud", "project", service_name, "dataIntegration", "destinations", id],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        request.send(&self.client, &[]).await?;
+        self.execute(
+            Method::DELETE,
+            &["cloud", "project", service_name, "dataIntegration", "destinations", id],
+            &[],
+            &HeaderMap::new(),
+            EMPTY_BODY,
+        )
+        .await?;
         Ok(())
     }
 
     async fn di_destination_update(&self, service_name: &str, id: &str, spec: &DestinationSpec) -> Result<Destination> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::PUT,
                 &["cloud", "project", service_name, "dataIntegration", "destinations", id],
                 &[],
@@ -357,27 +312,23 @@ impl DiApi for OVHapiV6Client {
                 Some(spec),
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
-    async fn di_workflows(&self, service_name: &str, filter: Option<String>) -> Result<Vec<Workflow>> {
-        let request = self
-            .build_request(
-                Method::GET,
-                &["cloud", "project", service_name, "dataIntegration", "workflows"],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        let response = request.send(&self.client, &[]).await?;
-        response.parse_with_filter(filter).await
+    async fn di_workflows(&self, service_name: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Workflow>> {
+        let stream = self.stream_items(&["cloud", "project", service_name, "dataIntegration", "workflows"], pagination.page_size);
+        collect_items(stream, filter, pagination.limit).await
+    }
+
+    fn di_workflows_stream(&self, service_name: &str, page_size: Option<u32>) -> BoxStream<'_, Result<Workflow>> {
+        self.stream_items(&["cloud", "project", service_name, "dataIntegration", "workflows"], page_size)
+            .and_then(|item| future::ready(parse_value(item)))
+            .boxed()
     }
 
     async fn di_workflow(&self, service_name: &str, id: &str) -> Result<Workflow> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::GET,
                 &["cloud", "project", service_name, "dataIntegration", "workflows", id],
                 &[],
@@ -385,13 +336,12 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_workflow_post(&self, service_name: &str, spec: &WorkflowSpec) -> Result<Workflow> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::POST,
                 &["cloud", "project", service_name, "dataIntegration", "workflows"],
                 &[],
@@ -399,27 +349,24 @@ impl DiApi for OVHapiV6Client {
                 Some(spec),
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_workflow_delete(&self, service_name: &str, id: &str) -> Result<()> {
-        let request = self
-            .build_request(
-                Method::DELETE,
-                &["cloud", "project", service_name, "dataIntegration", "workflows", id],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        request.send(&self.client, &[]).await?;
+        self.execute(
+            Method::DELETE,
+            &["cloud", "project", service_name, "dataIntegration", "workflows", id],
+            &[],
+            &HeaderMap::new(),
+            EMPTY_BODY,
+        )
+        .await?;
         Ok(())
     }
 
     async fn di_workflow_put(&self, service_name: &str, id: &str, spec: &WorkflowPatch) -> Result<Workflow> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::PUT,
                 &["cloud", "project", service_name, "dataIntegration", "workflows", id],
                 &[],
@@ -427,27 +374,29 @@ impl DiApi for OVHapiV6Client {
                 Some(spec),
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
-    async fn di_jobs(&self, service_name: &str, workflow_id: &str, filter: Option<String>) -> Result<Vec<Job>> {
-        let request = self
-            .build_request(
-                Method::GET,
-                &["cloud", "project", service_name, "dataIntegration", "workflows", workflow_id, "jobs"],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        let response = request.send(&self.client, &[]).await?;
-        response.parse_with_filter(filter).await
+    async fn di_jobs(&self, service_name: &str, workflow_id: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Job>> {
+        let stream = self.stream_items(
+            &["cloud", "project", service_name, "dataIntegration", "workflows", workflow_id, "jobs"],
+            pagination.page_size,
+        );
+        collect_items(stream, filter, pagination.limit).await
+    }
+
+    fn di_jobs_stream(&self, service_name: &str, workflow_id: &str, page_size: Option<u32>) -> BoxStream<'_, Result<Job>> {
+        self.stream_items(
+            &["cloud", "project", service_name, "dataIntegration", "workflows", workflow_id, "jobs"],
+            page_size,
+        )
+        .and_then(|item| future::ready(parse_value(item)))
+        .boxed()
     }
 
     async fn di_job(&self, service_name: &str, workflow_id: &str, id: &str) -> Result<Job> {
-        let request = self
-            .build_request(
+        let response = self
+            .execute(
                 Method::GET,
                 &["cloud", "project", service_name, "dataIntegration", "workflows", workflow_id, "jobs", id],
                 &[],
@@ -455,36 +404,31 @@ impl DiApi for OVHapiV6Client {
                 EMPTY_BODY,
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
-    async fn di_job_post(&self, service_name: &str, workflow_id: &str) -> Result<Job> {
-        let job = JobPost { parameters: vec![] };
-        let request = self
-            .build_request(
+    async fn di_job_post(&self, service_name: &str, workflow_id: &str, job: &JobPost) -> Result<Job> {
+        let response = self
+            .execute(
                 Method::POST,
                 &["cloud", "project", service_name, "dataIntegration", "workflows", workflow_id, "jobs"],
                 &[],
                 &HeaderMap::new(),
-                Some(&job),
+                Some(job),
             )
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn di_job_delete(&self, service_name: &str, workflow_id: &str, id: &str) -> Result<()> {
-        let request = self
-            .build_request(
-                Method::DELETE,
-                &["cloud", "project", service_name, "dataIntegration", "workflows", workflow_id, "jobs", id],
-                &[],
-                &HeaderMap::new(),
-                EMPTY_BODY,
-            )
-            .await?;
-        request.send(&self.client, &[]).await?;
+        self.execute(
+            Method::DELETE,
+            &["cloud", "project", service_name, "dataIntegration", "workflows", workflow_id, "jobs", id],
+            &[],
+            &HeaderMap::new(),
+            EMPTY_BODY,
+        )
+        .await?;
         Ok(())
     }
 }
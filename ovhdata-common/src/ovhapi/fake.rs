@@ -0,0 +1,706 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::api::{collect_items, Error, Result};
+use crate::model::auth::{CredentialDetails, CredentialRequest, CredentialValidation};
+use crate::model::di::common::Status;
+use crate::model::di::connector::{DestinationConnector, SourceConnector};
+use crate::model::di::destination::{Destination, DestinationSpec};
+use crate::model::di::job::Job;
+use crate::model::di::source::{Source, SourceSpec};
+use crate::model::di::source_metadata::{TableMeta, TablesMeta};
+use crate::model::di::workflow::{JobPost, Workflow, WorkflowPatch, WorkflowSpec};
+use crate::model::me::Me;
+use crate::model::project::Project;
+use crate::model::utils::Pagination;
+use crate::ovhapi::{AuthApi, DiApi, ProjectApi};
+use crate::BUG;
+
+pub const FAKE_CONNECTION_STATUS: &str = "CONNECTION_SUCCEED";
+pub const FAKE_WORKFLOW_STATUS: &str = "READY";
+// Connectors added by `with_sample_connectors`
+pub const FAKE_SOURCE_CONNECTOR_ID: &str = "source-connector";
+pub const FAKE_DESTINATION_CONNECTOR_ID: &str = "destination-connector";
+
+/// In memory implementation of the OVH API, useful to run commands without any network
+/// The state is shared between the clones of the client and between every service name
+/// Jobs move one step forward (PENDING, RUNNING then DONE) each time they are read
+#[derive(Debug, Clone, Default)]
+pub struct FakeOVHapiClient {
+    state: Arc<Mutex<FakeState>>,
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    me: Option<Me>,
+    credential: Option<CredentialDetails>,
+    projects: Vec<Project>,
+    source_connectors: Vec<SourceConnector>,
+    destination_connectors: Vec<DestinationConnector>,
+    sources: Vec<Source>,
+    destinations: Vec<Destination>,
+    workflows: Vec<Workflow>,
+    jobs: HashMap<String, Vec<Job>>,
+    source_metadata: HashMap<String, TablesMeta>,
+    extracted_tables: TablesMeta,
+    job_final_status: Option<String>,
+    // Seconds the server clock is ahead of the local one
+    clock_offset_secs: i64,
+}
+
+impl FakeOVHapiClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// User returned by the `me` call
+    pub fn with_me(self, me: Me) -> Self {
+        self.state().me = Some(me);
+        self
+    }
+
+    /// Credential returned by the `current_credential` call
+    pub fn with_credential(self, credential: CredentialDetails) -> Self {
+        self.state().credential = Some(credential);
+        self
+    }
+
+    /// Add a public cloud project
+    pub fn with_project(self, project: Project) -> Self {
+        self.state().projects.push(project);
+        self
+    }
+
+    /// Add an available source connector
+    pub fn with_source_connector(self, connector: SourceConnector) -> Self {
+        self.state().source_connectors.push(connector);
+        self
+    }
+
+    /// Add an available destination connector
+    pub fn with_destination_connector(self, connector: DestinationConnector) -> Self {
+        self.state().destination_connectors.push(connector);
+        self
+    }
+
+    /// Add a Postgres source connector and an Iceberg destination connector, without parameters
+    pub fn with_sample_connectors(self) -> Self {
+        self.with_source_connector(SourceConnector {
+            id: FAKE_SOURCE_CONNECTOR_ID.to_string(),
+            name: "Postgres".to_string(),
+            version: "1.0".to_string(),
+            description: "Postgres source".to_string(),
+            documentation_url: None,
+            parameters: vec![],
+        })
+        .with_destination_connector(DestinationConnector {
+            id: FAKE_DESTINATION_CONNECTOR_ID.to_string(),
+            name: "Iceberg".to_string(),
+            version: "1.0".to_string(),
+            description: "Iceberg destination".to_string(),
+            documentation_url: None,
+            parameters: vec![],
+        })
+    }
+
+    /// Tables returned by every source metadata extraction
+    pub fn with_extracted_tables(self, tables: TablesMeta) -> Self {
+        self.state().extracted_tables = tables;
+        self
+    }
+
+    /// Server clock ahead of the local one by the given seconds (behind if negative), for the metadata extraction dates
+    pub fn with_clock_offset(self, seconds: i64) -> Self {
+        self.state().clock_offset_secs = seconds;
+        self
+    }
+
+    /// Status of the jobs once they are over (DONE by default)
+    pub fn with_job_final_status(self, status: impl Into<String>) -> Self {
+        self.state().job_final_status = Some(status.into());
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().expect(BUG)
+    }
+}
+
+impl FakeState {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now() + Duration::seconds(self.clock_offset_secs)
+    }
+
+    fn source_mut(&mut self, id: &str) -> Result<&mut Source> {
+        self.sources.iter_mut().find(|source| source.id == id).ok_or(not_found("source", id))
+    }
+
+    fn destination_mut(&mut self, id: &str) -> Result<&mut Destination> {
+        self.destinations
+            .iter_mut()
+            .find(|destination| destination.id == id)
+            .ok_or(not_found("destination", id))
+    }
+
+    fn workflow_mut(&mut self, id: &str) -> Result<&mut Workflow> {
+        self.workflows
+            .iter_mut()
+            .find(|workflow| workflow.id == id)
+            .ok_or(not_found("workflow", id))
+    }
+
+    fn jobs_mut(&mut self, workflow_id: &str) -> Result<&mut Vec<Job>> {
+        self.workflow_mut(workflow_id)?;
+        Ok(self.jobs.entry(workflow_id.to_string()).or_default())
+    }
+
+    /// Move a job to its next status
+    fn advance_job(job: &mut Job, final_status: &str) {
+        match job.status.as_str() {
+            "PENDING" => {
+                job.status = "RUNNING".to_string();
+                job.started_at = Some(Utc::now());
+            }
+            "RUNNING" => {
+                job.status = final_status.to_string();
+                job.ended_at = Some(Utc::now());
+            }
+            "INTERRUPTING" => {
+                job.status = "INTERRUPTED".to_string();
+                job.ended_at = Some(Utc::now());
+            }
+            _ => {}
+        }
+    }
+
+    fn advance_jobs(&mut self, workflow_id: &str) -> Result<Vec<Job>> {
+        let final_status = self.job_final_status.clone().unwrap_or("DONE".to_string());
+        let jobs = self.jobs_mut(workflow_id)?;
+        jobs.iter_mut().for_each(|job| Self::advance_job(job, &final_status));
+        Ok(jobs.clone())
+    }
+}
+
+fn not_found(kind: &str, id: &str) -> Error {
+    Error::response(StatusCode::NOT_FOUND, format!("The {} {} does not exist", kind, id))
+}
+
+fn conflict(message: String) -> Error {
+    Error::response(StatusCode::CONFLICT, message)
+}
+
+/// Filter and paginate a list the same way the API client does
+async fn list<T>(items: Vec<T>, filter: Option<String>, pagination: &Pagination) -> Result<Vec<T>>
+where
+    T: Serialize + serde::de::DeserializeOwned,
+{
+    let values = items
+        .iter()
+        .map(|item| serde_json::to_value(item).map_err(|e| Error::DeserializeContent(e, String::default())))
+        .collect::<Result<Vec<Value>>>()?;
+    collect_items(stream::iter(values.into_iter().map(Ok)).boxed(), filter, pagination.limit).await
+}
+
+#[async_trait]
+impl AuthApi for FakeOVHapiClient {
+    async fn me(&self) -> Result<Me> {
+        self.state().me.clone().ok_or(Error::response(StatusCode::UNAUTHORIZED, "Not logged in"))
+    }
+
+    async fn current_credential(&self) -> Result<CredentialDetails> {
+        self.state()
+            .credential
+            .clone()
+            .ok_or(Error::response(StatusCode::UNAUTHORIZED, "Not logged in"))
+    }
+
+    // The consumer key is validated right away, with the requested access rules
+    async fn request_credential(&self, request: &CredentialRequest) -> Result<CredentialValidation> {
+        let mut state = self.state();
+        let credential_id = state.credential.as_ref().map(|credential| credential.credential_id + 1).unwrap_or(1);
+        state.credential = Some(CredentialDetails {
+            allowed_ips: vec![],
+            application_id: 1,
+            creation: Utc::now().to_rfc3339(),
+            credential_id,
+            expiration: None,
+            last_use: None,
+            ovh_support: false,
+            status: "validated".to_string(),
+            rules: request.access_rules.clone(),
+        });
+        Ok(CredentialValidation {
+            consumer_key: format!("fake-consumer-key-{}", credential_id),
+            state: "pendingValidation".to_string(),
+            validation_url: format!("https://fake.ovh/auth/?credentialToken={}", credential_id),
+        })
+    }
+}
+
+#[async_trait]
+impl ProjectApi for FakeOVHapiClient {
+    async fn projects(&self) -> Result<Vec<Project>> {
+        Ok(self.state().projects.clone())
+    }
+
+    async fn project(&self, service_name: &str) -> Result<Project> {
+        self.state()
+            .projects
+            .iter()
+            .find(|project| project.project_id == service_name)
+            .cloned()
+            .ok_or(not_found("project", service_name))
+    }
+
+    async fn project_list(&self) -> Result<Vec<String>> {
+        Ok(self.state().projects.iter().map(|project| project.project_id.clone()).collect())
+    }
+}
+
+#[async_trait]
+impl DiApi for FakeOVHapiClient {
+    async fn di_source_connectors(&self, _service_name: &str) -> Result<Vec<SourceConnector>> {
+        Ok(self.state().source_connectors.clone())
+    }
+
+    async fn di_source_connector(&self, _service_name: &str, id: &str) -> Result<SourceConnector> {
+        self.state()
+            .source_connectors
+            .iter()
+            .find(|connector| connector.id == id)
+            .cloned()
+            .ok_or(not_found("source connector", id))
+    }
+
+    async fn di_destination_connectors(&self, _service_name: &str) -> Result<Vec<DestinationConnector>> {
+        Ok(self.state().destination_connectors.clone())
+    }
+
+    async fn di_destination_connector(&self, _service_name: &str, id: &str) -> Result<DestinationConnector> {
+        self.state()
+            .destination_connectors
+            .iter()
+            .find(|connector| connector.id == id)
+            .cloned()
+            .ok_or(not_found("destination connector", id))
+    }
+
+    async fn di_sources(&self, _service_name: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Source>> {
+        let sources = self.state().sources.clone();
+        list(sources, filter, pagination).await
+    }
+
+    fn di_sources_stream(&self, _service_name: &str, _page_size: Option<u32>) -> BoxStream<'_, Result<Source>> {
+        stream::iter(self.state().sources.clone().into_iter().map(Ok)).boxed()
+    }
+
+    async fn di_source(&self, _service_name: &str, id: &str) -> Result<Source> {
+        Ok(self.state().source_mut(id)?.clone())
+    }
+
+    async fn di_source_status(&self, _service_name: &str, id: &str) -> Result<Status> {
+        let mut state = self.state();
+        let source = state.source_mut(id)?;
+        Ok(Status {
+            status: source.status.clone(),
+            date: source.last_update_date,
+        })
+    }
+
+    async fn di_source_metadata(&self, _service_name: &str, id: &str) -> Result<TablesMeta> {
+        let mut state = self.state();
+        state.source_mut(id)?;
+
+        let extracted_tables = state.extracted_tables.clone();
+        let now = state.now();
+        let tables = state.source_metadata.entry(id.to_string()).or_default();
+        let processing = tables.iter().any(|table| table.status == "PROCESSING");
+        if processing {
+            let started_at = tables.first().and_then(|table| table.started_at);
+            *tables = extracted_tables
+                .into_iter()
+                .map(|table| TableMeta {
+                    status: "SUCCESS".to_string(),
+                    started_at,
+                    ended_at: Some(now),
+                    ..table
+                })
+                .collect();
+        }
+        Ok(tables.clone())
+    }
+
+    async fn di_source_metadata_post(&self, _service_name: &str, id: &str) -> Result<TablesMeta> {
+        let mut state = self.state();
+        state.source_mut(id)?;
+
+        let tables = vec![TableMeta {
+            table_name: None,
+            status: "PROCESSING".to_string(),
+            error: None,
+            error_code: None,
+            started_at: Some(state.now()),
+            ended_at: None,
+            metadata: vec![],
+        }];
+        state.source_metadata.insert(id.to_string(), tables.clone());
+        Ok(tables)
+    }
+
+    async fn di_source_delete(&self, _service_name: &str, id: &str) -> Result<()> {
+        let mut state = self.state();
+        state.source_mut(id)?;
+        if state.workflows.iter().any(|workflow| workflow.source_id.as_deref() == Some(id)) {
+            return Err(conflict(format!("The source {} is used by a workflow", id)));
+        }
+        state.sources.retain(|source| source.id != id);
+        state.source_metadata.remove(id);
+        Ok(())
+    }
+
+    async fn di_source_post(&self, service_name: &str, spec: &SourceSpec) -> Result<Source> {
+        let connector_id = spec.connector_id.clone().unwrap_or_default();
+        self.di_source_connector(service_name, &connector_id).await?;
+
+        let mut state = self.state();
+        if state.sources.iter().any(|source| source.name == spec.name) {
+            return Err(conflict(format!("A source named {} already exists", spec.name)));
+        }
+        let source = Source {
+            id: Uuid::new_v4().to_string(),
+            name: spec.name.clone(),
+            status: FAKE_CONNECTION_STATUS.to_string(),
+            creation_date: Utc::now(),
+            last_update_date: Some(Utc::now()),
+            connector_id,
+            parameters: spec.parameters.clone(),
+        };
+        state.sources.push(source.clone());
+        Ok(source)
+    }
+
+    async fn di_source_update(&self, _service_name: &str, id: &str, spec: &SourceSpec) -> Result<Source> {
+        let mut state = self.state();
+        let source = state.source_mut(id)?;
+        source.name = spec.name.clone();
+        source.parameters = spec.parameters.clone();
+        source.last_update_date = Some(Utc::now());
+        Ok(source.clone())
+    }
+
+    async fn di_source_test(&self, _service_name: &str, id: &str) -> Result<Status> {
+        let mut state = self.state();
+        let source = state.source_mut(id)?;
+        source.status = FAKE_CONNECTION_STATUS.to_string();
+        source.last_update_date = Some(Utc::now());
+        Ok(Status {
+            status: source.status.clone(),
+            date: source.last_update_date,
+        })
+    }
+
+    async fn di_destinations(&self, _service_name: &str, filter: Option<String>) -> Result<Vec<Destination>> {
+        let destinations = self.state().destinations.clone();
+        list(destinations, filter, &Pagination::default()).await
+    }
+
+    async fn di_destination(&self, _service_name: &str, id: &str) -> Result<Destination> {
+        Ok(self.state().destination_mut(id)?.clone())
+    }
+
+    async fn di_destination_status(&self, _service_name: &str, id: &str) -> Result<Status> {
+        let mut state = self.state();
+        let destination = state.destination_mut(id)?;
+        Ok(Status {
+            status: destination.status.clone(),
+            date: destination.last_update_date,
+        })
+    }
+
+    async fn di_destination_post(&self, service_name: &str, spec: &DestinationSpec) -> Result<Destination> {
+        let connector_id = spec.connector_id.clone().unwrap_or_default();
+        self.di_destination_connector(service_name, &connector_id).await?;
+
+        let mut state = self.state();
+        if state.destinations.iter().any(|destination| destination.name == spec.name) {
+            return Err(conflict(format!("A destination named {} already exists", spec.name)));
+        }
+        let destination = Destination {
+            id: Uuid::new_v4().to_string(),
+            name: spec.name.clone(),
+            status: FAKE_CONNECTION_STATUS.to_string(),
+            creation_date: Utc::now(),
+            last_update_date: Some(Utc::now()),
+            connector_id,
+            parameters: spec.parameters.clone(),
+        };
+        state.destinations.push(destination.clone());
+        Ok(destination)
+    }
+
+    async fn di_destination_delete(&self, _service_name: &str, id: &str) -> Result<()> {
+        let mut state = self.state();
+        state.destination_mut(id)?;
+        if state.workflows.iter().any(|workflow| workflow.destination_id.as_deref() == Some(id)) {
+            return Err(conflict(format!("The destination {} is used by a workflow", id)));
+        }
+        state.destinations.retain(|destination| destination.id != id);
+        Ok(())
+    }
+
+    async fn di_destination_update(&self, _service_name: &str, id: &str, spec: &DestinationSpec) -> Result<Destination> {
+        let mut state = self.state();
+        let destination = state.destination_mut(id)?;
+        destination.name = spec.name.clone();
+        destination.parameters = spec.parameters.clone();
+        destination.last_update_date = Some(Utc::now());
+        Ok(destination.clone())
+    }
+
+    async fn di_destination_test(&self, _service_name: &str, id: &str) -> Result<Status> {
+        let mut state = self.state();
+        let destination = state.destination_mut(id)?;
+        destination.status = FAKE_CONNECTION_STATUS.to_string();
+        destination.last_update_date = Some(Utc::now());
+        Ok(Status {
+            status: destination.status.clone(),
+            date: destination.last_update_date,
+        })
+    }
+
+    async fn di_workflows(&self, _service_name: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Workflow>> {
+        let workflows = self.state().workflows.clone();
+        list(workflows, filter, pagination).await
+    }
+
+    fn di_workflows_stream(&self, _service_name: &str, _page_size: Option<u32>) -> BoxStream<'_, Result<Workflow>> {
+        stream::iter(self.state().workflows.clone().into_iter().map(Ok)).boxed()
+    }
+
+    async fn di_workflow(&self, _service_name: &str, id: &str) -> Result<Workflow> {
+        Ok(self.state().workflow_mut(id)?.clone())
+    }
+
+    async fn di_workflow_post(&self, _service_name: &str, spec: &WorkflowSpec) -> Result<Workflow> {
+        let mut state = self.state();
+        let source_name = state.source_mut(&spec.source_id)?.name.clone();
+        let destination_name = state.destination_mut(&spec.destination_id)?.name.clone();
+        if state.workflows.iter().any(|workflow| workflow.name == spec.name) {
+            return Err(conflict(format!("A workflow named {} already exists", spec.name)));
+        }
+
+        let workflow = Workflow {
+            id: Uuid::new_v4().to_string(),
+            name: spec.name.clone(),
+            description: spec.description.clone(),
+            region: spec.region.clone(),
+            source_id: Some(spec.source_id.clone()),
+            source_name: Some(source_name),
+            destination_id: Some(spec.destination_id.clone()),
+            destination_name: Some(destination_name),
+            parameters: vec![],
+            last_execution_date: None,
+            schedule: spec.schedule.clone(),
+            enabled: spec.enabled,
+            status: Some(FAKE_WORKFLOW_STATUS.to_string()),
+            error_details: None,
+        };
+        state.workflows.push(workflow.clone());
+        Ok(workflow)
+    }
+
+    async fn di_workflow_delete(&self, _service_name: &str, id: &str) -> Result<()> {
+        let mut state = self.state();
+        state.workflow_mut(id)?;
+        state.workflows.retain(|workflow| workflow.id != id);
+        state.jobs.remove(id);
+        Ok(())
+    }
+
+    async fn di_workflow_put(&self, _service_name: &str, id: &str, spec: &WorkflowPatch) -> Result<Workflow> {
+        let mut state = self.state();
+        let workflow = state.workflow_mut(id)?;
+        if let Some(name) = &spec.name {
+            workflow.name = name.clone();
+        }
+        if let Some(description) = &spec.description {
+            workflow.description = Some(description.clone());
+        }
+        if let Some(schedule) = &spec.schedule {
+            workflow.schedule = Some(schedule.clone());
+        }
+        if let Some(enabled) = spec.enabled {
+            workflow.enabled = enabled;
+        }
+        Ok(workflow.clone())
+    }
+
+    async fn di_jobs(&self, _service_name: &str, workflow_id: &str, filter: Option<String>, pagination: &Pagination) -> Result<Vec<Job>> {
+        let jobs = self.state().advance_jobs(workflow_id)?;
+        list(jobs, filter, pagination).await
+    }
+
+    fn di_jobs_stream(&self, _service_name: &str, workflow_id: &str, _page_size: Option<u32>) -> BoxStream<'_, Result<Job>> {
+        match self.state().advance_jobs(workflow_id) {
+            Ok(jobs) => stream::iter(jobs.into_iter().map(Ok)).boxed(),
+            Err(error) => stream::once(async { Err(error) }).boxed(),
+        }
+    }
+
+    async fn di_job(&self, _service_name: &str, workflow_id: &str, id: &str) -> Result<Job> {
+        self.state()
+            .advance_jobs(workflow_id)?
+            .into_iter()
+            .find(|job| job.id == id)
+            .ok_or(not_found("job", id))
+    }
+
+    async fn di_job_post(&self, _service_name: &str, workflow_id: &str, _job: &JobPost) -> Result<Job> {
+        let mut state = self.state();
+        let workflow = state.workflow_mut(workflow_id)?;
+        workflow.last_execution_date = Some(Utc::now());
+
+        let jobs = state.jobs_mut(workflow_id)?;
+        if jobs.iter().any(|job| job.ended_at.is_none()) {
+            return Err(conflict(format!("A job is already running for the workflow {}", workflow_id)));
+        }
+        let job = Job {
+            id: Uuid::new_v4().to_string(),
+            status: "PENDING".to_string(),
+            created_at: Utc::now(),
+            started_at: None,
+            ended_at: None,
+        };
+        jobs.push(job.clone());
+        Ok(job)
+    }
+
+    async fn di_job_delete(&self, _service_name: &str, workflow_id: &str, id: &str) -> Result<()> {
+        let mut state = self.state();
+        let job = state
+            .jobs_mut(workflow_id)?
+            .iter_mut()
+            .find(|job| job.id == id)
+            .ok_or(not_found("job", id))?;
+        if job.ended_at.is_none() {
+            job.status = "INTERRUPTING".to_string();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::di::destination::DestinationSpec;
+    use crate::model::di::source::SourceSpec;
+    use crate::model::di::workflow::{JobPost, WorkflowSpec};
+    use crate::model::utils::Pagination;
+    use crate::ovhapi::{DiApi, FakeOVHapiClient, FAKE_DESTINATION_CONNECTOR_ID, FAKE_SOURCE_CONNECTOR_ID};
+
+    const SERVICE_NAME: &str = "service-name";
+
+    #[tokio::test]
+    async fn create_run_delete() {
+        let client = FakeOVHapiClient::new().with_sample_connectors();
+
+        let source_spec = SourceSpec {
+            name: "source".to_string(),
+            connector_id: Some(FAKE_SOURCE_CONNECTOR_ID.to_string()),
+            parameters: vec![],
+        };
+        let source = client.di_source_post(SERVICE_NAME, &source_spec).await.unwrap();
+        assert!(client.di_source_post(SERVICE_NAME, &source_spec).await.is_err());
+
+        let destination_spec = DestinationSpec {
+            name: "destination".to_string(),
+            connector_id: Some(FAKE_DESTINATION_CONNECTOR_ID.to_string()),
+            parameters: vec![],
+        };
+        let destination = client.di_destination_post(SERVICE_NAME, &destination_spec).await.unwrap();
+
+        let workflow_spec = WorkflowSpec {
+            name: "workflow".to_string(),
+            region: "GRA".to_string(),
+            description: None,
+            source_id: source.id.clone(),
+            destination_id: destination.id.clone(),
+            schedule: None,
+            enabled: true,
+        };
+        let workflow = client.di_workflow_post(SERVICE_NAME, &workflow_spec).await.unwrap();
+        assert_eq!(workflow.source_name, Some("source".to_string()));
+
+        let job = client.di_job_post(SERVICE_NAME, &workflow.id, &JobPost::default()).await.unwrap();
+        assert_eq!(job.status, "PENDING");
+        assert!(client.di_job_post(SERVICE_NAME, &workflow.id, &JobPost::default()).await.is_err());
+        assert_eq!(client.di_job(SERVICE_NAME, &workflow.id, &job.id).await.unwrap().status, "RUNNING");
+        assert_eq!(client.di_job(SERVICE_NAME, &workflow.id, &job.id).await.unwrap().status, "DONE");
+
+        assert!(client.di_source_delete(SERVICE_NAME, &source.id).await.is_err());
+        client.di_workflow_delete(SERVICE_NAME, &workflow.id).await.unwrap();
+        client.di_source_delete(SERVICE_NAME, &source.id).await.unwrap();
+        client.di_destination_delete(SERVICE_NAME, &destination.id).await.unwrap();
+
+        assert!(client.di_sources(SERVICE_NAME, None, &Pagination::default()).await.unwrap().is_empty());
+        assert!(client.di_jobs(SERVICE_NAME, &workflow.id, None, &Pagination::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stop_job() {
+        let client = FakeOVHapiClient::new().with_sample_connectors().with_job_final_status("FAILED");
+        let source = client
+            .di_source_post(
+                SERVICE_NAME,
+                &SourceSpec {
+                    name: "source".to_string(),
+                    connector_id: Some(FAKE_SOURCE_CONNECTOR_ID.to_string()),
+                    parameters: vec![],
+                },
+            )
+            .await
+            .unwrap();
+        let destination = client
+            .di_destination_post(
+                SERVICE_NAME,
+                &DestinationSpec {
+                    name: "destination".to_string(),
+                    connector_id: Some(FAKE_DESTINATION_CONNECTOR_ID.to_string()),
+                    parameters: vec![],
+                },
+            )
+            .await
+            .unwrap();
+        let workflow = client
+            .di_workflow_post(
+                SERVICE_NAME,
+                &WorkflowSpec {
+                    name: "workflow".to_string(),
+                    region: "GRA".to_string(),
+                    description: None,
+                    source_id: source.id,
+                    destination_id: destination.id,
+                    schedule: None,
+                    enabled: true,
+                },
+            )
+            .await
+            .unwrap();
+
+        let job = client.di_job_post(SERVICE_NAME, &workflow.id, &JobPost::default()).await.unwrap();
+        client.di_job_delete(SERVICE_NAME, &workflow.id, &job.id).await.unwrap();
+        assert_eq!(client.di_job(SERVICE_NAME, &workflow.id, &job.id).await.unwrap().status, "INTERRUPTED");
+
+        let job = client.di_job_post(SERVICE_NAME, &workflow.id, &JobPost::default()).await.unwrap();
+        client.di_job(SERVICE_NAME, &workflow.id, &job.id).await.unwrap();
+        assert_eq!(client.di_job(SERVICE_NAME, &workflow.id, &job.id).await.unwrap().status, "FAILED");
+    }
+}
@@ -1,9 +1,11 @@
 mod auth_api;
 mod di_api;
+mod fake;
 mod ovhapiv6;
 mod project_api;
 
 pub use auth_api::*;
 pub use di_api::*;
+pub use fake::*;
 pub use ovhapiv6::*;
 pub use project_api::*;
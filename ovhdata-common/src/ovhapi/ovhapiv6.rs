@@ -1,16 +1,26 @@
 extern crate serde;
 extern crate serde_json;
 
-use hyper::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE, USER_AGENT};
-use reqwest::{Client, Method};
+use chrono::Utc;
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use hyper::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE, ETAG, IF_NONE_MATCH, USER_AGENT};
+use reqwest::{Client, Method, Request, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::Value;
 use sha1::{Digest, Sha1};
 use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use crate::api::{Error, RequestWrapper, Result, EMPTY_BODY};
-use crate::utils::http::Url;
+use crate::api::{
+    parse_value, CachedResponse, Cassette, Error, RequestSigner, RequestWrapper, ResponseCache, ResponseWrapper, Result, RetryPolicy, EMPTY_BODY,
+};
+use crate::utils::http::{Url, UrlError};
+use crate::BUG;
 
 // Required headers for auth
 pub const HEADER_OVH_APPLICATION: &str = "X-Ovh-Application";
@@ -18,12 +28,29 @@ pub const HEADER_OVH_TIMESTAMP: &str = "X-Ovh-Timestamp";
 pub const HEADER_OVH_SIGNATURE: &str = "X-Ovh-Signature";
 pub const HEADER_OVH_CONSUMER: &str = "X-Ovh-Consumer";
 
+// Header identifying a request for the OVH support
+pub const HEADER_OVH_QUERY_ID: &str = "X-Ovh-Queryid";
+
+// Error returned by the API when the request timestamp is too far from the server time
+const QUERY_TIME_OUT_ERROR_CODE: &str = "QUERY_TIME_OUT";
+const QUERY_TIME_OUT_MESSAGE: &str = "Query out of time";
+
+// Query parameter used to ask a page size on list calls
+const PAGE_SIZE_QUERY_PARAM: &str = "size";
+
 #[derive(Debug, Clone)]
 pub struct OVHapiV6Client {
     endpoint_url: String,
     application_key: String,
     application_secret: String,
     consumer_key: String,
+    // Delta in seconds between the OVH API server clock and the local clock, shared between clones
+    time_delta: Arc<RwLock<Option<i64>>>,
+    retry_policy: RetryPolicy,
+    // Cassette recording or replaying the requests, shared between clones
+    cassette: Option<Arc<Cassette>>,
+    // On disk cache of the responses read with `get_cached`
+    response_cache: Option<ResponseCache>,
     pub client: Client,
 }
 
@@ -35,10 +62,54 @@ impl OVHapiV6Client {
             application_key,
             application_secret,
             consumer_key,
+            time_delta: Arc::new(RwLock::new(None)),
+            retry_policy: RetryPolicy::default(),
+            cassette: None,
+            response_cache: None,
             client: Client::new(),
         }
     }
 
+    /// Use a previously computed time delta instead of asking the OVH API server time
+    pub fn with_time_delta(self, time_delta: Option<i64>) -> Self {
+        *self.time_delta.write().expect(BUG) = time_delta;
+        self
+    }
+
+    /// Send the requests with the given HTTP client (proxy, certificates, timeouts...)
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Retry transient failures according to the given policy
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Record or replay the requests with the given cassette
+    pub fn with_cassette(mut self, cassette: Option<Cassette>) -> Self {
+        self.cassette = cassette.map(Arc::new);
+        self
+    }
+
+    /// Keep the responses read with `get_cached` in the given cache
+    pub fn with_response_cache(mut self, response_cache: Option<ResponseCache>) -> Self {
+        self.response_cache = response_cache;
+        self
+    }
+
+    /// Cassette recording or replaying the requests, if any
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_deref()
+    }
+
+    /// Current delta in seconds between the OVH API server clock and the local clock, if already known
+    pub fn time_delta(&self) -> Option<i64> {
+        *self.time_delta.read().expect(BUG)
+    }
+
     /// Compute signature for OVH.
     fn build_sig(method: &str, query: &str, body: &str, timestamp: &str, aas: &str, ck: &str) -> String {
         let sep = "+";
@@ -68,14 +139,209 @@ impl OVHapiV6Client {
         prefix + &hash_hexstr
     }
 
-    /// Ask time to OVH API server to compute delta time
-    async fn remote_time(&self) -> Result<u64> {
+    /// Ask time to OVH API server, as a unix timestamp
+    pub async fn server_time(&self) -> Result<u64> {
         let request = self
             .build_request_without_authent(Method::GET, &["auth", "time"], &[], &HeaderMap::new(), EMPTY_BODY)
             .await?;
-        let response = request.send(&self.client, &[]);
-        let time = (response.await?.parse::<u64>().await).unwrap_or(1);
-        Ok(time)
+        let response = request.send(&self.client, &[], &self.retry_policy, self.cassette.as_deref(), None);
+        response.await?.parse::<u64>().await
+    }
+
+    /// Ask time to OVH API server and store the delta with the local clock
+    async fn refresh_time_delta(&self) -> Result<i64> {
+        let remote_time = self.server_time().await? as i64;
+        let time_delta = remote_time - Utc::now().timestamp();
+        info!(time_delta, "OVH API time delta computed");
+        *self.time_delta.write().expect(BUG) = Some(time_delta);
+        Ok(time_delta)
+    }
+
+    /// Compute the request timestamp from the local clock and the time delta
+    async fn timestamp(&self) -> Result<u64> {
+        let time_delta = match self.time_delta() {
+            Some(time_delta) => time_delta,
+            None => self.refresh_time_delta().await?,
+        };
+        Ok((Utc::now().timestamp() + time_delta) as u64)
+    }
+
+    /// Build, sign and send a request
+    /// If the API rejects the request timestamp, the time delta is refreshed and the request is sent once again
+    pub async fn execute<T>(
+        &self,
+        method: Method,
+        path: &[&str],
+        query: &[(String, String)],
+        headers: &HeaderMap,
+        body: Option<&T>,
+    ) -> Result<ResponseWrapper>
+    where
+        T: Serialize,
+    {
+        let url = self.url(path);
+        self.execute_with_full_url(method, &url, query, headers, body, &[]).await
+    }
+
+    /// Read an API path through the response cache, if any
+    /// A fresh response is served without request, an expired one is revalidated with its ETag,
+    /// and still served when the API can't be reached
+    pub async fn get_cached<T: DeserializeOwned>(&self, path: &[&str]) -> Result<T> {
+        let cache = match &self.response_cache {
+            Some(cache) => cache,
+            None => return self.execute(Method::GET, path, &[], &HeaderMap::new(), EMPTY_BODY).await?.parse().await,
+        };
+
+        let cached = cache.get(path);
+        let mut headers = HeaderMap::new();
+        if let Some(cached) = &cached {
+            if cache.is_fresh(cached) {
+                debug!(path = %path.join("/"), "Response served from the cache");
+                return parse_value(cached.body.clone());
+            }
+            if let Some(etag) = cached.etag.as_ref().and_then(|etag| HeaderValue::from_str(etag).ok()) {
+                headers.insert(IF_NONE_MATCH, etag);
+            }
+        }
+
+        let url = self.url(path);
+        let response = match self
+            .execute_with_full_url(Method::GET, &url, &[], &headers, EMPTY_BODY, &[StatusCode::NOT_MODIFIED])
+            .await
+        {
+            Ok(response) => response,
+            Err(Error::Request(error)) if cached.is_some() => {
+                warn!(error = %error, path = %path.join("/"), "OVH API unreachable, expired response served from the cache");
+                return parse_value(cached.expect(BUG).body);
+            }
+            Err(error) => return Err(error),
+        };
+
+        let etag = response.headers().get(ETAG).and_then(|etag| etag.to_str().ok()).map(String::from);
+        // Not modified, the cached response is valid for a new time to live
+        let cached = match (response.status(), cached) {
+            (StatusCode::NOT_MODIFIED, Some(cached)) => CachedResponse {
+                fetched_at: Utc::now(),
+                etag: etag.or(cached.etag),
+                body: cached.body,
+            },
+            _ => CachedResponse {
+                fetched_at: Utc::now(),
+                etag,
+                body: response.parse::<Value>().await?,
+            },
+        };
+        cache.put(path, &cached);
+        parse_value(cached.body)
+    }
+
+    /// Build, sign and send a request to a full url
+    async fn execute_with_full_url<T>(
+        &self,
+        method: Method,
+        url: &Url,
+        query: &[(String, String)],
+        headers: &HeaderMap,
+        body: Option<&T>,
+        allowed_statuses: &[StatusCode],
+    ) -> Result<ResponseWrapper>
+    where
+        T: Serialize,
+    {
+        let request = self.build_signed_request(method.clone(), url, query, headers, body).await?;
+        match request
+            .send(&self.client, allowed_statuses, &self.retry_policy, self.cassette.as_deref(), Some(self))
+            .await
+        {
+            Err(Error::Response(StatusCode::BAD_REQUEST, error))
+                if error.error_code.as_deref() == Some(QUERY_TIME_OUT_ERROR_CODE) || error.message == QUERY_TIME_OUT_MESSAGE =>
+            {
+                info!("Request timestamp rejected by the OVH API, refreshing time delta");
+                self.refresh_time_delta().await?;
+                let request = self.build_signed_request(method, url, query, headers, body).await?;
+                request
+                    .send(&self.client, allowed_statuses, &self.retry_policy, self.cassette.as_deref(), Some(self))
+                    .await
+            }
+            other => other,
+        }
+    }
+
+    /// Build and send a request authenticated by the application key only, without consumer key nor signature
+    pub async fn execute_without_consumer_key<T>(
+        &self,
+        method: Method,
+        path: &[&str],
+        query: &[(String, String)],
+        headers: &HeaderMap,
+        body: Option<&T>,
+    ) -> Result<ResponseWrapper>
+    where
+        T: Serialize,
+    {
+        let mut request = self.build_request_without_authent(method, path, query, headers, body).await?;
+        let headers = request.headers_mut();
+        headers.insert(HEADER_OVH_APPLICATION, HeaderValue::from_str(self.application_key.as_str()).unwrap());
+        headers.insert(ACCEPT, HeaderValue::from_str("application/json; charset=utf-8").unwrap());
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json; charset=utf-8").unwrap());
+        request.send(&self.client, &[], &self.retry_policy, self.cassette.as_deref(), None).await
+    }
+
+    /// Stream the items of a list, following the next page links until the last page
+    pub fn stream_items(&self, path: &[&str], page_size: Option<u32>) -> BoxStream<'_, Result<Value>> {
+        let mut first_page_url = self.url(path);
+        if let Some(page_size) = page_size {
+            first_page_url
+                .0
+                .query_pairs_mut()
+                .append_pair(PAGE_SIZE_QUERY_PARAM, page_size.to_string().as_str());
+        }
+
+        stream::try_unfold(Some(first_page_url), move |page_url| async move {
+            let page_url = match page_url {
+                Some(page_url) => page_url,
+                None => return Ok(None),
+            };
+
+            let response = self
+                .execute_with_full_url(Method::GET, &page_url, &[], &HeaderMap::new(), EMPTY_BODY, &[])
+                .await?;
+            let page = response.parse_page::<Value>().await?;
+            debug!(
+                current_page = page.metadata.current_page,
+                page_count = page.metadata.page_count,
+                total = page.metadata.total,
+                "Page received"
+            );
+
+            let next_page_url = match page.metadata.links.next {
+                Some(next) => Some(self.next_page_url(next.as_str())?),
+                None => None,
+            };
+            Ok(Some((stream::iter(page.items.into_iter().map(Ok)), next_page_url)))
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    /// Url of an API path
+    fn url(&self, path: &[&str]) -> Url {
+        Url::from_str(self.endpoint_url.as_str()).unwrap().with_segment(path)
+    }
+
+    /// Resolve the next page link, either a full url or a path relative to the endpoint
+    fn next_page_url(&self, next: &str) -> Result<Url> {
+        let endpoint = self.url(&[]);
+        let endpoint_path = endpoint.0.path().trim_end_matches('/');
+        let next = match next.starts_with(endpoint_path) || Url::from_str(next).is_ok() {
+            true => next.to_string(),
+            false => format!("{}/{}", endpoint_path, next.trim_start_matches('/')),
+        };
+        endpoint
+            .0
+            .join(next.as_str())
+            .map(Url::from)
+            .map_err(|error| Error::Url(UrlError::ParseError(next, error)))
     }
 
     /// Start a client request with given method
@@ -91,35 +357,61 @@ impl OVHapiV6Client {
     where
         T: Serialize,
     {
-        // compute time delta
-        let computed_time = self.remote_time().await?;
-        let timestamp = computed_time.to_string();
+        let url = self.url(path);
+        self.build_signed_request(method, &url, query, headers, body).await
+    }
+
+    /// Start a signed client request to a full url
+    async fn build_signed_request<T>(
+        &self,
+        method: Method,
+        url: &Url,
+        query: &[(String, String)],
+        headers: &HeaderMap,
+        body: Option<&T>,
+    ) -> Result<RequestWrapper>
+    where
+        T: Serialize,
+    {
+        // compute timestamp from the time delta
+        let timestamp = self.timestamp().await?;
 
         // build the request
-        let url = Url::from_str(self.endpoint_url.as_str()).unwrap().with_segment(path);
-        let result_request_wrapper = self.build_request_with_full_url(method.to_owned(), &url, query, headers, body).await;
+        let result_request_wrapper = self.build_request_with_full_url(method, url, query, headers, body).await;
         let mut request = result_request_wrapper.unwrap();
 
+        let headers = request.headers_mut();
+        headers.insert(ACCEPT, HeaderValue::from_str("application/json; charset=utf-8").unwrap());
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json; charset=utf-8").unwrap());
+        self.sign_request(&mut request, timestamp);
+
+        Ok(request)
+    }
+
+    /// Adds the ovhapiv6 authentication headers, signed with the given timestamp
+    fn sign_request(&self, request: &mut Request, timestamp: u64) {
+        let timestamp = timestamp.to_string();
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).to_string())
+            .unwrap_or_default();
+
         // build the ovhapi signature
         let sign = OVHapiV6Client::build_sig(
-            method.to_owned().as_str(),
+            request.method().as_str(),
             request.url().as_str(),
-            &request.body_str(),
+            &body,
             &timestamp,
             self.application_secret.as_str(),
             self.consumer_key.as_str(),
         );
 
-        // Adds ovhapiv6 authentication headers
         let headers = request.headers_mut();
         headers.insert(HEADER_OVH_APPLICATION, HeaderValue::from_str(self.application_key.as_str()).unwrap());
         headers.insert(HEADER_OVH_CONSUMER, HeaderValue::from_str(self.consumer_key.as_str()).unwrap());
         headers.insert(HEADER_OVH_TIMESTAMP, HeaderValue::from_str(timestamp.as_str()).unwrap());
         headers.insert(HEADER_OVH_SIGNATURE, HeaderValue::from_str(sign.as_str()).unwrap());
-        headers.insert(ACCEPT, HeaderValue::from_str("application/json; charset=utf-8").unwrap());
-        headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json; charset=utf-8").unwrap());
-
-        Ok(request)
     }
 
     async fn build_request_without_authent<T>(
@@ -133,7 +425,7 @@ impl OVHapiV6Client {
     where
         T: Serialize,
     {
-        let url = Url::from_str(self.endpoint_url.as_str()).unwrap().with_segment(path);
+        let url = self.url(path);
         self.build_request_with_full_url(method, &url, query, headers, body).await
     }
 
@@ -168,3 +460,11 @@ impl OVHapiV6Client {
         Ok(request_wrapper)
     }
 }
+
+impl RequestSigner for OVHapiV6Client {
+    /// Sign again a request about to be retried, the time delta is known since its first signature
+    fn sign(&self, request: &mut Request) {
+        let timestamp = Utc::now().timestamp() + self.time_delta().unwrap_or_default();
+        self.sign_request(request, timestamp as u64);
+    }
+}
@@ -1,10 +1,10 @@
 use async_trait::async_trait;
 use futures::future::try_join_all;
 use hyper::HeaderMap;
-use reqwest::Method;
+use reqwest::{Method, StatusCode};
 
-use crate::api::Result;
 use crate::api::EMPTY_BODY;
+use crate::api::{Error, Result};
 use crate::model::project::Project;
 use crate::ovhapi::OVHapiV6Client;
 
@@ -20,23 +20,31 @@ pub trait ProjectApi {
 impl ProjectApi for OVHapiV6Client {
     async fn projects(&self) -> Result<Vec<Project>> {
         let projects = self.project_list().await?;
-        let result = try_join_all(projects.iter().map(|s: &String| self.project(s))).await?;
+        let result = try_join_all(projects.iter().map(|s: &String| async move {
+            match self.project(s).await {
+                // The least privilege credentials can't read the project details, only its ID is known
+                Err(Error::Response(StatusCode::FORBIDDEN, _)) => Ok(Project {
+                    project_id: s.clone(),
+                    description: String::new(),
+                }),
+                other => other,
+            }
+        }))
+        .await?;
         Ok(result)
     }
 
     async fn project(&self, service_name: &str) -> Result<Project> {
-        let request = self
-            .build_request(Method::GET, &["cloud", "project", service_name], &[], &HeaderMap::new(), EMPTY_BODY)
+        let response = self
+            .execute(Method::GET, &["cloud", "project", service_name], &[], &HeaderMap::new(), EMPTY_BODY)
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 
     async fn project_list(&self) -> Result<Vec<String>> {
-        let request = self
-            .build_request(Method::GET, &["cloud", "project"], &[], &HeaderMap::new(), EMPTY_BODY)
+        let response = self
+            .execute(Method::GET, &["cloud", "project"], &[], &HeaderMap::new(), EMPTY_BODY)
             .await?;
-        let response = request.send(&self.client, &[]).await?;
         response.parse().await
     }
 }
@@ -0,0 +1,344 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use thiserror::Error as ThisError;
+
+// Days looked ahead for the next runs, enough for a schedule on the 29th of February
+const MAX_LOOKAHEAD_DAYS: u32 = 366 * 9;
+
+const MONTH_NAMES: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+#[derive(ThisError, Debug, PartialEq)]
+pub enum CronError {
+    #[error("a schedule has 5 fields (minute hour day-of-month month day-of-week), {0} found")]
+    FieldCount(usize),
+    #[error("invalid {0} '{1}': {2}")]
+    InvalidField(&'static str, String, String),
+    #[error("the schedule never runs")]
+    NeverRuns,
+}
+
+/// Allowed values of a cron field
+struct Field {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    // Names of the values, from the minimum one
+    names: &'static [&'static str],
+}
+
+const MINUTE: Field = Field {
+    name: "minute",
+    min: 0,
+    max: 59,
+    names: &[],
+};
+const HOUR: Field = Field {
+    name: "hour",
+    min: 0,
+    max: 23,
+    names: &[],
+};
+const DAY_OF_MONTH: Field = Field {
+    name: "day of month",
+    min: 1,
+    max: 31,
+    names: &[],
+};
+const MONTH: Field = Field {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: &MONTH_NAMES,
+};
+// Sunday is either 0 or 7
+const DAY_OF_WEEK: Field = Field {
+    name: "day of week",
+    min: 0,
+    max: 7,
+    names: &DAY_NAMES,
+};
+
+/// Values matched by a cron field, `*` matches any of them
+#[derive(Debug, Clone, PartialEq)]
+struct Values {
+    values: BTreeSet<u32>,
+    any: bool,
+}
+
+/// Schedule of a workflow in cron format, in UTC: minute hour day-of-month month day-of-week
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Values,
+    hours: Values,
+    days_of_month: Values,
+    months: Values,
+    // From 0 (Sunday) to 6 (Saturday)
+    days_of_week: Values,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, CronError> {
+        let fields = expression.split_whitespace().collect::<Vec<&str>>();
+        if fields.len() != 5 {
+            return Err(CronError::FieldCount(fields.len()));
+        }
+
+        let mut days_of_week = DAY_OF_WEEK.parse_values(fields[4])?;
+        if days_of_week.values.remove(&7) {
+            days_of_week.values.insert(0);
+        }
+        let schedule = Self {
+            expression: fields.join(" "),
+            minutes: MINUTE.parse_values(fields[0])?,
+            hours: HOUR.parse_values(fields[1])?,
+            days_of_month: DAY_OF_MONTH.parse_values(fields[2])?,
+            months: MONTH.parse_values(fields[3])?,
+            days_of_week,
+        };
+
+        // e.g. the 31st of February
+        if schedule.next_runs(Utc::now(), 1).is_empty() {
+            return Err(CronError::NeverRuns);
+        }
+        Ok(schedule)
+    }
+
+    /// Next run times after the given date
+    pub fn next_runs(&self, after: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        let mut runs = vec![];
+        let start = after.with_second(0).and_then(|start| start.with_nanosecond(0)).unwrap_or(after) + Duration::minutes(1);
+        let mut date = start.date_naive();
+
+        for _ in 0..MAX_LOOKAHEAD_DAYS {
+            if self.matches_day(date) {
+                for hour in &self.hours.values {
+                    for minute in &self.minutes.values {
+                        let run = date.and_hms_opt(*hour, *minute, 0).map(|run| Utc.from_utc_datetime(&run));
+                        match run {
+                            Some(run) if run >= start => runs.push(run),
+                            _ => continue,
+                        }
+                        if runs.len() == count {
+                            return runs;
+                        }
+                    }
+                }
+            }
+            date = match date.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        runs
+    }
+
+    /// Like cron, a day matches either the day of month or the day of week when both are set
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !self.months.values.contains(&date.month()) {
+            return false;
+        }
+        let day_of_month = self.days_of_month.values.contains(&date.day());
+        let day_of_week = self.days_of_week.values.contains(&date.weekday().num_days_from_sunday());
+        match (self.days_of_month.any, self.days_of_week.any) {
+            (true, true) => true,
+            (true, false) => day_of_week,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+
+    /// Human readable schedule, e.g. "daily at 04:05 UTC", or the expression itself when it can't be told simply
+    pub fn describe(&self) -> String {
+        let every_day = self.days_of_month.any && self.months.any && self.days_of_week.any;
+        let minute = single(&self.minutes);
+        let hour = single(&self.hours);
+
+        match (minute, hour) {
+            _ if !every_day && (minute.is_none() || hour.is_none()) => self.expression.clone(),
+            (None, _) if self.minutes.any && self.hours.any => "every minute".to_string(),
+            (None, _) if self.hours.any => match step(&self.minutes) {
+                Some(step) => format!("every {} minutes", step),
+                None => self.expression.clone(),
+            },
+            (Some(0), None) if self.hours.any => "hourly".to_string(),
+            (Some(minute), None) if self.hours.any => format!("hourly at minute {}", minute),
+            (Some(minute), None) => match step(&self.hours) {
+                Some(step) => format!("every {} hours at minute {}", step, minute),
+                None => self.expression.clone(),
+            },
+            (Some(minute), Some(hour)) => {
+                let time = format!("at {:02}:{:02} UTC", hour, minute);
+                match (self.days_of_month.any, self.months.any, self.days_of_week.any) {
+                    (true, true, true) => format!("daily {}", time),
+                    (true, true, false) if self.days_of_week.values == (1..=5).collect() => format!("on weekdays {}", time),
+                    (true, true, false) => format!("on {} {}", names(&self.days_of_week, &DAY_NAMES, 0), time),
+                    (false, true, true) => format!("monthly on day {} {}", list(&self.days_of_month), time),
+                    (false, false, true) => format!(
+                        "yearly on {} day {} {}",
+                        names(&self.months, &MONTH_NAMES, 1),
+                        list(&self.days_of_month),
+                        time
+                    ),
+                    _ => self.expression.clone(),
+                }
+            }
+            _ => self.expression.clone(),
+        }
+    }
+}
+
+impl Field {
+    /// Parse a field made of values, ranges and steps separated by commas: `*`, `5`, `1-5`, `*/15`, `MON-FRI`...
+    fn parse_values(&self, text: &str) -> Result<Values, CronError> {
+        let invalid = |reason: String| CronError::InvalidField(self.name, text.to_string(), reason);
+
+        let mut values = BTreeSet::new();
+        for part in text.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step = step.parse::<u32>().ok().filter(|step| *step > 0);
+                    (
+                        range,
+                        Some(step.ok_or(invalid(format!("the step of {} is not a positive number", part)))?),
+                    )
+                }
+                None => (part, None),
+            };
+
+            let (first, last) = match range.split_once('-') {
+                _ if range == "*" => (self.min, self.max),
+                Some((first, last)) => (self.parse_value(first).map_err(&invalid)?, self.parse_value(last).map_err(&invalid)?),
+                // A single value with a step runs from this value to the maximum
+                None if step.is_some() => (self.parse_value(range).map_err(&invalid)?, self.max),
+                None => {
+                    let value = self.parse_value(range).map_err(&invalid)?;
+                    (value, value)
+                }
+            };
+            if first > last {
+                return Err(invalid(format!("the range {} is reversed", range)));
+            }
+            values.extend((first..=last).step_by(step.unwrap_or(1) as usize));
+        }
+
+        Ok(Values { values, any: text == "*" })
+    }
+
+    fn parse_value(&self, text: &str) -> Result<u32, String> {
+        let value = match self.names.iter().position(|name| name.eq_ignore_ascii_case(text)) {
+            Some(position) => position as u32 + self.min,
+            None => text.parse::<u32>().map_err(|_| format!("{} is not a number", text))?,
+        };
+        match value >= self.min && value <= self.max {
+            true => Ok(value),
+            false => Err(format!("{} is not between {} and {}", value, self.min, self.max)),
+        }
+    }
+}
+
+fn single(values: &Values) -> Option<u32> {
+    match values.values.len() {
+        1 => values.values.first().copied(),
+        _ => None,
+    }
+}
+
+/// Step of values evenly spread from 0, like `*/15`
+fn step(values: &Values) -> Option<u32> {
+    let mut iter = values.values.iter();
+    let (first, second) = (iter.next()?, iter.next()?);
+    let step = second - first;
+    let evenly_spread = *first == 0 && values.values.iter().zip(values.values.iter().skip(1)).all(|(a, b)| b - a == step);
+    evenly_spread.then_some(step)
+}
+
+fn list(values: &Values) -> String {
+    values.values.iter().map(u32::to_string).collect::<Vec<String>>().join(", ")
+}
+
+fn names(values: &Values, names: &[&str], min: u32) -> String {
+    values
+        .values
+        .iter()
+        .map(|value| {
+            let name = names[(value - min) as usize];
+            format!("{}{}", &name[..1], name[1..].to_lowercase())
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::utils::cron::{CronError, CronSchedule};
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(CronSchedule::parse("5 4 * *"), Err(CronError::FieldCount(4)));
+        assert_eq!(
+            CronSchedule::parse("60 4 * * *"),
+            Err(CronError::InvalidField(
+                "minute",
+                "60".to_string(),
+                "60 is not between 0 and 59".to_string()
+            ))
+        );
+        assert_eq!(
+            CronSchedule::parse("0 4 * * MON-FOO"),
+            Err(CronError::InvalidField(
+                "day of week",
+                "MON-FOO".to_string(),
+                "FOO is not a number".to_string()
+            ))
+        );
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert_eq!(CronSchedule::parse("0 0 31 2 *"), Err(CronError::NeverRuns));
+    }
+
+    #[test]
+    fn next_runs() {
+        let after = Utc.with_ymd_and_hms(2023, 7, 14, 4, 5, 30).unwrap();
+
+        let schedule = CronSchedule::parse("5 4 * * *").unwrap();
+        assert_eq!(
+            schedule.next_runs(after, 2),
+            vec![
+                Utc.with_ymd_and_hms(2023, 7, 15, 4, 5, 0).unwrap(),
+                Utc.with_ymd_and_hms(2023, 7, 16, 4, 5, 0).unwrap()
+            ]
+        );
+
+        // Day of month or day of week (Sunday as 7)
+        let schedule = CronSchedule::parse("0 12 20 * 7").unwrap();
+        assert_eq!(
+            schedule.next_runs(after, 3),
+            vec![
+                Utc.with_ymd_and_hms(2023, 7, 16, 12, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2023, 7, 20, 12, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2023, 7, 23, 12, 0, 0).unwrap()
+            ]
+        );
+
+        let schedule = CronSchedule::parse("0 0 29 feb *").unwrap();
+        assert_eq!(schedule.next_runs(after, 1), vec![Utc.with_ymd_and_hms(2024, 2, 29, 0, 0, 0).unwrap()]);
+    }
+
+    #[test]
+    fn describe() {
+        let describe = |expression: &str| CronSchedule::parse(expression).unwrap().describe();
+        assert_eq!(describe("5 4 * * *"), "daily at 04:05 UTC");
+        assert_eq!(describe("*/15 * * * *"), "every 15 minutes");
+        assert_eq!(describe("0 * * * *"), "hourly");
+        assert_eq!(describe("30 */6 * * *"), "every 6 hours at minute 30");
+        assert_eq!(describe("0 8 * * MON-FRI"), "on weekdays at 08:00 UTC");
+        assert_eq!(describe("0 8 * * 1,3"), "on Mon, Wed at 08:00 UTC");
+        assert_eq!(describe("0 2 1,15 * *"), "monthly on day 1, 15 at 02:00 UTC");
+        assert_eq!(describe("0 2 1 jan *"), "yearly on Jan day 1 at 02:00 UTC");
+        assert_eq!(describe("5-10 4 * * *"), "5-10 4 * * *");
+    }
+}
@@ -58,7 +58,7 @@ pub fn file_content_or_string(potential_file_path: String) -> String {
 pub fn age(date: &DateTime<Utc>) -> String {
     use chrono::TimeZone;
     let now = Utc.with_ymd_and_hms(2021, 7, 8, 9, 10, 11).unwrap();
-    let age = now - date.clone();
+    let age = now - *date;
     human_duration(&age.num_seconds())
 }
 
@@ -78,11 +78,11 @@ pub fn duration(start: &Option<DateTime<Utc>>, end: &Option<DateTime<Utc>>) -> S
     } else {
         let start_date = start.unwrap();
         if end.is_none() {
-            let duration = now - start_date.clone();
+            let duration = now - start_date;
             human_duration(&duration.num_seconds())
         } else {
             let end_date = end.unwrap();
-            let duration = end_date.clone() - start_date.clone();
+            let duration = end_date - start_date;
             human_duration(&duration.num_seconds())
         }
     }
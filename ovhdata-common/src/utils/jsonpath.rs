@@ -13,8 +13,8 @@ pub enum JsonPathError {
 }
 
 pub fn json_parse_task(json: Value, path: Option<String>) -> Result<Value, JsonPathError> {
-    if path.is_some() {
-        let json_path = JsonPathInst::from_str(path.unwrap().as_str()).map_err(JsonPathError::JsonPath)?;
+    if let Some(path) = path {
+        let json_path = JsonPathInst::from_str(path.as_str()).map_err(JsonPathError::JsonPath)?;
         let finder = JsonPathFinder::new(Box::new(json), Box::new(json_path));
 
         let result = finder.find();
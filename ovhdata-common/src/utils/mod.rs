@@ -1,5 +1,8 @@
+pub mod cron;
 pub mod date;
 pub mod http;
 pub mod jsonpath;
+pub mod reference;
 pub mod serde;
+pub mod snapshot;
 pub mod style;
@@ -0,0 +1,101 @@
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::PathBuf;
+
+use thiserror::Error as ThisError;
+
+pub const ENV_REFERENCE_PREFIX: &str = "@env:";
+pub const FILE_REFERENCE_PREFIX: &str = "@file:";
+pub const STDIN_REFERENCE: &str = "-";
+
+#[derive(ThisError, Debug, PartialEq)]
+pub enum ReferenceError {
+    #[error("the environment variable {0} is not set")]
+    MissingEnv(String),
+    #[error("unable to read the file {0}: {1}")]
+    File(String, String),
+    #[error("unable to read the standard input: {0}")]
+    Stdin(String),
+}
+
+/// Value given by reference, so that secrets stay out of the shell history and of the process list
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueReference {
+    /// `@env:VARIABLE`
+    Env(String),
+    /// `@file:path`
+    File(PathBuf),
+    /// `-`, read from the standard input
+    Stdin,
+}
+
+impl ValueReference {
+    /// Reference given in place of a value, if any
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Some(variable) = value.strip_prefix(ENV_REFERENCE_PREFIX) {
+            return Some(Self::Env(variable.to_string()));
+        }
+        if let Some(path) = value.strip_prefix(FILE_REFERENCE_PREFIX) {
+            return Some(Self::File(PathBuf::from(path)));
+        }
+        (value == STDIN_REFERENCE).then_some(Self::Stdin)
+    }
+
+    /// Value referenced, without the trailing new line of files and standard input
+    pub fn resolve(&self) -> Result<String, ReferenceError> {
+        let value = match self {
+            Self::Env(variable) => return std::env::var(variable).map_err(|_| ReferenceError::MissingEnv(variable.clone())),
+            Self::File(path) => std::fs::read_to_string(path).map_err(|error| ReferenceError::File(path.display().to_string(), error.to_string()))?,
+            Self::Stdin => {
+                let mut value = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut value)
+                    .map_err(|error| ReferenceError::Stdin(error.to_string()))?;
+                value
+            }
+        };
+        Ok(value.trim_end_matches(['\n', '\r']).to_string())
+    }
+}
+
+impl Display for ValueReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Env(variable) => write!(f, "{}{}", ENV_REFERENCE_PREFIX, variable),
+            Self::File(path) => write!(f, "{}{}", FILE_REFERENCE_PREFIX, path.display()),
+            Self::Stdin => write!(f, "{}", STDIN_REFERENCE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::utils::reference::{ReferenceError, ValueReference};
+
+    #[test]
+    fn parse_references() {
+        assert_eq!(ValueReference::parse("@env:PASSWORD"), Some(ValueReference::Env("PASSWORD".to_string())));
+        assert_eq!(
+            ValueReference::parse("@file:./secret.txt"),
+            Some(ValueReference::File(PathBuf::from("./secret.txt")))
+        );
+        assert_eq!(ValueReference::parse("-"), Some(ValueReference::Stdin));
+        assert_eq!(ValueReference::parse("p4ss"), None);
+        assert_eq!(ValueReference::parse("@file:./secret.txt").unwrap().to_string(), "@file:./secret.txt");
+    }
+
+    #[test]
+    fn resolve_references() {
+        let path = std::env::temp_dir().join(format!("ovhdata-reference-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "p4ss\n").unwrap();
+        assert_eq!(ValueReference::File(path.clone()).resolve(), Ok("p4ss".to_string()));
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            ValueReference::Env("OVHDATA_TEST_UNSET_VARIABLE".to_string()).resolve(),
+            Err(ReferenceError::MissingEnv("OVHDATA_TEST_UNSET_VARIABLE".to_string()))
+        );
+    }
+}
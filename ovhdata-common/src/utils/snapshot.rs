@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+// Snapshot names sort in the order they were taken
+const SNAPSHOT_NAME_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+pub const LATEST_SNAPSHOT: &str = "latest";
+
+#[derive(ThisError, Debug)]
+pub enum SnapshotError {
+    #[error("No snapshot taken yet in {0}")]
+    NoSnapshot(PathBuf),
+    #[error("Unknown snapshot {0}, available ones: {1}")]
+    UnknownSnapshot(String, String),
+    #[error("Unable to read the snapshot {0}: {1}")]
+    Read(PathBuf, String),
+    #[error("Unable to write the snapshot {0}: {1}")]
+    Write(PathBuf, String),
+}
+
+/// Content saved at a given time
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct Snapshot<T> {
+    pub taken_at: DateTime<Utc>,
+    pub content: T,
+}
+
+/// Timestamped snapshots kept on disk, one JSON file per snapshot
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Save a new snapshot, named after the time it was taken
+    pub fn save<T: Serialize>(&self, content: &T) -> Result<String, SnapshotError> {
+        let taken_at = Utc::now();
+        let name = taken_at.format(SNAPSHOT_NAME_FORMAT).to_string();
+        let file = self.file(&name);
+
+        let snapshot = Snapshot { taken_at, content };
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&file, serde_json::to_vec_pretty(&snapshot)?))
+            .map_err(|error| SnapshotError::Write(file, error.to_string()))?;
+        Ok(name)
+    }
+
+    /// Names of the snapshots, the oldest first
+    pub fn list(&self) -> Vec<String> {
+        let mut names = std::fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter_map(|entry| entry.file_name().to_str().and_then(|name| name.strip_suffix(".json")).map(str::to_string))
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Load a snapshot by name, `latest` being the last one taken
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Result<(String, Snapshot<T>), SnapshotError> {
+        let names = self.list();
+        let name = match name {
+            LATEST_SNAPSHOT => names.last().ok_or_else(|| SnapshotError::NoSnapshot(self.dir.clone()))?,
+            name => names
+                .iter()
+                .find(|known| *known == name)
+                .ok_or_else(|| SnapshotError::UnknownSnapshot(name.to_string(), names.join(", ")))?,
+        };
+
+        let file = self.file(name);
+        let snapshot = std::fs::read_to_string(&file)
+            .map_err(|error| error.to_string())
+            .and_then(|content| serde_json::from_str::<Snapshot<T>>(&content).map_err(|error| error.to_string()))
+            .map_err(|error| SnapshotError::Read(file, error))?;
+        Ok((name.clone(), snapshot))
+    }
+
+    fn file(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::snapshot::{SnapshotError, SnapshotStore, LATEST_SNAPSHOT};
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir().join(format!("ovhdata-snapshots-{}", uuid::Uuid::new_v4()));
+        let store = SnapshotStore::new(dir.clone());
+        assert!(matches!(store.load::<Vec<String>>(LATEST_SNAPSHOT), Err(SnapshotError::NoSnapshot(_))));
+
+        let first = store.save(&vec!["orders".to_string()]).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let last = store.save(&vec!["orders".to_string(), "users".to_string()]).unwrap();
+        assert_eq!(store.list(), vec![first.clone(), last.clone()]);
+
+        let (name, snapshot) = store.load::<Vec<String>>(LATEST_SNAPSHOT).unwrap();
+        assert_eq!((name, snapshot.content.len()), (last, 2));
+        assert_eq!(store.load::<Vec<String>>(&first).unwrap().1.content, vec!["orders".to_string()]);
+        assert!(matches!(
+            store.load::<Vec<String>>("20200101T000000.000Z"),
+            Err(SnapshotError::UnknownSnapshot(..))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}